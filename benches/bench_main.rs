@@ -9,6 +9,7 @@ mod storages;
 #[allow(dead_code)]
 pub mod components {
 	use enrs::{tl, TL};
+	use std::marker::PhantomData;
 
 	pub struct A(pub u64);
 	pub struct B(pub u64);
@@ -27,6 +28,20 @@ pub mod components {
 	pub struct O(pub u64);
 	pub struct P(pub u64);
 
+	pub struct D0;
+	pub struct D1;
+	pub struct D2;
+	pub struct D3;
+	pub struct D4;
+	pub struct D5;
+	pub struct D6;
+	pub struct D7;
+	pub struct D8;
+	pub struct D9;
+
+	/// Each distinct `Tag` is its own component type, so 3 digits of markers gives 1000 archetypes
+	pub struct Tag<X, Y, Z>(pub PhantomData<(X, Y, Z)>);
+
 	pub type Type4 = TL![
 		&'static mut A,
 		&'static mut B,
@@ -125,6 +140,7 @@ criterion::criterion_main! {
 	storages::entity_table::benchmarks,
 	storages::dense_entity_dynamic_paged_multi_value_table::benchmarks,
 	storages::simple_storages::benchmarks,
+	storages::sparse_typed_paged_map::benchmarks,
 	other_ecs::flecs::benchmarks,
 	other_ecs::legion::benchmarks,
 	other_ecs::shipyard::benchmarks,
@@ -1,7 +1,7 @@
 use crate::components::*;
 use criterion::*;
 use enrs::database::Database;
-use enrs::tables::{DenseEntityDynamicPagedMultiValueTable, EntityTable};
+use enrs::tables::{DenseEntityDynamicPagedMultiValueTable, EntityTable, PageConfig};
 use enrs::{tl, TL};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -15,6 +15,17 @@ fn setup(
 	Database,
 	Rc<RefCell<EntityTable<EntityType>>>,
 	Rc<RefCell<DenseEntityDynamicPagedMultiValueTable<EntityType>>>,
+) {
+	setup_with_page_config(times, PageConfig::default())
+}
+
+fn setup_with_page_config(
+	times: u64,
+	page_config: PageConfig,
+) -> (
+	Database,
+	Rc<RefCell<EntityTable<EntityType>>>,
+	Rc<RefCell<DenseEntityDynamicPagedMultiValueTable<EntityType>>>,
 ) {
 	let mut database = Database::new();
 	let entities_storage = database
@@ -28,12 +39,62 @@ fn setup(
 		.tables
 		.create(
 			"multi",
-			DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+			DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone())
+				.page_config(page_config),
 		)
 		.unwrap();
 	(database, entities_storage, multi_storage)
 }
 
+/// Migrates a whole 8 component archetype to a 9 component one per iteration
+const MIGRATE_COUNT: u64 = 10_000;
+
+fn migrate_setup() -> (
+	Database,
+	Rc<RefCell<EntityTable<EntityType>>>,
+	Rc<RefCell<DenseEntityDynamicPagedMultiValueTable<EntityType>>>,
+	Vec<EntityType>,
+) {
+	let (database, entities_storage, multi_storage) = setup(MIGRATE_COUNT);
+	let entity_vec = {
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let entity_vec: Vec<_> = (0..MIGRATE_COUNT)
+			.map(|_| entities.insert().raw())
+			.collect();
+		let mut inserter = multi.group_insert::<Type8>().unwrap();
+		let mut lock = inserter.lock(&mut multi);
+		for &e in entity_vec.iter() {
+			lock.insert(entities.valid(e).unwrap(), type8_new(e))
+				.unwrap();
+		}
+		entity_vec
+	};
+	(database, entities_storage, multi_storage, entity_vec)
+}
+
+macro_rules! create_archetypes {
+	($MULTI:ident; [$($X:ident)*] $YS:tt $ZS:tt) => {
+		$(create_archetypes!(@y $MULTI; $X; $YS $ZS);)*
+	};
+	(@y $MULTI:ident; $X:ident; [$($Y:ident)*] $ZS:tt) => {
+		$(create_archetypes!(@z $MULTI; $X $Y; $ZS);)*
+	};
+	(@z $MULTI:ident; $X:ident $Y:ident; [$($Z:ident)*]) => {
+		$(
+			black_box($MULTI.group_insert::<TL![&mut Tag<$X, $Y, $Z>, &mut A]>().unwrap());
+		)*
+	};
+}
+
+fn create_500_archetypes(multi: &mut DenseEntityDynamicPagedMultiValueTable<EntityType>) {
+	create_archetypes!(multi;
+		[D0 D1 D2 D3 D4 D5 D6 D7 D8 D9]
+		[D0 D1 D2 D3 D4 D5 D6 D7 D8 D9]
+		[D0 D1 D2 D3 D4]
+	);
+}
+
 macro_rules! delete_benchmark {
 	($GROUP:ident, $COUNT:expr, $TYPE:ty, $NEW:ident) => {
 		$GROUP.bench_function(format!("delete/{}/components-only", $COUNT), move |b| {
@@ -167,6 +228,25 @@ fn benchmark(c: &mut Criterion) {
 			start.elapsed()
 		});
 	});
+	group.bench_function("insert/8/no-create-entity/group-capacity-hint", move |b| {
+		b.iter_custom(|times| {
+			let page_config = PageConfig {
+				group_capacity_hint: times as usize,
+			};
+			let (_database, entities_storage, multi_storage) =
+				setup_with_page_config(times, page_config);
+			let mut entities = entities_storage.borrow_mut();
+			let mut multi = multi_storage.borrow_mut();
+			let entity_vec: Vec<_> = (0..times).map(|_| entities.insert().raw()).collect();
+			let mut inserter = multi.group_insert::<Type8>().unwrap();
+			let mut lock = inserter.lock(&mut multi);
+			let start = Instant::now();
+			for e in entity_vec {
+				let _ = black_box(lock.insert(entities.valid(e).unwrap(), type8_new(e)));
+			}
+			start.elapsed()
+		});
+	});
 	group.bench_function("insert/8/with-create-entity", move |b| {
 		b.iter_custom(|times| {
 			let (_database, entities_storage, multi_storage) = setup(times);
@@ -376,6 +456,82 @@ fn benchmark(c: &mut Criterion) {
 			start.elapsed()
 		});
 	});
+	group.bench_function(
+		format!("transform/8/add-1/migrate-{}/one-by-one", MIGRATE_COUNT),
+		move |b| {
+			b.iter_custom(|times| {
+				let mut elapsed = std::time::Duration::default();
+				for _ in 0..times {
+					let (_database, entities_storage, multi_storage, entity_vec) = migrate_setup();
+					let entities = entities_storage.borrow();
+					let mut multi = multi_storage.borrow_mut();
+					let transform_to = multi.group_insert::<TL![&mut I]>().unwrap();
+					let mut lock = multi.lock().unwrap();
+					let start = Instant::now();
+					for e in entity_vec {
+						let _ = lock.transform::<(), _>(
+							entities.valid(e).unwrap(),
+							&transform_to,
+							tl![I(e)],
+						);
+					}
+					elapsed += start.elapsed();
+				}
+				elapsed
+			});
+		},
+	);
+	group.bench_function(
+		format!("transform/8/add-1/migrate-{}/many", MIGRATE_COUNT),
+		move |b| {
+			b.iter_custom(|times| {
+				let mut elapsed = std::time::Duration::default();
+				for _ in 0..times {
+					let (_database, entities_storage, multi_storage, entity_vec) = migrate_setup();
+					let entities = entities_storage.borrow();
+					let mut multi = multi_storage.borrow_mut();
+					let transform_to = multi.group_insert::<TL![&mut I]>().unwrap();
+					let mut lock = multi.lock().unwrap();
+					let start = Instant::now();
+					let _ = lock.transform_many::<(), _, _>(
+						&transform_to,
+						entity_vec
+							.iter()
+							.map(|&e| (entities.valid(e).unwrap(), tl![I(e)])),
+					);
+					elapsed += start.elapsed();
+				}
+				elapsed
+			});
+		},
+	);
+	group.bench_function("archetypes/500/create", move |b| {
+		b.iter_custom(|times| {
+			let mut elapsed = std::time::Duration::default();
+			for _ in 0..times {
+				let (_database, _entities_storage, multi_storage) = setup(0);
+				let mut multi = multi_storage.borrow_mut();
+				let start = Instant::now();
+				create_500_archetypes(&mut multi);
+				elapsed += start.elapsed();
+			}
+			elapsed
+		});
+	});
+	group.bench_function("archetypes/500/create-reserved", move |b| {
+		b.iter_custom(|times| {
+			let mut elapsed = std::time::Duration::default();
+			for _ in 0..times {
+				let (_database, _entities_storage, multi_storage) = setup(0);
+				let mut multi = multi_storage.borrow_mut();
+				let start = Instant::now();
+				multi.reserve_groups(500);
+				create_500_archetypes(&mut multi);
+				elapsed += start.elapsed();
+			}
+			elapsed
+		});
+	});
 }
 
 criterion_group!(benchmarks, benchmark,);
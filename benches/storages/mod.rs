@@ -1,3 +1,4 @@
 pub mod dense_entity_dynamic_paged_multi_value_table;
 pub mod entity_table;
 pub mod simple_storages;
+pub mod sparse_typed_paged_map;
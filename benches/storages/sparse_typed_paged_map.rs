@@ -0,0 +1,69 @@
+use crate::components::*;
+use criterion::*;
+use enrs::storages::sparse_typed_paged_map::SparseTypedPagedMap;
+use enrs::{tl, TL};
+use std::marker::PhantomData;
+use std::time::Instant;
+
+type EntityType = u64;
+
+macro_rules! create_archetypes {
+	($MAP:ident, $ENTITY:ident; [$($X:ident)*] $YS:tt $ZS:tt) => {
+		$(create_archetypes!(@y $MAP, $ENTITY; $X; $YS $ZS);)*
+	};
+	(@y $MAP:ident, $ENTITY:ident; $X:ident; [$($Y:ident)*] $ZS:tt) => {
+		$(create_archetypes!(@z $MAP, $ENTITY; $X $Y; $ZS);)*
+	};
+	(@z $MAP:ident, $ENTITY:ident; $X:ident $Y:ident; [$($Z:ident)*]) => {
+		$(
+			$MAP.insert::<TL![&mut Tag<$X, $Y, $Z>, &mut A]>($ENTITY, tl![Tag(PhantomData), A($ENTITY)])
+				.unwrap();
+			$ENTITY += 1;
+		)*
+	};
+}
+
+fn create_1000_archetypes(map: &mut SparseTypedPagedMap<EntityType>) {
+	let mut entity: EntityType = 1;
+	create_archetypes!(map, entity;
+		[D0 D1 D2 D3 D4 D5 D6 D7 D8 D9]
+		[D0 D1 D2 D3 D4 D5 D6 D7 D8 D9]
+		[D0 D1 D2 D3 D4 D5 D6 D7 D8 D9]
+	);
+}
+
+fn benchmark(c: &mut Criterion) {
+	let mut group = c.benchmark_group(
+		std::any::type_name::<SparseTypedPagedMap<EntityType>>()
+			.split("::")
+			.last()
+			.unwrap(),
+	);
+	group.bench_function("archetypes/1000/create-with-query", move |b| {
+		b.iter_custom(|times| {
+			let mut duration = std::time::Duration::default();
+			for _ in 0..times {
+				let mut map = SparseTypedPagedMap::<EntityType>::new();
+				// Register the query first so every new archetype has to update its mapping
+				let _query = map.query::<TL![&mut A]>().unwrap();
+				let start = Instant::now();
+				create_1000_archetypes(&mut map);
+				duration += start.elapsed();
+			}
+			duration
+		});
+	});
+	group.bench_function("archetypes/1000/repeated-query", move |b| {
+		b.iter_custom(|times| {
+			let mut map = SparseTypedPagedMap::<EntityType>::new();
+			create_1000_archetypes(&mut map);
+			let start = Instant::now();
+			for _ in 0..times {
+				black_box(map.query::<TL![&mut A]>().unwrap().count());
+			}
+			start.elapsed()
+		});
+	});
+}
+
+criterion_group!(benchmarks, benchmark,);
@@ -6,7 +6,7 @@ use std::sync::atomic::AtomicUsize;
 use indexmap::map::IndexMap;
 use smol_str::SmolStr;
 
-use crate::table::{Table, TableBuilder};
+use crate::table::{Table, TableBuilder, TableHandle};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TableId(usize);
@@ -18,6 +18,10 @@ pub struct DatabaseId(usize);
 pub enum DatabaseErrors {
 	TableNameAlreadyExists(SmolStr),
 	TableDoesNotExistWithName(SmolStr),
+	EntityTableNotInDatabase(SmolStr),
+	TableCannotBeRenamed(SmolStr),
+	TableCannotBeMoved(SmolStr),
+	EntityTableInUse(SmolStr),
 }
 
 impl std::fmt::Display for DatabaseErrors {
@@ -26,6 +30,18 @@ impl std::fmt::Display for DatabaseErrors {
 		match self {
 			TableNameAlreadyExists(name) => write!(f, "Table name `{}` already exists", name),
 			TableDoesNotExistWithName(name) => write!(f, "Table name `{}` does not exist", name),
+			EntityTableNotInDatabase(name) => write!(
+				f,
+				"Entity table `{}` must be in the destination database first",
+				name
+			),
+			TableCannotBeRenamed(name) => write!(f, "Table `{}` does not support renaming", name),
+			TableCannotBeMoved(name) => {
+				write!(f, "Table `{}` does not support moving databases", name)
+			}
+			EntityTableInUse(name) => {
+				write!(f, "Entity table `{}` still has tables keyed by it", name)
+			}
 		}
 	}
 }
@@ -36,24 +52,56 @@ impl std::error::Error for DatabaseErrors {
 		match self {
 			TableNameAlreadyExists(_name) => None,
 			TableDoesNotExistWithName(_name) => None,
+			EntityTableNotInDatabase(_name) => None,
+			TableCannotBeRenamed(_name) => None,
+			TableCannotBeMoved(_name) => None,
+			EntityTableInUse(_name) => None,
 		}
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableEvent {
+	Created,
+	Removed,
+}
+
 // mod private {
 // 	pub(super) trait Sealed {}
 // }
 
 pub struct Tables {
 	database_id: DatabaseId,
-	mapping: IndexMap<SmolStr, Rc<RefCell<dyn Table>>>,
+	/// Indexed by `TableId`, the slot of a removed table is left empty so no `TableId` ever changes.
+	tables: Vec<Option<Rc<RefCell<dyn Table>>>>,
+	mapping: IndexMap<SmolStr, TableId>,
+	on_table_event: Vec<Box<dyn FnMut(TableEvent, TableId, &str, DatabaseId)>>,
 }
 
 impl Tables {
 	fn new(database_id: DatabaseId) -> Self {
 		Self {
 			database_id,
+			tables: Vec::new(),
 			mapping: IndexMap::default(),
+			on_table_event: Vec::new(),
+		}
+	}
+
+	/// Registers `f` to be called after every table is created or removed with its `TableId`, name,
+	/// and the `DatabaseId` of this database.
+	pub fn on_table_event(
+		&mut self,
+		f: Box<dyn FnMut(TableEvent, TableId, &str, DatabaseId)>,
+	) -> usize {
+		self.on_table_event.push(f);
+		self.on_table_event.len() - 1
+	}
+
+	fn fire_table_event(&mut self, event: TableEvent, table_id: TableId, name: &str) {
+		let database_id = self.database_id;
+		for cb in self.on_table_event.iter_mut() {
+			cb(event, table_id, name, database_id);
 		}
 	}
 
@@ -74,15 +122,64 @@ impl Tables {
 		if self.mapping.contains_key(&name) {
 			return Err(DatabaseErrors::TableNameAlreadyExists(name));
 		}
-		let table = table_builder.build(self.database_id, &name, TableId(self.mapping.len()));
+		let table_id = self.next_table_id();
+		let table = table_builder.build(self.database_id, &name, table_id);
 		assert_eq!(table.borrow().get_database_id(), self.database_id);
-		let old_value = self.mapping.insert(name, table.clone());
-		assert!(old_value.is_none());
+		self.insert(name, table.clone());
 		Ok(table)
 	}
 
+	fn next_table_id(&self) -> TableId {
+		TableId(self.tables.len())
+	}
+
+	fn insert(&mut self, name: SmolStr, table: Rc<RefCell<dyn Table>>) {
+		let table_id = self.next_table_id();
+		assert_eq!(table.borrow().table_id(), table_id);
+		let old_value = self.mapping.insert(name.clone(), table_id);
+		assert!(old_value.is_none());
+		self.tables.push(Some(table));
+		self.fire_table_event(TableEvent::Created, table_id, &name);
+	}
+
+	/// Removes the table named `name` from this database and returns it, the `TableId`'s of the
+	/// other tables stay the same and the `TableId` of the removed table is never reused.
+	///
+	/// A table keyed by an `EntityTable` is dropped once the returned handle and every other one
+	/// is, its delete hook on the `EntityTable` is left behind as a no-op.  An `EntityTable` that
+	/// other tables in this database are still keyed by is refused with `EntityTableInUse`.
+	///
+	/// Panics if any other table is currently mutably borrowed.
+	pub fn remove(&mut self, name: &str) -> Result<Rc<RefCell<dyn Table>>, DatabaseErrors> {
+		let table = self.get_by_name(name)?;
+		let in_use = self.iter().any(|other| {
+			!Rc::ptr_eq(other, &table)
+				&& other
+					.borrow()
+					.entity_table()
+					.map_or(false, |entity_table| Rc::ptr_eq(&entity_table, &table))
+		});
+		if in_use {
+			return Err(DatabaseErrors::EntityTableInUse(name.into()));
+		}
+		Ok(self.take(name).expect("table was just looked up by name"))
+	}
+
+	fn take(&mut self, name: &str) -> Option<Rc<RefCell<dyn Table>>> {
+		let (name, table_id) = self.mapping.shift_remove_entry(name)?;
+		let table = self.tables[table_id.0]
+			.take()
+			.expect("table mapping is in an invalid state with Tables");
+		self.fire_table_event(TableEvent::Removed, table_id, &name);
+		Some(table)
+	}
+
+	fn iter(&self) -> impl Iterator<Item = &Rc<RefCell<dyn Table>>> {
+		self.tables.iter().flatten()
+	}
+
 	pub fn get_by_id(&self, id: TableId) -> Rc<RefCell<dyn Table>> {
-		if let Some((_name, table)) = self.mapping.get_index(id.0) {
+		if let Some(Some(table)) = self.tables.get(id.0) {
 			table.clone()
 		} else {
 			panic!("passed in an invalid TableId to a Database, this signifies an fatal programming error as a TableId from one Database should not be used on another Database")
@@ -90,13 +187,40 @@ impl Tables {
 	}
 
 	pub fn get_by_name(&self, name: &str) -> Result<Rc<RefCell<dyn Table>>, DatabaseErrors> {
-		if let Some(table) = self.mapping.get(name) {
-			Ok(table.clone())
+		if let Some(table_id) = self.mapping.get(name) {
+			Ok(self.get_by_id(*table_id))
 		} else {
 			Err(DatabaseErrors::TableDoesNotExistWithName(name.into()))
 		}
 	}
 
+	/// Returns a `TableHandle` to the table named `name` that does not keep it alive.
+	pub fn weak_handle(&self, name: &str) -> Result<TableHandle, DatabaseErrors> {
+		self.get_by_name(name).map(|table| TableHandle::new(&table))
+	}
+
+	/// Renames the table, its `TableId` stays the same.
+	pub fn rename(
+		&mut self,
+		name: &str,
+		new_name: impl Into<SmolStr>,
+	) -> Result<(), DatabaseErrors> {
+		let new_name: SmolStr = new_name.into();
+		if self.mapping.contains_key(&new_name) {
+			return Err(DatabaseErrors::TableNameAlreadyExists(new_name));
+		}
+		let table = self.get_by_name(name)?;
+		if !table.borrow_mut().rename(&new_name) {
+			return Err(DatabaseErrors::TableCannotBeRenamed(name.into()));
+		}
+		let table_id = self
+			.mapping
+			.shift_remove(name)
+			.expect("table was just looked up by name");
+		self.mapping.insert(new_name, table_id);
+		Ok(())
+	}
+
 	// pub fn delete<T: TableCastable, TR: DerefMut<Target = T>>(
 	// 	&mut self,
 	// 	mut table: TR,
@@ -132,6 +256,65 @@ impl Database {
 	pub fn database_id(&self) -> DatabaseId {
 		self.uid
 	}
+
+	/// Moves the table named `name` into the `into` database, re-stamping its `DatabaseId` and
+	/// `TableId`, this fires a `Removed` event on this database and a `Created` event on `into`.
+	///
+	/// Tables keyed by an `EntityTable` keep their delete hook registered on that same `EntityTable`,
+	/// so it must have already been moved into `into`, otherwise this returns an
+	/// `EntityTableNotInDatabase` error.  The `TableId`'s of the other tables in this database stay
+	/// the same.
+	pub fn move_table(
+		&mut self,
+		name: &str,
+		into: &mut Database,
+	) -> Result<Rc<RefCell<dyn Table>>, DatabaseErrors> {
+		let table = self.tables.get_by_name(name)?;
+		if into.tables.mapping.contains_key(name) {
+			return Err(DatabaseErrors::TableNameAlreadyExists(name.into()));
+		}
+		if let Some(entity_table) = table.borrow().entity_table() {
+			let entity_table_name: SmolStr = entity_table.borrow().table_name().into();
+			let moved = into
+				.tables
+				.get_by_name(&entity_table_name)
+				.map_or(false, |moved| Rc::ptr_eq(&moved, &entity_table));
+			if !moved {
+				return Err(DatabaseErrors::EntityTableNotInDatabase(entity_table_name));
+			}
+		}
+		if !table
+			.borrow_mut()
+			.rehome(into.uid, into.tables.next_table_id())
+		{
+			return Err(DatabaseErrors::TableCannotBeMoved(name.into()));
+		}
+		let table = self
+			.tables
+			.take(name)
+			.expect("table was just looked up by name");
+		into.tables.insert(name.into(), table.clone());
+		Ok(table)
+	}
+
+	/// Clears every row from every table in this database, the tables themselves stay registered.
+	///
+	/// Panics if any table is currently borrowed.
+	pub fn clear_all_tables(&self) {
+		for table in self.tables.iter() {
+			table.borrow_mut().clear_table();
+		}
+	}
+
+	/// Deletes the entities queued with `EntityTable::defer_delete` in every table of this database,
+	/// running the delete hooks of every table keyed by them in one pass.
+	///
+	/// Panics if any table is currently borrowed.
+	pub fn flush_deletions(&self) {
+		for table in self.tables.iter() {
+			table.borrow_mut().flush_deletions();
+		}
+	}
 }
 
 #[cfg(test)]
@@ -140,6 +323,8 @@ mod tests {
 	use crate::tables::dense_entity_value_table::DenseEntityValueTable;
 	use crate::tables::entity_table::EntityTable;
 	use crate::tables::vec_entity_value_table::VecEntityValueTable;
+	use crate::utils::secondary_entity_index::SecondaryEntityIndexErrors;
+	use std::rc::Weak;
 
 	#[test]
 	fn initialize() {
@@ -173,6 +358,374 @@ mod tests {
 		assert_eq!(entities_by_name.borrow().table_id(), entities_table_id);
 	}
 
+	#[test]
+	fn move_table() {
+		let mut source = Database::new();
+		let mut destination = Database::new();
+		let events = Rc::new(RefCell::new(Vec::new()));
+		for database in [&mut source, &mut destination].iter_mut() {
+			let events_cb = events.clone();
+			database
+				.tables
+				.on_table_event(Box::new(move |event, table_id, name, database_id| {
+					events_cb.borrow_mut().push((
+						event,
+						table_id,
+						SmolStr::from(name),
+						database_id,
+					));
+				}));
+		}
+		let entities_storage = source
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ints_storage = source
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let entity = entities_storage.borrow_mut().insert().raw();
+		ints_storage
+			.borrow_mut()
+			.insert(entities_storage.borrow().valid(entity).unwrap(), 42)
+			.unwrap();
+		assert_eq!(
+			source.move_table("ints", &mut destination).err(),
+			Some(DatabaseErrors::EntityTableNotInDatabase("entities".into()))
+		);
+		source.move_table("entities", &mut destination).unwrap();
+		// The table left behind keeps its `TableId`
+		assert_eq!(
+			source.tables.get_by_id(TableId(1)).borrow().table_name(),
+			"ints"
+		);
+		assert_eq!(ints_storage.borrow().table_id(), TableId(1));
+		source.move_table("ints", &mut destination).unwrap();
+		assert!(source.tables.is_empty());
+		assert_eq!(
+			source.tables.get_by_name("ints").err(),
+			Some(DatabaseErrors::TableDoesNotExistWithName("ints".into()))
+		);
+		let ints = destination.tables.get_by_name("ints").unwrap();
+		assert_eq!(ints.borrow().get_database_id(), destination.database_id());
+		assert_eq!(ints.borrow().table_id(), TableId(1));
+		assert!(Rc::ptr_eq(
+			&destination.tables.get_by_id(TableId(1)),
+			&(ints_storage.clone() as Rc<RefCell<dyn Table>>)
+		));
+		let (source_id, destination_id) = (source.database_id(), destination.database_id());
+		assert_eq!(
+			events.borrow()[2..],
+			[
+				(
+					TableEvent::Removed,
+					TableId(0),
+					"entities".into(),
+					source_id
+				),
+				(
+					TableEvent::Created,
+					TableId(0),
+					"entities".into(),
+					destination_id
+				),
+				(TableEvent::Removed, TableId(1), "ints".into(), source_id),
+				(
+					TableEvent::Created,
+					TableId(1),
+					"ints".into(),
+					destination_id
+				),
+			]
+		);
+		// The delete hook still fires from the moved entity table
+		entities_storage.borrow_mut().delete(entity).unwrap();
+		assert!(!ints_storage.borrow().contains(entity));
+	}
+
+	#[test]
+	fn table_events() {
+		let mut database = Database::new();
+		let expected_database_id = database.database_id();
+		let events = Rc::new(RefCell::new(Vec::new()));
+		let events_cb = events.clone();
+		database
+			.tables
+			.on_table_event(Box::new(move |event, table_id, name, database_id| {
+				assert_eq!(database_id, expected_database_id);
+				events_cb
+					.borrow_mut()
+					.push((event, table_id, SmolStr::from(name)));
+			}));
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ints_storage = database
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let shorts_storage = database
+			.tables
+			.create(
+				"shorts",
+				VecEntityValueTable::<u64, i16>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		assert_eq!(
+			database.tables.remove("entities").err(),
+			Some(DatabaseErrors::EntityTableInUse("entities".into()))
+		);
+		let removed = database.tables.remove("ints").unwrap();
+		assert!(Rc::ptr_eq(
+			&removed,
+			&(ints_storage.clone() as Rc<RefCell<dyn Table>>)
+		));
+		assert_eq!(
+			database.tables.remove("ints").err(),
+			Some(DatabaseErrors::TableDoesNotExistWithName("ints".into()))
+		);
+		assert_eq!(
+			*events.borrow(),
+			vec![
+				(TableEvent::Created, TableId(0), "entities".into()),
+				(TableEvent::Created, TableId(1), "ints".into()),
+				(TableEvent::Created, TableId(2), "shorts".into()),
+				(TableEvent::Removed, TableId(1), "ints".into()),
+			]
+		);
+		// The other tables keep their `TableId` and the removed one is not reused
+		let shorts = database.tables.get_by_name("shorts").unwrap();
+		assert_eq!(shorts.borrow().table_id(), TableId(2));
+		assert!(Rc::ptr_eq(&database.tables.get_by_id(TableId(2)), &shorts));
+		assert_eq!(database.tables.len(), 2);
+		let ints_storage = database
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		assert_eq!(ints_storage.borrow().table_id(), TableId(3));
+		// Once nothing is keyed by it anymore the entity table can be removed too
+		database.tables.remove("ints").unwrap();
+		database.tables.remove("shorts").unwrap();
+		drop((shorts, shorts_storage));
+		database.tables.remove("entities").unwrap();
+		assert!(database.tables.is_empty());
+	}
+
+	#[test]
+	fn rename_table() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		database.tables.rename("entities", "renamed").unwrap();
+		assert_eq!(entities_storage.borrow().table_name(), "renamed");
+		assert!(database.tables.get_by_name("entities").is_err());
+		assert_eq!(
+			database
+				.tables
+				.get_by_name("renamed")
+				.unwrap()
+				.borrow()
+				.table_id(),
+			entities_storage.borrow().table_id()
+		);
+	}
+
+	/// A table that keeps the defaults of `Table`, so it can be neither renamed nor moved.
+	struct FixedTable {
+		this: Weak<RefCell<FixedTable>>,
+		database_id: DatabaseId,
+		table_name: SmolStr,
+		table_id: TableId,
+	}
+
+	struct FixedTableBuilder;
+
+	impl TableBuilder for FixedTableBuilder {
+		type Table = FixedTable;
+
+		fn build(
+			self,
+			database_id: DatabaseId,
+			table_name: &str,
+			table_id: TableId,
+		) -> Rc<RefCell<FixedTable>> {
+			let this = Rc::new(RefCell::new(FixedTable {
+				this: Weak::new(),
+				database_id,
+				table_name: table_name.into(),
+				table_id,
+			}));
+			this.borrow_mut().this = Rc::downgrade(&this);
+			this
+		}
+	}
+
+	impl Table for FixedTable {
+		fn as_any(&self) -> &dyn std::any::Any {
+			self
+		}
+
+		fn get_strong(&self) -> Rc<RefCell<dyn Table>> {
+			self.this.upgrade().unwrap()
+		}
+
+		fn get_database_id(&self) -> DatabaseId {
+			self.database_id
+		}
+
+		fn table_name(&self) -> &str {
+			&self.table_name
+		}
+
+		fn table_id(&self) -> TableId {
+			self.table_id
+		}
+	}
+
+	#[test]
+	fn fixed_table() {
+		let mut database = Database::new();
+		let mut destination = Database::new();
+		let fixed = database.tables.create("fixed", FixedTableBuilder).unwrap();
+		assert_eq!(
+			database.tables.rename("fixed", "renamed").err(),
+			Some(DatabaseErrors::TableCannotBeRenamed("fixed".into()))
+		);
+		assert_eq!(
+			database.move_table("fixed", &mut destination).err(),
+			Some(DatabaseErrors::TableCannotBeMoved("fixed".into()))
+		);
+		assert_eq!(fixed.borrow().table_name(), "fixed");
+		assert_eq!(fixed.borrow().get_database_id(), database.database_id());
+		assert!(database.tables.get_by_name("fixed").is_ok());
+		assert!(destination.tables.is_empty());
+	}
+
+	#[test]
+	fn clear_all_tables() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ints_storage = database
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let shorts_storage = database
+			.tables
+			.create(
+				"shorts",
+				VecEntityValueTable::<u64, i16>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let entity1 = entities_storage.borrow_mut().insert().raw();
+		let entity2 = entities_storage.borrow_mut().insert().raw();
+		{
+			let entities = entities_storage.borrow();
+			let mut ints = ints_storage.borrow_mut();
+			let mut shorts = shorts_storage.borrow_mut();
+			ints.insert(entities.valid(entity1).unwrap(), 1).unwrap();
+			ints.insert(entities.valid(entity2).unwrap(), 2).unwrap();
+			shorts.insert(entities.valid(entity1).unwrap(), 1).unwrap();
+		}
+		// Through the trait object of a single table
+		database
+			.tables
+			.get_by_name("ints")
+			.unwrap()
+			.borrow_mut()
+			.clear_table();
+		assert!(ints_storage.borrow().is_empty());
+		assert!(!ints_storage.borrow().contains(entity1));
+		assert_eq!(shorts_storage.borrow().len(), 1);
+		database.clear_all_tables();
+		assert!(shorts_storage.borrow().is_empty());
+		assert!(!shorts_storage.borrow().contains(entity1));
+		// Entities are not rows of the value tables so they are still alive and can be re-added
+		let entities = entities_storage.borrow();
+		ints_storage
+			.borrow_mut()
+			.insert(entities.valid(entity2).unwrap(), 3)
+			.unwrap();
+		assert_eq!(ints_storage.borrow().len(), 1);
+	}
+
+	#[test]
+	fn flush_deletions() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ints_storage = database
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let shorts_storage = database
+			.tables
+			.create(
+				"shorts",
+				VecEntityValueTable::<u64, i16>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let entity1 = entities_storage.borrow_mut().insert().raw();
+		let entity2 = entities_storage.borrow_mut().insert().raw();
+		{
+			let mut entities = entities_storage.borrow_mut();
+			let mut ints = ints_storage.borrow_mut();
+			let mut shorts = shorts_storage.borrow_mut();
+			ints.insert(entities.valid(entity1).unwrap(), 1).unwrap();
+			ints.insert(entities.valid(entity2).unwrap(), 2).unwrap();
+			shorts.insert(entities.valid(entity1).unwrap(), 1).unwrap();
+			// Safe while the value tables are locked, as they would be mid-iteration
+			entities.defer_delete(entity1).unwrap();
+			entities.defer_delete(entity1).unwrap();
+			assert!(entities.contains(entity1));
+			assert!(ints.contains(entity1));
+			assert!(shorts.contains(entity1));
+		}
+		database.flush_deletions();
+		assert!(!entities_storage.borrow().contains(entity1));
+		assert!(entities_storage.borrow().contains(entity2));
+		assert!(!ints_storage.borrow().contains(entity1));
+		assert!(ints_storage.borrow().contains(entity2));
+		assert!(shorts_storage.borrow().is_empty());
+		// The queue was emptied by the flush
+		assert_eq!(entities_storage.borrow_mut().flush_deletions(), 0);
+		let mut entities = entities_storage.borrow_mut();
+		assert_eq!(
+			entities.defer_delete(entity1),
+			Err(SecondaryEntityIndexErrors::IndexDoesNotExist(entity1))
+		);
+		let entity3 = entities.insert().raw();
+		entities.defer_delete(entity2).unwrap();
+		entities.defer_delete(entity3).unwrap();
+		entities.defer_delete(entity2).unwrap();
+		// Deleted since it was queued so the flush skips it
+		entities.delete(entity3).unwrap();
+		assert_eq!(entities.flush_deletions(), 1);
+		assert!(!entities.contains(entity2));
+	}
+
 	#[test]
 	fn remove_rows() {
 		let mut database = Database::new();
@@ -17,16 +17,60 @@
 /// assert_eq!(e.version(), 1);
 /// assert_eq!(e.0, 1048592);
 /// ```
+///
+/// An entity type that does not track versions can leave out the items that have defaults:
+///
+/// ```
+/// # use enrs::entity::Entity;
+/// #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+/// struct Unversioned(usize);
+/// impl Entity for Unversioned {
+/// 	type StorageType = usize;
+/// 	type VersionType = ();
+/// 	fn new(id: usize) -> Self {
+/// 		Unversioned(id)
+/// 	}
+/// 	fn is_null(self) -> bool {
+/// 		self.0 == 0
+/// 	}
+/// 	fn idx(self) -> usize {
+/// 		self.0
+/// 	}
+/// 	fn set_idx(&mut self, idx: usize) -> &mut Self {
+/// 		self.0 = idx;
+/// 		self
+/// 	}
+/// 	fn version(self) -> Self::VersionType {}
+/// 	fn bump_version_with_idx(&mut self, idx: usize) {
+/// 		self.0 = idx;
+/// 	}
+/// }
+/// assert!(Unversioned::invalid().is_null());
+/// assert_eq!(Unversioned::new(42).version_as_usize(), 0);
+/// assert_eq!(Unversioned::try_from_idx_version(42, 0), Some(Unversioned(42)));
+/// assert_eq!(Unversioned::try_from_idx_version(42, 1), None);
+/// ```
 pub trait Entity: 'static + PartialEq + Copy + Ord + Default + std::fmt::Debug {
 	/// The actual container type of this entity date, it should be Copy, and thus cheap to Copy.
 	type StorageType;
 
 	/// The type returned to hold the version, smaller than the StorageType in general.
 	type VersionType;
+	/// The largest index this entity type is able to hold, unbounded unless overridden.
+	const MAX_IDX: usize = usize::MAX;
 	/// Constructs an Entity Handle using the given ID and a 0 version
 	fn new(id: usize) -> Self;
-	/// Return true if this entity is index 0
+	/// The marker for no entity, its index is reserved and is never given to a live entity, the
+	/// `Default` entity of index `0` unless overridden
+	fn invalid() -> Self {
+		Self::default()
+	}
+	/// Return true if this entity has the index of the `invalid` entity, whatever its version
 	fn is_null(self) -> bool;
+	/// Return true if this entity does not have the index of the `invalid` entity
+	fn is_valid(self) -> bool {
+		!self.is_null()
+	}
 	//	fn id(self) -> Self::StorageType;
 	/// Returns the index of this entity
 	fn idx(self) -> usize;
@@ -36,53 +80,223 @@ pub trait Entity: 'static + PartialEq + Copy + Ord + Default + std::fmt::Debug {
 	fn version(self) -> Self::VersionType;
 	/// Sets the index as well as increments the version in a single call
 	fn bump_version_with_idx(&mut self, idx: usize);
+	/// Returns the version of this entity as a `usize`, always `0` unless overridden, as for an
+	/// entity type that does not track versions
+	fn version_as_usize(self) -> usize {
+		0
+	}
+	/// Constructs an Entity Handle with the given index and version, or `None` if either does not fit,
+	/// only version `0` fits unless overridden along with `version_as_usize`
+	///
+	/// ```
+	/// # use enrs::entity::Entity;
+	/// let entity: u64 = 0x00000003_0000002A;
+	/// assert_eq!(entity.idx(), 42);
+	/// assert_eq!(entity.version_as_usize(), 3);
+	/// assert_eq!(u64::try_from_idx_version(42, 3), Some(entity));
+	/// assert_eq!(u16::try_from_idx_version(4096, 0), None);
+	/// assert_eq!(u16::try_from_idx_version(42, 16), None);
+	/// ```
+	fn try_from_idx_version(idx: usize, version: usize) -> Option<Self> {
+		if idx > Self::MAX_IDX || version != 0 {
+			return None;
+		}
+		Some(Self::new(idx))
+	}
+
+	/// Converts this entity into a wider entity type, re-packing the index and version into its
+	/// layout, returns `None` if either does not fit.
+	///
+	/// ```
+	/// # use enrs::entity::Entity;
+	/// let mut e = 42u16;
+	/// e.bump_version_with_idx(4095);
+	/// let wide: u32 = e.try_widen().unwrap();
+	/// assert_eq!(wide.idx(), 4095);
+	/// assert_eq!(wide.version(), 1);
+	/// assert_eq!(wide.try_narrow::<u16>(), Some(e));
+	/// assert_eq!(0u16.try_widen::<u64>(), Some(0u64));
+	/// ```
+	fn try_widen<Wider: Entity>(self) -> Option<Wider> {
+		Wider::try_from_idx_version(self.idx(), self.version_as_usize())
+	}
+
+	/// Converts this entity into a narrower entity type, re-packing the index and version into its
+	/// layout, returns `None` if either does not fit.
+	///
+	/// ```
+	/// # use enrs::entity::Entity;
+	/// assert_eq!(4095u32.try_narrow::<u16>(), Some(4095u16));
+	/// assert_eq!(4096u32.try_narrow::<u16>(), None);
+	/// let mut e = 1u32;
+	/// for _ in 0..16 {
+	/// 	e.bump_version_with_idx(1);
+	/// }
+	/// assert_eq!(e.try_narrow::<u16>(), None);
+	/// ```
+	fn try_narrow<Narrower: Entity>(self) -> Option<Narrower> {
+		Narrower::try_from_idx_version(self.idx(), self.version_as_usize())
+	}
 }
 
+/// Implements `Entity` on an unsigned integer, or on a tuple wrapper of one as `Wrapper(u32)`.
+///
+/// The invalid marker defaults to `0`, otherwise pass it before the doc string.  Its index bits are
+/// reserved and never allocated to a live entity, so it must either have an index of `0` or of the
+/// full index mask, in the latter case `MAX_IDX` is one less than the index mask.
+///
+/// ```
+/// # use enrs::{unsigned_integral_entity, entity::Entity};
+/// #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+/// struct Wrapper(u16);
+/// unsigned_integral_entity!(Wrapper(u16), u8, 0x0_FFF, 0xF_000, 12, u16::MAX, "Max invalid");
+/// assert_eq!(Wrapper::MAX_IDX, 4094);
+/// assert!(Wrapper::invalid().is_null());
+/// assert!(Wrapper::new(0).is_valid());
+/// assert!(!Wrapper::new(4095).is_valid());
+/// ```
 #[macro_export]
 macro_rules! unsigned_integral_entity {
 	($INT:ident, $INT_VERSION:ident, $INDEX_MASK:literal, $VERSION_MASK:literal, $SHIFT_BITS:literal, $DOC:literal) => {
+		enrs::unsigned_integral_entity!(
+			$INT,
+			$INT_VERSION,
+			$INDEX_MASK,
+			$VERSION_MASK,
+			$SHIFT_BITS,
+			0,
+			$DOC
+		);
+	};
+	($SELF:ident($INT:ident), $INT_VERSION:ident, $INDEX_MASK:literal, $VERSION_MASK:literal, $SHIFT_BITS:literal, $INVALID:expr, $DOC:literal) => {
+		impl From<$INT> for $SELF {
+			fn from(raw: $INT) -> Self {
+				$SELF(raw)
+			}
+		}
+
+		impl From<$SELF> for $INT {
+			fn from(entity: $SELF) -> Self {
+				entity.0
+			}
+		}
+
+		enrs::unsigned_integral_entity!(
+			@impl $SELF,
+			$INT,
+			$INT_VERSION,
+			$INDEX_MASK,
+			$VERSION_MASK,
+			$SHIFT_BITS,
+			$INVALID,
+			$DOC
+		);
+	};
+	($INT:ident, $INT_VERSION:ident, $INDEX_MASK:literal, $VERSION_MASK:literal, $SHIFT_BITS:literal, $INVALID:expr, $DOC:literal) => {
+		enrs::unsigned_integral_entity!(
+			@impl $INT,
+			$INT,
+			$INT_VERSION,
+			$INDEX_MASK,
+			$VERSION_MASK,
+			$SHIFT_BITS,
+			$INVALID,
+			$DOC
+		);
+	};
+	(@impl $SELF:ident, $INT:ident, $INT_VERSION:ident, $INDEX_MASK:literal, $VERSION_MASK:literal, $SHIFT_BITS:literal, $INVALID:expr, $DOC:literal) => {
 		#[doc=$DOC]
-		impl enrs::entity::Entity for $INT {
+		// The `From`/`Into` calls and casts are identities when `$SELF` is the integer itself
+		#[allow(clippy::useless_conversion, clippy::unnecessary_cast)]
+		impl enrs::entity::Entity for $SELF {
 			type StorageType = $INT;
 			type VersionType = $INT_VERSION;
+			const MAX_IDX: usize = $INDEX_MASK
+				- ((($INVALID as $INT & $INDEX_MASK) as usize == $INDEX_MASK) as usize);
 
 			fn new(idx: usize) -> Self {
 				#[cfg(not(enrs_disable_asserts))]
 				assert!(idx <= $INDEX_MASK);
-				idx as Self::StorageType
+				Self::from(idx as Self::StorageType)
+			}
+
+			fn invalid() -> Self {
+				Self::from($INVALID as Self::StorageType)
 			}
 
 			#[allow(clippy::verbose_bit_mask)]
 			fn is_null(self) -> bool {
-				(self & $INDEX_MASK) == 0
+				let raw: Self::StorageType = self.into();
+				(raw & $INDEX_MASK) == ($INVALID as Self::StorageType & $INDEX_MASK)
 			}
 
 			fn idx(self) -> usize {
-				(self & $INDEX_MASK) as usize
+				let raw: Self::StorageType = self.into();
+				(raw & $INDEX_MASK) as usize
 			}
 
 			fn set_idx(&mut self, idx: usize) -> &mut Self {
 				#[cfg(not(enrs_disable_asserts))]
 				assert!(idx <= $INDEX_MASK);
-				*self = (*self & $VERSION_MASK) | (idx as Self::StorageType);
+				let raw: Self::StorageType = (*self).into();
+				*self = Self::from((raw & $VERSION_MASK) | (idx as Self::StorageType));
 				self
 			}
 
 			fn version(self) -> Self::VersionType {
-				(self & $VERSION_MASK).wrapping_shr($SHIFT_BITS) as Self::VersionType
+				let raw: Self::StorageType = self.into();
+				(raw & $VERSION_MASK).wrapping_shr($SHIFT_BITS) as Self::VersionType
 			}
 
 			fn bump_version_with_idx(&mut self, idx: usize) {
 				#[cfg(not(enrs_disable_asserts))]
 				assert!(idx <= $INDEX_MASK);
-				*self = ((*self & $VERSION_MASK).wrapping_shr($SHIFT_BITS) + 1)
-					.wrapping_shl($SHIFT_BITS)
-					+ (idx as Self::StorageType);
+				let raw: Self::StorageType = (*self).into();
+				*self = Self::from(
+					((raw & $VERSION_MASK).wrapping_shr($SHIFT_BITS) + 1).wrapping_shl($SHIFT_BITS)
+						+ (idx as Self::StorageType),
+				);
+			}
+
+			fn version_as_usize(self) -> usize {
+				let raw: Self::StorageType = self.into();
+				(raw & $VERSION_MASK).wrapping_shr($SHIFT_BITS) as usize
+			}
+
+			fn try_from_idx_version(idx: usize, version: usize) -> Option<Self> {
+				let max_version = ($VERSION_MASK as Self::StorageType).wrapping_shr($SHIFT_BITS);
+				if idx > $INDEX_MASK || version > max_version as usize {
+					None
+				} else {
+					Some(Self::from(
+						(version as Self::StorageType).wrapping_shl($SHIFT_BITS)
+							| (idx as Self::StorageType),
+					))
+				}
 			}
 		}
 	};
 }
 
+/// `u32` Entity that uses `u32::MAX` as its invalid marker instead of `0`, for interop with formats
+/// that reserve the maximum value as the null entity.
+///
+/// ```
+/// # use enrs::entity::{Entity, MaxInvalidU32};
+/// assert_eq!(MaxInvalidU32::invalid(), MaxInvalidU32(u32::MAX));
+/// assert_eq!(MaxInvalidU32::MAX_IDX, 1048574);
+/// assert!(MaxInvalidU32::new(0).is_valid());
+/// assert!(MaxInvalidU32(0x000_FFFFF).is_null());
+/// assert!(MaxInvalidU32(0xFFF_FFFFF).is_null());
+/// // Compared to the default convention where index 0 is the invalid one
+/// assert_eq!(u32::invalid(), 0);
+/// assert_eq!(u32::MAX_IDX, 1048575);
+/// assert!(!0u32.is_valid());
+/// assert!(u32::new(0x000_FFFFF).is_valid());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxInvalidU32(pub u32);
+
 /// Can make a trivial tuple1 wrapper with the `delegate_wrapped_entity!` macro around another valid
 /// type:
 ///
@@ -98,6 +312,7 @@ macro_rules! unsigned_integral_entity {
 /// assert_eq!(e.idx(), 16);
 /// assert_eq!(e.version(), 1);
 /// assert_eq!(e.0, 1048592);
+/// assert_eq!(Wrapper::MAX_IDX, 1048575);
 /// ```
 #[macro_export]
 macro_rules! delegate_wrapped_entity {
@@ -105,11 +320,16 @@ macro_rules! delegate_wrapped_entity {
 		impl enrs::entity::Entity for $SELF {
 			type StorageType = <$INTERNAL as enrs::entity::Entity>::StorageType;
 			type VersionType = <$INTERNAL as enrs::entity::Entity>::VersionType;
+			const MAX_IDX: usize = <$INTERNAL as enrs::entity::Entity>::MAX_IDX;
 
 			fn new(idx: usize) -> Self {
 				$SELF(<$INTERNAL as enrs::entity::Entity>::new(idx))
 			}
 
+			fn invalid() -> Self {
+				$SELF(<$INTERNAL as enrs::entity::Entity>::invalid())
+			}
+
 			#[allow(clippy::verbose_bit_mask)]
 			fn is_null(self) -> bool {
 				self.0.is_null()
@@ -131,6 +351,14 @@ macro_rules! delegate_wrapped_entity {
 			fn bump_version_with_idx(&mut self, idx: usize) {
 				self.0.bump_version_with_idx(idx)
 			}
+
+			fn version_as_usize(self) -> usize {
+				self.0.version_as_usize()
+			}
+
+			fn try_from_idx_version(idx: usize, version: usize) -> Option<Self> {
+				<$INTERNAL as enrs::entity::Entity>::try_from_idx_version(idx, version).map($SELF)
+			}
 		}
 	};
 }
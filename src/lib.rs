@@ -1,8 +1,8 @@
-//pub use frunk;
+pub use frunk;
 
 pub mod database;
 pub mod entity;
-//pub mod storages;
+pub mod storages;
 pub mod table;
 pub mod tables;
 pub mod utils;
@@ -13,7 +13,7 @@ macro_rules! TL {
 		()
 	};
 	[ $c:ty $(,$cs:ty)* $(,)* ] => {
-	    ($c, TL![$($cs),*])
+	    ($c, $crate::TL![$($cs),*])
 	};
 	// [ $c:ty $(,$cs:ty)*, ] => {
 	//     ($c, TL![$($cs),*])
@@ -29,7 +29,7 @@ macro_rules! tl {
 		()
 	};
 	[ $c:expr $(,$cs:expr)* $(,)* ] => {
-	    ($c, tl![$($cs),*])
+	    ($c, $crate::tl![$($cs),*])
 	};
 	// [ $c:expr $(,$cs:expr)*, ] => {
 	//     ($c, tl![$($cs),*])
@@ -55,8 +55,78 @@ macro_rules! tlp {
 	}
 }
 
+/// Declares a struct of components that are inserted together and implements
+/// `tables::Bundle` for it, so the insert types and the values are written once.
+///
+/// ```
+/// use enrs::bundle;
+/// use enrs::database::Database;
+/// use enrs::tables::{Bundle, DenseEntityDynamicPagedMultiValueTable, EntityTable};
+///
+/// pub struct Position(f32, f32);
+/// pub struct Velocity(f32, f32);
+///
+/// bundle! {
+/// 	pub struct Physics {
+/// 		pub position: Position,
+/// 		pub velocity: Velocity,
+/// 	}
+/// }
+///
+/// let mut database = Database::new();
+/// let entities_storage = database
+/// 	.tables
+/// 	.create("entities", EntityTable::<u64>::builder())
+/// 	.unwrap();
+/// let multi_storage = database
+/// 	.tables
+/// 	.create(
+/// 		"multi",
+/// 		DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+/// 	)
+/// 	.unwrap();
+/// let mut entities = entities_storage.borrow_mut();
+/// let mut multi = multi_storage.borrow_mut();
+/// let mut inserter = multi.group_insert::<<Physics as Bundle>::Insert>().unwrap();
+/// let entity = entities.insert();
+/// let physics = Physics {
+/// 	position: Position(0.0, 0.0),
+/// 	velocity: Velocity(1.0, 0.0),
+/// };
+/// inserter
+/// 	.lock(&mut multi)
+/// 	.insert(entity, physics.into_data())
+/// 	.unwrap();
+/// assert_eq!(multi.component_count(entity), Some(2));
+/// ```
+#[macro_export]
+macro_rules! bundle {
+	(
+		$(#[$meta:meta])*
+		$vis:vis struct $name:ident {
+			$($field_vis:vis $field:ident : $T:ty),* $(,)?
+		}
+	) => {
+		$(#[$meta])*
+		$vis struct $name {
+			$($field_vis $field: $T),*
+		}
+
+		impl $crate::tables::Bundle for $name {
+			type Insert = $crate::TL![$(&'static mut $T),*];
+
+			fn into_data(
+				self,
+			) -> <Self::Insert as $crate::tables::InsertValueTypes>::MoveData {
+				$crate::tl![$(self.$field),*]
+			}
+		}
+	};
+}
+
 mod entity_instances {
 	pub use crate as enrs;
+	use crate::entity::MaxInvalidU32;
 	use crate::unsigned_integral_entity;
 
 	unsigned_integral_entity!(
@@ -83,4 +153,13 @@ mod entity_instances {
 		32,
 		"`u64` Entity, Index: 32 bits, Generation: 32 bits, Invalid ID: 0, Max: 4294967295"
 	);
+	unsigned_integral_entity!(
+		MaxInvalidU32(u32),
+		u16,
+		0x000_FFFFF,
+		0xFFF_00000,
+		20,
+		u32::MAX,
+		"`u32` Entity, Index: 20 bits, Generation: 12 bits, Invalid ID: u32::MAX, Max: 1048574"
+	);
 }
@@ -3,7 +3,6 @@ use std::marker::PhantomData;
 
 use crate::frunk::{prelude::HList, HCons, HNil};
 
-pub mod secondary_index;
 pub mod sparse_typed_paged_map;
 pub mod typed_index_map;
 
@@ -42,9 +41,22 @@ pub trait TypeList: 'static + HList {
 	/// assert_eq!(<Hlist![]>::contains_type_id(std::any::TypeId::of::<usize>()), false);
 	/// assert_eq!(<Hlist![usize]>::contains_type_id(std::any::TypeId::of::<usize>()), true);
 	/// assert_eq!(<Hlist![i32, usize, i64]>::contains_type_id(std::any::TypeId::of::<usize>()), true);
+	/// assert_eq!(<Hlist![usize, String]>::contains_type_id(std::any::TypeId::of::<String>()), true);
+	/// assert_eq!(<Hlist![usize, String]>::contains_type_id(std::any::TypeId::of::<f32>()), false);
 	/// ```
 	fn contains_type_id(tid: TypeId) -> bool;
 
+	/// Get the index of the first type in this HList with the passed in TypeId.
+	///
+	/// ```rust
+	/// # use enrs::{frunk::{*, prelude::*}, storages::*};
+	/// assert_eq!(<Hlist![]>::index_of_type_id(std::any::TypeId::of::<usize>()), None);
+	/// assert_eq!(<Hlist![usize, String]>::index_of_type_id(std::any::TypeId::of::<usize>()), Some(0));
+	/// assert_eq!(<Hlist![usize, String]>::index_of_type_id(std::any::TypeId::of::<String>()), Some(1));
+	/// assert_eq!(<Hlist![usize, String]>::index_of_type_id(std::any::TypeId::of::<f32>()), None);
+	/// ```
+	fn index_of_type_id(tid: TypeId) -> Option<usize>;
+
 	/// Get the TypeId at a given index in this HList.
 	///
 	/// TODO:  Make a constant version of this once const generics are in Rust.
@@ -90,6 +102,10 @@ impl TypeList for HNil {
 		false
 	}
 	#[inline]
+	fn index_of_type_id(_tid: TypeId) -> Option<usize> {
+		None
+	}
+	#[inline]
 	fn get_type_id_at(_idx: usize) -> Option<TypeId> {
 		None
 	}
@@ -117,6 +133,15 @@ where
 		tid == std::any::TypeId::of::<H>() || T::contains_type_id(tid)
 	}
 
+	#[inline]
+	fn index_of_type_id(tid: TypeId) -> Option<usize> {
+		if tid == TypeId::of::<H>() {
+			Some(0)
+		} else {
+			T::index_of_type_id(tid).map(|idx| idx + 1)
+		}
+	}
+
 	#[inline]
 	fn get_type_id_at(idx: usize) -> Option<TypeId> {
 		if idx == 0 {
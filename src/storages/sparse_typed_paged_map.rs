@@ -1,5 +1,5 @@
 use std::any::{Any, TypeId};
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::collections::hash_map::RandomState;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
@@ -16,16 +16,19 @@ use itertools::Itertools;
 use owning_ref::{OwningHandle, OwningRef, OwningRefMut};
 
 use crate::entity::Entity;
+use crate::frunk::indices::{Here, There};
 use crate::frunk::{prelude::HList, HCons, HNil};
-use crate::storages::secondary_index::{SecondaryIndex, SecondaryIndexErrors};
 use crate::storages::TypeList;
+use crate::utils::secondary_entity_index::{SecondaryEntityIndex, SecondaryEntityIndexErrors};
 use crate::utils::unique_hasher::UniqueHasherBuilder;
 
 /// Possible Errors given by a SparsePageMap's operation.
 #[derive(Debug, PartialEq, Eq)]
 pub enum SparseTypedPagedMapErrors<EntityType: Entity> {
 	PoisonError,
-	SecondaryIndexError(SecondaryIndexErrors<EntityType>),
+	/// The entities are borrowed by a live iterator and so cannot be structurally changed.
+	BorrowMutError,
+	SecondaryIndexError(SecondaryEntityIndexErrors<EntityType>),
 	StorageDoesNotExistInGroup(usize, TypeId),
 	StorageAlreadyExistsInGroup(usize, TypeId),
 	EntityAlreadyExistsInStorage,
@@ -40,6 +43,7 @@ impl<EntityType: Entity> std::error::Error for SparseTypedPagedMapErrors<EntityT
 		use SparseTypedPagedMapErrors::*;
 		match self {
 			PoisonError => None,
+			BorrowMutError => None,
 			SecondaryIndexError(source) => Some(source),
 			StorageDoesNotExistInGroup(_group, _tid) => None,
 			StorageAlreadyExistsInGroup(_group, _tid) => None,
@@ -57,6 +61,7 @@ impl<EntityType: Entity> std::fmt::Display for SparseTypedPagedMapErrors<EntityT
 		use SparseTypedPagedMapErrors::*;
 		match self {
 			PoisonError => write!(f, "Lock Poisoned"),
+			BorrowMutError => write!(f, "Entities are borrowed by an active iterator"),
 			SecondaryIndexError(_source) => write!(f, "SecondaryIndexError"),
 			StorageDoesNotExistInGroup(group, tid) => {
 				write!(f, "Storage does not exist in group {}: {:?}", group, tid)
@@ -88,14 +93,20 @@ impl<EntityType: Entity> std::fmt::Display for SparseTypedPagedMapErrors<EntityT
 	}
 }
 
-impl<EntityType: Entity> From<SecondaryIndexErrors<EntityType>>
+impl<EntityType: Entity> From<SecondaryEntityIndexErrors<EntityType>>
 	for SparseTypedPagedMapErrors<EntityType>
 {
-	fn from(source: SecondaryIndexErrors<EntityType>) -> Self {
+	fn from(source: SecondaryEntityIndexErrors<EntityType>) -> Self {
 		SparseTypedPagedMapErrors::SecondaryIndexError(source)
 	}
 }
 
+impl<EntityType: Entity> From<std::cell::BorrowMutError> for SparseTypedPagedMapErrors<EntityType> {
+	fn from(_source: std::cell::BorrowMutError) -> Self {
+		SparseTypedPagedMapErrors::BorrowMutError
+	}
+}
+
 impl<EntityType: Entity, Guard> From<PoisonError<Guard>> for SparseTypedPagedMapErrors<EntityType> {
 	fn from(_source: PoisonError<Guard>) -> Self {
 		SparseTypedPagedMapErrors::PoisonError
@@ -112,9 +123,9 @@ pub trait DensePagedData: private::Sealed + 'static {
 	fn len_groups(&self) -> usize;
 	fn resize(&self, new_len: usize);
 	fn try_resize(&self, new_len: usize) -> bool;
-	fn truncate_group(&self, group: usize, len: usize);
-	fn swap_remove(&self, group: usize, index: usize);
-	fn swap_to_group(&self, old_group: usize, index: usize, new_group: usize) -> usize;
+	fn truncate_group(&self, group: GroupId, len: usize);
+	fn swap_remove(&self, group: GroupId, index: usize);
+	fn swap_to_group(&self, old_group: GroupId, index: usize, new_group: GroupId) -> usize;
 }
 
 impl dyn DensePagedData {
@@ -179,37 +190,84 @@ impl dyn DensePagedData {
 }
 
 pub struct DensePagedDataActual<DataType: 'static> {
-	index: usize,
+	index: MapId,
 	data: Vec<Vec<DataType>>,
+	/// The insert tick of every value in `data`, at the same group and index.
+	ticks: Vec<Vec<u64>>,
+	/// The insert tick given to the most recently pushed values.
+	tick: u64,
 }
 
 pub struct DensePagedDataInstance<DataType: 'static>(Rc<RefCell<DensePagedDataActual<DataType>>>);
 
 impl<DataType: 'static> DensePagedDataActual<DataType> {
-	fn push(&mut self, group: usize, data: DataType) -> usize {
-		let storage = &mut self.data[group];
+	fn push(&mut self, group: GroupId, data: DataType) -> usize {
+		self.tick += 1;
+		self.ticks[group.0].push(self.tick);
+		let storage = &mut self.data[group.0];
 		storage.push(data);
 		storage.len() - 1
 	}
 
-	fn push_all<I: IntoIterator<Item = DataType>>(&mut self, group: usize, data: I) -> usize {
-		let storage = &mut self.data[group];
+	fn push_all<I: IntoIterator<Item = DataType>>(&mut self, group: GroupId, data: I) -> usize {
+		self.tick += 1;
+		let storage = &mut self.data[group.0];
 		let start_idx = storage.len();
 		storage.extend(data);
+		let len = storage.len();
+		self.ticks[group.0].resize(len, self.tick);
 		start_idx
 	}
 
 	#[inline]
-	fn truncate_group(&mut self, group: usize, len: usize) {
-		self.data[group].truncate(len);
+	fn truncate_group(&mut self, group: GroupId, len: usize) {
+		self.data[group.0].truncate(len);
+		self.ticks[group.0].truncate(len);
+	}
+
+	#[inline]
+	fn resize_groups(&mut self, new_len: usize) {
+		self.data.resize_with(new_len, Vec::new);
+		self.ticks.resize_with(new_len, Vec::new);
+	}
+
+	#[inline]
+	fn swap_remove(&mut self, group: GroupId, index: usize) -> DataType {
+		self.ticks[group.0].swap_remove(index);
+		self.data[group.0].swap_remove(index)
+	}
+
+	/// Moves a value to the end of another group, keeping its insert tick as it is not a new value.
+	fn swap_to_group(&mut self, old_group: GroupId, index: usize, new_group: GroupId) -> usize {
+		let tick = self.ticks[old_group.0].swap_remove(index);
+		let data = self.data[old_group.0].swap_remove(index);
+		self.ticks[new_group.0].push(tick);
+		let group = &mut self.data[new_group.0];
+		group.push(data);
+		group.len() - 1
+	}
+
+	/// The insert tick of the most recently pushed values, values pushed after this call get a
+	/// higher insert tick.
+	#[inline]
+	pub fn tick(&self) -> u64 {
+		self.tick
+	}
+
+	/// The insert tick of the value at this location, if it exists.
+	#[inline]
+	pub fn insert_tick(&self, group: GroupId, index: usize) -> Option<u64> {
+		self.ticks.get(group.0)?.get(index).copied()
 	}
 }
 
 impl<DataType: 'static> DensePagedDataInstance<DataType> {
-	fn with_groups(map_idx: usize, group_size: usize) -> Self {
+	fn with_groups(map_idx: MapId, group_size: usize) -> Self {
 		Self(Rc::new(RefCell::new(DensePagedDataActual {
 			index: map_idx,
 			data: (0..group_size).map(|_| vec![]).collect(),
+			ticks: (0..group_size).map(|_| vec![]).collect(),
+			tick: 0,
 		})))
 	}
 
@@ -254,32 +312,30 @@ impl<DataType: 'static> DensePagedData for DensePagedDataInstance<DataType> {
 	}
 	#[inline]
 	fn resize(&self, new_len: usize) {
-		self.0.borrow_mut().data.resize_with(new_len, Vec::new);
+		self.0.borrow_mut().resize_groups(new_len);
 	}
 	#[inline]
 	fn try_resize(&self, new_len: usize) -> bool {
 		if let Ok(mut inner) = self.0.try_borrow_mut() {
-			inner.data.resize_with(new_len, Vec::new);
+			inner.resize_groups(new_len);
 			true
 		} else {
 			false
 		}
 	}
 	#[inline]
-	fn truncate_group(&self, group: usize, len: usize) {
-		self.0.borrow_mut().data[group].truncate(len);
+	fn truncate_group(&self, group: GroupId, len: usize) {
+		self.0.borrow_mut().truncate_group(group, len);
 	}
 	#[inline]
-	fn swap_remove(&self, group: usize, index: usize) {
-		self.0.borrow_mut().data[group].swap_remove(index);
+	fn swap_remove(&self, group: GroupId, index: usize) {
+		self.0.borrow_mut().swap_remove(group, index);
 	}
 	#[inline]
-	fn swap_to_group(&self, old_group: usize, index: usize, new_group: usize) -> usize {
-		let mut this = self.0.borrow_mut();
-		let data = this.data[old_group].swap_remove(index);
-		let group = &mut this.data[new_group];
-		group.push(data);
-		group.len() - 1
+	fn swap_to_group(&self, old_group: GroupId, index: usize, new_group: GroupId) -> usize {
+		self.0
+			.borrow_mut()
+			.swap_to_group(old_group, index, new_group)
 	}
 }
 
@@ -362,15 +418,39 @@ impl<DataType: 'static> DensePagedData for DensePagedDataInstance<DataType> {
 // 	}
 // }
 
+/// The index of a group, the set of entities sharing the same component types, so that it can't be
+/// mixed up with a `MapId` or an index within a group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupId(usize);
+
+impl GroupId {
+	#[inline(always)]
+	pub fn index(self) -> usize {
+		self.0
+	}
+}
+
+/// The index of the storage of a component type in the maps, so that it can't be mixed up with a
+/// `GroupId`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MapId(usize);
+
+impl MapId {
+	#[inline(always)]
+	pub fn index(self) -> usize {
+		self.0
+	}
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ComponentLocations {
-	group: usize,
+	group: GroupId,
 	index: usize,
 }
 
 impl ComponentLocations {
 	const INVALID: ComponentLocations = ComponentLocations {
-		group: usize::MAX,
+		group: GroupId(usize::MAX),
 		index: usize::MAX,
 	};
 
@@ -453,6 +533,8 @@ impl<'a> indexmap::Equivalent<QueryTypedPagedKeyBoxed> for QueryTypedPagedKey<'a
 /// These are the indexes to the `group_sets_to_maps`
 struct QueryTypedPagedLink {
 	include_groups: Rc<RefCell<Vec<usize>>>,
+	/// Bit per group, set when that group matches this query, mirrors `include_groups`
+	include_groups_set: BitBox,
 	//exclude_groups: Vec<usize>,
 	include_maps: Vec<usize>, // read_only_groups: Vec<usize>,
 	                          // read_write_groups: Vec<usize>,
@@ -461,6 +543,38 @@ struct QueryTypedPagedLink {
 	                          // read_write_maps: Vec<usize>
 }
 
+impl QueryTypedPagedLink {
+	fn new(include_groups: Vec<usize>, include_maps: Vec<usize>) -> Self {
+		let len = include_groups.last().map_or(0, |&group| group + 1);
+		let mut include_groups_set = bitvec![0; len];
+		for &group in include_groups.iter() {
+			include_groups_set.set(group, true);
+		}
+		Self {
+			include_groups: Rc::new(RefCell::new(include_groups)),
+			include_groups_set: include_groups_set.into_boxed_bitslice(),
+			include_maps,
+		}
+	}
+
+	#[inline]
+	fn contains_group(&self, group: usize) -> bool {
+		self.include_groups_set.get(group) == Some(&true)
+	}
+
+	/// Marks a newly created group as matching, growing the bitset in powers of two so repeated
+	/// group creation doesn't reallocate it every time.
+	fn add_group(&mut self, group: usize) {
+		if group >= self.include_groups_set.len() {
+			let mut bits = self.include_groups_set.as_bitslice().to_bitvec();
+			bits.resize((group + 1).next_power_of_two(), false);
+			self.include_groups_set = bits.into_boxed_bitslice();
+		}
+		self.include_groups_set.set(group, true);
+		self.include_groups.borrow_mut().push(group);
+	}
+}
+
 type MapIndexMap = IndexMap<TypeId, Box<dyn DensePagedData>, UniqueHasherBuilder>;
 
 struct GroupTypeSetAddRemoveIntoIterator<'a>(
@@ -545,9 +659,9 @@ impl GroupTypeSetToMapSet {
 	}
 
 	#[inline(always)]
-	fn get_by_group(&self, group: usize) -> (&Box<[TypeId]>, &BitBox) {
+	fn get_by_group(&self, group: GroupId) -> (&Box<[TypeId]>, &BitBox) {
 		self.0
-			.get_index(group)
+			.get_index(group.0)
 			.expect("Passed group was invalid, system in inconsistent state")
 	}
 
@@ -622,7 +736,7 @@ impl GroupTypeSetToMapSet {
 }
 
 pub struct SparseTypedPagedMap<EntityType: Entity> {
-	reverse: Rc<RefCell<SecondaryIndex<EntityType, ComponentLocations>>>,
+	reverse: Rc<RefCell<SecondaryEntityIndex<EntityType, ComponentLocations>>>,
 	entities: Rc<RefCell<Vec<Vec<EntityType>>>>,
 	maps: Rc<RefCell<MapIndexMap>>,
 	group_sets_to_maps: Rc<RefCell<GroupTypeSetToMapSet>>,
@@ -643,23 +757,81 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 		query_mappings: &mut IndexMap<QueryTypedPagedKeyBoxed, QueryTypedPagedLink>,
 		group: usize,
 	) {
-		let (types, _map) = &group_to_maps
+		let (types, map_idxs) = &group_to_maps
 			.0
 			.get_index(group)
 			.expect("Attempting to update mapping group when group does not yet exist");
 		for (query, link) in query_mappings.iter_mut() {
-			if query.include.iter().all(|tid| types.contains(tid))
-				&& query.exclude.iter().all(|tid| !types.contains(tid))
+			if link.contains_group(group) {
+				continue;
+			}
+			// The group's map bitset already encodes which storages it has, so test the query's
+			// include maps against it rather than scanning the type list for each include type,
+			// except the include maps of a query with excludes also hold the maps of its excludes
+			let includes = if query.exclude.is_empty() {
+				link.include_maps
+					.iter()
+					.all(|&map_idx| map_idxs.get(map_idx) == Some(&true))
+			} else {
+				query.include.iter().all(|tid| types.contains(tid))
+			};
+			if !link.include_maps.is_empty()
+				&& includes && query.exclude.iter().all(|tid| !types.contains(tid))
 			{
-				link.include_groups.borrow_mut().push(group);
+				link.add_group(group);
+			}
+		}
+	}
+
+	/// Every group holding all of `include_tids` and none of `exclude_tids`, for a new link.
+	fn matching_query_groups<CT: ComponentTupleQuery<'static, EntityType>>(
+		group_sets_to_maps: &GroupTypeSetToMapSet,
+		include_tids: &GenericArray<TypeId, CT::LenIncludeTN>,
+		exclude_tids: &[TypeId],
+	) -> Vec<usize> {
+		let mut groups = CT::get_include_matching_query_groups(group_sets_to_maps, include_tids);
+		if !exclude_tids.is_empty() {
+			let group_types: Vec<_> = group_sets_to_maps.keys().collect();
+			groups.retain(|&group| {
+				exclude_tids
+					.iter()
+					.all(|tid| !group_types[group].contains(tid))
+			});
+		}
+		groups
+	}
+
+	fn get_or_create_group(
+		&self,
+		group_sets_to_maps: &mut GroupTypeSetToMapSet,
+		query_mappings: &mut IndexMap<QueryTypedPagedKeyBoxed, QueryTypedPagedLink>,
+		include_tids: &[TypeId],
+		get_map_idxs: impl FnOnce(&mut MapIndexMap) -> Vec<usize>,
+	) -> GroupId {
+		if let Some(group) = group_sets_to_maps.get_index_of(include_tids) {
+			GroupId(group)
+		} else {
+			let mut maps = self.maps.borrow_mut();
+			let mut map_idxs = bitvec![0; maps.len()+include_tids.len()];
+			for map_idx in get_map_idxs(&mut *maps) {
+				map_idxs.set(map_idx, true);
+			}
+			let map_idxs = map_idxs.into_boxed_bitslice();
+			group_sets_to_maps.insert(include_tids.to_vec().into_boxed_slice(), map_idxs);
+			self.entities.borrow_mut().push(Vec::with_capacity(1));
+			let group = group_sets_to_maps.len() - 1;
+			for map in maps.values_mut() {
+				map.resize(group + 1);
 			}
+			Self::update_query_mappings(&*group_sets_to_maps, query_mappings, group);
+			GroupId(group)
 		}
 	}
 
 	// public
 	pub fn new() -> Self {
 		Self {
-			reverse: Rc::new(RefCell::new(SecondaryIndex::new(
+			reverse: Rc::new(RefCell::new(SecondaryEntityIndex::new(
 				ComponentLocations::INVALID,
 			))),
 			entities: Rc::new(RefCell::new(Default::default())),
@@ -673,11 +845,78 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 		Self::get_valid_location(&*self.reverse.borrow(), &*self.entities.borrow(), entity).is_ok()
 	}
 
+	/// Inserts the entity with the given components, creating the group for the component types
+	/// of `CT` if it does not yet exist.
+	pub fn insert<CT: ComponentTupleQuery<'static, EntityType>>(
+		&mut self,
+		entity: EntityType,
+		components: CT::StorageMovedValues,
+	) -> Result<(), SparseTypedPagedMapErrors<EntityType>> {
+		self.entities.try_borrow_mut()?;
+		let include_tids: generic_array::GenericArray<TypeId, CT::LenIncludeTN> =
+			CT::get_include_tids();
+		let group = self.get_or_create_group(
+			&mut *self.group_sets_to_maps.borrow_mut(),
+			&mut *self.query_mappings.borrow_mut(),
+			&include_tids,
+			CT::get_map_idxs,
+		);
+		let map_idxs = CT::get_map_idxs(&mut *self.maps.borrow_mut());
+		let storages = CT::get_storages(&self.entities, &*self.maps.borrow(), &map_idxs);
+		let mut reverse = self.reverse.borrow_mut();
+		let mut entities = self.entities.borrow_mut();
+		Self::insert_valid_location_mut(&mut *reverse, &mut *entities, entity, group)?;
+		CT::insert(components, &mut CT::get_locked_storages(&storages), group.0);
+		Ok(())
+	}
+
+	/// Inserts all of `entities` with their components given as an `hlist!` of one
+	/// `ExactSizeIterator` per component type, creating the group for those component types if it
+	/// does not yet exist.
+	///
+	/// The lengths of all the iterators are checked against `entities` before any are consumed.  If
+	/// any entity fails to insert then none of them are inserted.
+	pub fn extend_hlist<C: ComponentSliceSet, EI: ExactSizeIterator<Item = EntityType>>(
+		&mut self,
+		entities: EI,
+		components: C,
+	) -> Result<(), SparseTypedPagedMapErrors<EntityType>> {
+		if !components.all_same_len(entities.len()) {
+			return Err(SparseTypedPagedMapErrors::IteratorsNotAllSameLength);
+		}
+		self.entities.try_borrow_mut()?;
+		let mut include_tids = Vec::with_capacity(C::LEN);
+		C::populate_item_type_ids(&mut include_tids);
+		let group = self.get_or_create_group(
+			&mut *self.group_sets_to_maps.borrow_mut(),
+			&mut *self.query_mappings.borrow_mut(),
+			&include_tids,
+			|maps| components.into_type_idx_vec(maps),
+		);
+		let map_idxs = components.into_type_idx_vec(&mut *self.maps.borrow_mut());
+		let mut reverse = self.reverse.borrow_mut();
+		let mut entities_groups = self.entities.borrow_mut();
+		let group_size = entities_groups[group.0].len();
+		for entity in entities {
+			if let Err(error) =
+				Self::insert_valid_location_mut(&mut *reverse, &mut *entities_groups, entity, group)
+			{
+				// Nothing was pushed to the component storages yet so only the entities are undone
+				for entity in entities_groups[group.0].drain(group_size..) {
+					*reverse.get_mut(entity).unwrap() = ComponentLocations::INVALID;
+				}
+				return Err(error);
+			}
+		}
+		components.insert_all(&mut *self.maps.borrow_mut(), &map_idxs, group);
+		Ok(())
+	}
+
 	fn insert_valid_location_mut<'a>(
-		reverse: &'a mut SecondaryIndex<EntityType, ComponentLocations>,
+		reverse: &'a mut SecondaryEntityIndex<EntityType, ComponentLocations>,
 		entities: &mut Vec<Vec<EntityType>>,
 		entity: EntityType,
-		group: usize,
+		group: GroupId,
 	) -> Result<&'a mut ComponentLocations, SparseTypedPagedMapErrors<EntityType>> {
 		let location = reverse.insert_mut(entity)?;
 		location.group = group;
@@ -685,53 +924,53 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 		// if entities.len() <= location.group {
 		// 	entities.resize(location.group, vec![]);
 		// }
-		let entities_group = &mut entities[group];
+		let entities_group = &mut entities[group.0];
 		location.index = entities_group.len();
 		entities_group.push(entity);
 		Ok(location)
 	}
 
 	fn get_valid_location<'a>(
-		reverse: &'a SecondaryIndex<EntityType, ComponentLocations>,
+		reverse: &'a SecondaryEntityIndex<EntityType, ComponentLocations>,
 		entities: &Vec<Vec<EntityType>>,
 		entity: EntityType,
 	) -> Result<&'a ComponentLocations, SparseTypedPagedMapErrors<EntityType>> {
 		let location = reverse.get(entity)?;
-		if entities[location.group][location.index] != entity {
+		if entities[location.group.0][location.index] != entity {
 			return Err(SparseTypedPagedMapErrors::EntityGenerationMismatch(
 				entity,
-				entities[location.group][location.index],
+				entities[location.group.0][location.index],
 			));
 		}
 		Ok(location)
 	}
 
 	fn get_valid_location_mut<'a>(
-		reverse: &'a mut SecondaryIndex<EntityType, ComponentLocations>,
+		reverse: &'a mut SecondaryEntityIndex<EntityType, ComponentLocations>,
 		entities: &Vec<Vec<EntityType>>,
 		entity: EntityType,
 	) -> Result<&'a mut ComponentLocations, SparseTypedPagedMapErrors<EntityType>> {
 		let location = reverse.get_mut(entity)?;
-		if entities[location.group][location.index] != entity {
+		if entities[location.group.0][location.index] != entity {
 			return Err(SparseTypedPagedMapErrors::EntityGenerationMismatch(
 				entity,
-				entities[location.group][location.index],
+				entities[location.group.0][location.index],
 			));
 		}
 		Ok(location)
 	}
 
 	fn remove_valid_location(
-		reverse: &mut SecondaryIndex<EntityType, ComponentLocations>,
+		reverse: &mut SecondaryEntityIndex<EntityType, ComponentLocations>,
 		entities: &mut Vec<Vec<EntityType>>,
 		entity: EntityType,
 	) -> Result<ComponentLocations, SparseTypedPagedMapErrors<EntityType>> {
 		let location = reverse.get_mut(entity)?;
-		let entities_group = &mut entities[location.group];
+		let entities_group = &mut entities[location.group.0];
 		if entities_group[location.index] != entity {
 			return Err(SparseTypedPagedMapErrors::EntityGenerationMismatch(
 				entity,
-				entities[location.group][location.index],
+				entities[location.group.0][location.index],
 			));
 		}
 		let loc = *location;
@@ -747,18 +986,27 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 		Ok(loc)
 	}
 
+	/// Removes the entity and all of its components.
+	///
+	/// Errors with `BorrowMutError` if an iterator from `ComponentPagedQuery::iter_slices` is still
+	/// alive, as are `insert` and `extend_hlist`.
 	pub fn remove(
 		&mut self,
 		entity: EntityType,
 	) -> Result<(), SparseTypedPagedMapErrors<EntityType>> {
 		let location = Self::remove_valid_location(
 			&mut *self.reverse.borrow_mut(),
-			&mut *self.entities.borrow_mut(),
+			&mut *self.entities.try_borrow_mut()?,
 			entity,
 		)?;
+		let group_sets_to_maps = self.group_sets_to_maps.borrow();
+		let (_types, map_idxs) = group_sets_to_maps.get_by_group(location.group);
 		let mut maps = self.maps.borrow_mut();
-		for map in maps.values_mut() {
-			map.swap_remove(location.group, location.index);
+		// Only the maps of the entity's own group hold a value for it
+		for (map_idx, map) in maps.values_mut().enumerate() {
+			if map_idxs.get(map_idx) == Some(&true) {
+				map.swap_remove(location.group, location.index);
+			}
 		}
 		Ok(())
 	}
@@ -1158,42 +1406,33 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 		};
 		let mut query_mappings = self.query_mappings.borrow_mut();
 		let mut group_sets_to_maps = self.group_sets_to_maps.borrow_mut();
-		let group = if let Some(group) = group_sets_to_maps.get_index_of(include_tids.as_slice()) {
-			group
-		} else {
-			let mut maps = self.maps.borrow_mut();
-			let mut map_idxs = bitvec![0; maps.len()+CT::LenIncludeTN::USIZE];
-			for map_idx in CT::get_map_idxs(&mut *maps) {
-				map_idxs.set(map_idx, true);
-			}
-			let map_idxs = map_idxs.into_boxed_bitslice();
-			group_sets_to_maps.insert(include_tids.to_vec().into_boxed_slice(), map_idxs);
-			self.entities.borrow_mut().push(Vec::with_capacity(1));
-			let group = group_sets_to_maps.len() - 1;
-			for map in maps.values_mut() {
-				map.resize(group + 1);
-			}
-			SparseTypedPagedMap::<EntityType>::update_query_mappings(
-				&*group_sets_to_maps,
-				&mut *query_mappings,
-				group,
-			);
-			group
-		};
+		let group = self.get_or_create_group(
+			&mut *group_sets_to_maps,
+			&mut *query_mappings,
+			&include_tids,
+			|maps| {
+				// The group only holds the included types, not the maps of the excluded ones
+				let mut map_idxs = CT::get_map_idxs(maps);
+				map_idxs
+					.retain(|&map_idx| include_tids.contains(maps.get_index(map_idx).unwrap().0));
+				map_idxs
+			},
+		);
 		let link: &QueryTypedPagedLink = {
-			query_mappings
-				.entry(query_key.to_box())
-				.or_insert_with(|| QueryTypedPagedLink {
-					include_groups: Rc::new(RefCell::new(CT::get_include_matching_query_groups(
+			query_mappings.entry(query_key.to_box()).or_insert_with(|| {
+				QueryTypedPagedLink::new(
+					Self::matching_query_groups::<CT>(
 						&*group_sets_to_maps,
 						&include_tids,
-					))),
-					// exclude_groups: CT::get_exclude_matching_query_groups(
+						&exclude_tids,
+					),
+					// CT::get_exclude_matching_query_groups(
 					// 	&self.group_sets_to_maps,
 					// 	&exclude_tids,
 					// ),
-					include_maps: CT::get_map_idxs(&mut *self.maps.borrow_mut()),
-				})
+					CT::get_map_idxs(&mut *self.maps.borrow_mut()),
+				)
+			})
 		};
 		Ok(ComponentPagedQuery {
 			reverse: self.reverse.clone(),
@@ -1206,6 +1445,41 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 			groups: link.include_groups.clone(),
 		})
 	}
+
+	/// Returns a handle to the groups matching `CT` that can be kept across frames, call
+	/// `PreparedQuery::refresh` to pick up the groups created since it was prepared or last refreshed.
+	pub fn prepared_query<CT: ComponentTupleQuery<'static, EntityType>>(
+		&self,
+	) -> PreparedQuery<EntityType, CT> {
+		let include_tids: generic_array::GenericArray<TypeId, CT::LenIncludeTN> =
+			CT::get_include_tids();
+		let exclude_tids: generic_array::GenericArray<TypeId, CT::LenExcludeTN> =
+			CT::get_exclude_tids();
+		let query_key = QueryTypedPagedKey {
+			include: &include_tids,
+			exclude: &exclude_tids,
+		};
+		let mut query_mappings = self.query_mappings.borrow_mut();
+		let group_sets_to_maps = self.group_sets_to_maps.borrow();
+		let entry = query_mappings.entry(query_key.to_box());
+		let link_idx = entry.index();
+		let link = entry.or_insert_with(|| {
+			QueryTypedPagedLink::new(
+				Self::matching_query_groups::<CT>(
+					&*group_sets_to_maps,
+					&include_tids,
+					&exclude_tids,
+				),
+				CT::get_map_idxs(&mut *self.maps.borrow_mut()),
+			)
+		});
+		let groups = link.include_groups.borrow().clone();
+		PreparedQuery {
+			link_idx,
+			groups,
+			_phantom: PhantomData,
+		}
+	}
 	/*
 	pub fn iter<'a, CS: ComponentStorageSet<'a>>(
 		&'a self,
@@ -1328,14 +1602,48 @@ impl<EntityType: Entity> SparseTypedPagedMap<EntityType> {
 	// }
 }
 
+/// The groups matching a query, cached so they don't need to be looked up again every frame, see
+/// `SparseTypedPagedMap::prepared_query`.
+pub struct PreparedQuery<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>> {
+	/// Index of the link of this query in the `query_mappings` of the map it was prepared from.
+	link_idx: usize,
+	groups: Vec<usize>,
+	_phantom: PhantomData<(EntityType, CT)>,
+}
+
+impl<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
+	PreparedQuery<EntityType, CT>
+{
+	/// The groups matching this query as of the last refresh.
+	pub fn groups(&self) -> &[usize] {
+		&self.groups
+	}
+
+	/// Appends the matching groups that were created since the last refresh, returning how many
+	/// were appended.
+	///
+	/// Matching groups are tracked by `map` as they are created so this only copies the new ones,
+	/// `map` must be the same map this query was prepared from.
+	pub fn refresh(&mut self, map: &SparseTypedPagedMap<EntityType>) -> usize {
+		let query_mappings = map.query_mappings.borrow();
+		let (_query, link) = query_mappings
+			.get_index(self.link_idx)
+			.expect("PreparedQuery refreshed from a different map than it was prepared from");
+		let include_groups = link.include_groups.borrow();
+		let added = &include_groups[self.groups.len()..];
+		self.groups.extend_from_slice(added);
+		added.len()
+	}
+}
+
 pub struct ComponentPagedQuery<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>> {
-	reverse: Rc<RefCell<SecondaryIndex<EntityType, ComponentLocations>>>,
+	reverse: Rc<RefCell<SecondaryEntityIndex<EntityType, ComponentLocations>>>,
 	entities: Rc<RefCell<Vec<Vec<EntityType>>>>,
 	group_sets_to_maps: Rc<RefCell<GroupTypeSetToMapSet>>,
 	query_mappings: Rc<RefCell<IndexMap<QueryTypedPagedKeyBoxed, QueryTypedPagedLink>>>,
 	maps: Rc<RefCell<MapIndexMap>>,
 	storages: CT::Storages,
-	group: usize,
+	group: GroupId,
 	groups: Rc<RefCell<Vec<usize>>>,
 }
 
@@ -1344,13 +1652,13 @@ pub struct ComponentPagedQueryLocked<
 	EntityType: Entity,
 	CT: ComponentTupleQuery<'static, EntityType>,
 > {
-	reverse: RefMut<'a, SecondaryIndex<EntityType, ComponentLocations>>,
+	reverse: RefMut<'a, SecondaryEntityIndex<EntityType, ComponentLocations>>,
 	entities: RefMut<'a, Vec<Vec<EntityType>>>,
 	group_sets_to_maps: RefMut<'a, GroupTypeSetToMapSet>,
 	query_mappings: &'a Rc<RefCell<IndexMap<QueryTypedPagedKeyBoxed, QueryTypedPagedLink>>>,
 	maps: RefMut<'a, MapIndexMap>,
 	storages: CT::StoragesLocked,
-	group: usize,
+	group: GroupId,
 	groups: &'a Rc<RefCell<Vec<usize>>>,
 }
 
@@ -1368,7 +1676,7 @@ impl<'a, EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 			entity,
 			self.group,
 		)?;
-		CT::insert(components, &mut self.storages, self.group);
+		CT::insert(components, &mut self.storages, self.group.0);
 		Ok(())
 	}
 
@@ -1383,7 +1691,7 @@ impl<'a, EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 				entity,
 				self.group,
 			)?;
-			CT::insert(components, &mut self.storages, self.group);
+			CT::insert(components, &mut self.storages, self.group.0);
 		}
 
 		Ok(())
@@ -1415,7 +1723,7 @@ impl<'a, EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 			components,
 			//&mut cast_storages,
 			&mut self.storages,
-			self.group,
+			self.group.0,
 			count,
 		);
 
@@ -1429,19 +1737,59 @@ impl<'a, EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 		let location =
 			SparseTypedPagedMap::get_valid_location(&*self.reverse, &*self.entities, entity)?;
 		let mut cast_storages = CTT::cast_locked_storages::<CT>(&mut self.storages);
-		if let Some(value) = CTT::get_storage_values_at(
+		CTT::get_storage_values_at(
 			// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
 			// This 'should' be safeish as it's just casting lifetimes to a more constrained lifetime
 			unsafe { &mut *(&mut cast_storages as *mut CTT::StoragesLockedRef) },
-			location.group,
+			location.group.0,
 			location.index,
-		) {
-			Ok(value)
-		} else {
-			Err(SparseTypedPagedMapErrors::EntityDoesNotExistInStorage(
-				entity, "",
-			))
-		}
+		)
+		.map_err(|name| SparseTypedPagedMapErrors::EntityDoesNotExistInStorage(entity, name))
+	}
+
+	/// Same as `get` except that `CTT` must be a subset of this query's component tuple, checked at
+	/// compile time instead of panicking at runtime.
+	///
+	/// ```rust
+	/// # use enrs::storages::sparse_typed_paged_map::SparseTypedPagedMap;
+	/// # use enrs::{tl, TL};
+	/// let map = SparseTypedPagedMap::<u64>::new();
+	/// let mut query = map.query::<TL![&mut usize, &mut u16]>().unwrap();
+	/// let mut query = query.lock();
+	/// query.insert(1, tl![21, 2]).unwrap();
+	/// assert_eq!(*query.get_checked::<TL![&mut u16], _>(1).unwrap().0, 2);
+	/// ```
+	///
+	/// Requesting a type that is not in the query fails to compile:
+	///
+	/// ```rust,compile_fail
+	/// # use enrs::storages::sparse_typed_paged_map::SparseTypedPagedMap;
+	/// # use enrs::{tl, TL};
+	/// let map = SparseTypedPagedMap::<u64>::new();
+	/// let mut query = map.query::<TL![&mut usize, &mut u16]>().unwrap();
+	/// let mut query = query.lock();
+	/// query.get_checked::<TL![&isize], _>(1).unwrap();
+	/// ```
+	///
+	/// As does requesting a different mutability than the query holds:
+	///
+	/// ```rust,compile_fail
+	/// # use enrs::storages::sparse_typed_paged_map::SparseTypedPagedMap;
+	/// # use enrs::{tl, TL};
+	/// let map = SparseTypedPagedMap::<u64>::new();
+	/// let mut query = map.query::<TL![&mut usize, &mut u16]>().unwrap();
+	/// let mut query = query.lock();
+	/// query.get_checked::<TL![&usize], _>(1).unwrap();
+	/// ```
+	#[inline]
+	pub fn get_checked<'s, CTT, Indices>(
+		&'s mut self,
+		entity: EntityType,
+	) -> Result<CTT::StorageValues, SparseTypedPagedMapErrors<EntityType>>
+	where
+		CTT: 's + ComponentTupleQuery<'s, EntityType> + ComponentTupleSubset<CT, Indices>,
+	{
+		self.get::<CTT>(entity)
 	}
 }
 
@@ -1483,15 +1831,100 @@ impl<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 	// 	}
 	// }
 
+	/// Returns how many entities match this query, without locking any component storages.
+	///
+	/// This only counts over the groups this query matches, so excluded groups are not counted.
+	pub fn count(&self) -> usize {
+		let entities = self.entities.borrow();
+		self.groups
+			.borrow()
+			.iter()
+			.map(|&group| entities[group].len())
+			.sum()
+	}
+
+	/// Partitions the groups this query matches into `n` buckets of roughly equal entity counts,
+	/// so each worker of a manually driven thread pool can be handed one bucket of groups.
+	///
+	/// Groups are never split, so a single large group can still unbalance the buckets, and
+	/// buckets may be empty when there are fewer groups than `n`.  The groups in each bucket are
+	/// in ascending order.
+	pub fn group_chunks(&self, n: usize) -> Vec<Vec<usize>> {
+		let mut chunks = vec![Vec::new(); n];
+		if n == 0 {
+			return chunks;
+		}
+		let entities = self.entities.borrow();
+		let mut groups: Vec<(usize, usize)> = self
+			.groups
+			.borrow()
+			.iter()
+			.map(|&group| (entities[group].len(), group))
+			.collect();
+		// Largest first, each going to the currently lightest bucket
+		groups.sort_unstable_by(|a, b| b.cmp(a));
+		let mut totals = vec![0usize; n];
+		for (len, group) in groups {
+			let (lightest, _) = totals
+				.iter()
+				.enumerate()
+				.min_by_key(|&(_, &total)| total)
+				.unwrap();
+			totals[lightest] += len;
+			chunks[lightest].push(group);
+		}
+		for chunk in chunks.iter_mut() {
+			chunk.sort_unstable();
+		}
+		chunks
+	}
+
+	/// Records the current insert tick of every `Added` component of this query, from then on they
+	/// only match the values inserted after this call.
+	pub fn record_tick(&self) {
+		CT::record_ticks(&self.storages);
+	}
+
+	/// Iterates the slices of each matching group.
+	///
+	/// The entities stay borrowed for as long as the iterator lives, so inserting into or removing
+	/// from the map meanwhile errors with `BorrowMutError` instead of moving the slices.
 	pub fn iter_slices(&self) -> ComponentPagedIterator<EntityType, CT> {
 		ComponentPagedIterator {
 			_phantom: PhantomData,
+			_entities: OwningHandle::new(self.entities.clone()),
 			//reverse: self.reverse.clone(),
 			storages: self.storages.clone(),
 			groups: self.groups.borrow().iter().copied().collect(),
 		}
 	}
 
+	/// Returns the slices of the group when this query matches exactly one group, skipping the
+	/// iterator, otherwise `None`, as it also is when that one group is empty.
+	///
+	/// This applies to queries over a set of components only a single archetype has, such as a
+	/// tag only ever inserted with the same other components.
+	pub fn single_group_slices(&self) -> Option<CT::StorageSlices> {
+		match self.groups.borrow().as_slice() {
+			&[group] => CT::get_storage_slices_at(&self.storages, group),
+			_ => None,
+		}
+	}
+
+	/// Maps the slices of each matching group through `f`, such as into a user struct.
+	pub fn map_slices<R>(&self, f: impl FnMut(CT::StorageSlices) -> R) -> impl Iterator<Item = R> {
+		self.iter_slices().map(f)
+	}
+
+	/// Like `iter_slices` but each set of slices comes with a `GroupSliceContext` to look up the
+	/// entity owning each of their indices, without needing the entity in `CT`.
+	pub fn iter_slices_with_context(&self) -> ComponentPagedContextIterator<EntityType, CT> {
+		ComponentPagedContextIterator {
+			iter: self.iter_slices(),
+			entities: self.entities.clone(),
+		}
+	}
+
 	// pub fn iter(&self) -> ComponentPagedFlatIterator<EntityType, CT> {
 	// 	ComponentPagedFlatIterator {
 	// 		// _phantom: PhantomData,
@@ -1547,7 +1980,8 @@ impl<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 pub struct ComponentPagedIterator<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
 {
 	_phantom: PhantomData<EntityType>,
-	//reverse: Rc<RefCell<SecondaryIndex<EntityType, ComponentLocations>>>,
+	//reverse: Rc<RefCell<SecondaryEntityIndex<EntityType, ComponentLocations>>>,
+	_entities: OwningHandle<Rc<RefCell<Vec<Vec<EntityType>>>>, Ref<'static, Vec<Vec<EntityType>>>>,
 	storages: CT::Storages,
 	groups: tinyvec::TinyVec<[usize; 16]>,
 }
@@ -1558,19 +1992,77 @@ impl<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>> Iterator
 	type Item = CT::StorageSlices;
 
 	fn next(&mut self) -> Option<Self::Item> {
+		self.next_group().map(|(_group, slices)| slices)
+	}
+}
+
+impl<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>>
+	ComponentPagedIterator<EntityType, CT>
+{
+	fn next_group(&mut self) -> Option<(usize, CT::StorageSlices)> {
 		while let Some(group) = self.groups.pop() {
-			let next = CT::get_storage_slices_at(&self.storages, group);
-			if next.is_some() {
-				return next;
+			if let Some(slices) = CT::get_storage_slices_at(&self.storages, group) {
+				return Some((group, slices));
 			}
 		}
 		None
 	}
 }
 
+/// The entities of the group a set of slices from `iter_slices_with_context` came from.
+pub struct GroupSliceContext<EntityType: Entity> {
+	group: usize,
+	entities: OwningRef<
+		OwningHandle<Rc<RefCell<Vec<Vec<EntityType>>>>, Ref<'static, Vec<Vec<EntityType>>>>,
+		[EntityType],
+	>,
+}
+
+impl<EntityType: Entity> GroupSliceContext<EntityType> {
+	pub fn group(&self) -> usize {
+		self.group
+	}
+
+	/// The entities of the group, in the same order as the values of the slices.
+	pub fn entities(&self) -> &[EntityType] {
+		&self.entities
+	}
+
+	/// Returns the entity owning index `i` of the slices, panics if `i` is out of range.
+	pub fn entity_at(&self, i: usize) -> EntityType {
+		self.entities[i]
+	}
+}
+
+pub struct ComponentPagedContextIterator<
+	EntityType: Entity,
+	CT: ComponentTupleQuery<'static, EntityType>,
+> {
+	iter: ComponentPagedIterator<EntityType, CT>,
+	entities: Rc<RefCell<Vec<Vec<EntityType>>>>,
+}
+
+impl<EntityType: Entity, CT: ComponentTupleQuery<'static, EntityType>> Iterator
+	for ComponentPagedContextIterator<EntityType, CT>
+{
+	type Item = (GroupSliceContext<EntityType>, CT::StorageSlices);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (group, slices) = self.iter.next_group()?;
+		let entities = OwningRef::new(OwningHandle::new(self.entities.clone()))
+			.map(|entities| entities[group].as_slice());
+		Some((GroupSliceContext { group, entities }, slices))
+	}
+}
+
 pub trait ComponentQuery<'a, EntityType> {
 	type RawType: 'static;
 	fn get_self_typeid() -> TypeId;
+	/// The name of the component type, used to identify it in errors.
+	#[inline(always)]
+	fn get_type_name() -> &'static str {
+		std::any::type_name::<Self::RawType>()
+	}
 
 	type LenIncludeTN: generic_array::typenum::Unsigned + generic_array::ArrayLength<TypeId>;
 	type LenExcludeTN: generic_array::typenum::Unsigned + generic_array::ArrayLength<TypeId>;
@@ -1616,6 +2108,9 @@ pub trait ComponentQuery<'a, EntityType> {
 		group: usize,
 		count: usize,
 	);
+	/// Only `Added` records anything, the current insert tick of its storage.
+	#[inline(always)]
+	fn record_tick(_storage: &Self::Storage) {}
 }
 
 pub struct EntityRef;
@@ -1842,7 +2337,7 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for &T {
 	type StorageMovedValue = ();
 	#[inline(always)]
 	fn into_keyset_mapidx_vec(locked_storage: &Self::StorageLocked, out: &mut BitVec<Lsb0, usize>) {
-		out.set(locked_storage.index, true);
+		out.set(locked_storage.index.0, true);
 	}
 	#[inline(always)]
 	fn resize_locked_storage_groups(_locked_storage: &mut Self::StorageLocked, _new_size: usize) {}
@@ -1982,6 +2477,151 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Excl
 	}
 }
 
+/// Matches the entities whose `T` value was inserted after the tick last recorded by the query, at
+/// its creation or by `ComponentPagedQuery::record_tick`.  Values moved to another group keep
+/// their insert tick.
+///
+/// Each slice is the indexes in the group of those values, groups without any are skipped.  As it
+/// holds the storage of `T` immutably it can be paired with `&T` but not with `&mut T`.
+pub struct Added<T: 'static> {
+	_phantom: PhantomData<T>,
+}
+
+impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Added<T> {
+	type RawType = T;
+	#[inline(always)]
+	fn get_self_typeid() -> TypeId {
+		TypeId::of::<Added<Self::RawType>>()
+	}
+
+	type LenIncludeTN = generic_array::typenum::U1;
+	type LenExcludeTN = generic_array::typenum::U0;
+	type LenKeysetTN = generic_array::typenum::U0;
+
+	#[inline(always)]
+	fn get_include_tid() -> Option<std::any::TypeId> {
+		Some(std::any::TypeId::of::<T>())
+	}
+	#[inline(always)]
+	fn get_exclude_tid() -> Option<std::any::TypeId> {
+		None // Do nothing as this is not an exclude
+	}
+	#[inline(always)]
+	fn get_keyset_tid() -> Option<TypeId> {
+		None
+	}
+
+	#[inline]
+	fn push_matching_include_query_group(
+		_groups_to_maps: &IndexMap<Vec<TypeId>, Vec<usize>>,
+		_out: &mut Vec<usize>,
+	) {
+	}
+	#[inline]
+	fn push_matching_exclude_query_group(
+		_groups_to_maps: &IndexMap<Vec<TypeId>, Vec<usize>>,
+		_out: &mut Vec<usize>,
+	) {
+		// Do nothing as this is not an exclude
+	}
+
+	/// The storage along with the tick recorded by the query, later insert ticks were added after it
+	type Storage = (
+		Rc<RefCell<DensePagedDataActual<Self::RawType>>>,
+		Rc<Cell<u64>>,
+	);
+	#[inline]
+	fn get_storage(
+		_entities: &Rc<RefCell<Vec<Vec<EntityType>>>>,
+		maps: &MapIndexMap,
+		map_id: usize,
+	) -> Self::Storage {
+		let storage = maps
+			.get_index(map_id)
+			.unwrap()
+			.1
+			.get_strong::<Self::RawType>();
+		let tick = storage.borrow().tick();
+		(storage, Rc::new(Cell::new(tick)))
+	}
+
+	type StorageSlice = Vec<usize>;
+	#[inline]
+	fn get_storage_slice_at(storage: &Self::Storage, group: usize) -> Option<Self::StorageSlice> {
+		let since = storage.1.get();
+		let added: Vec<usize> = storage.0.borrow().ticks[group]
+			.iter()
+			.enumerate()
+			.filter(|(_index, &tick)| tick > since)
+			.map(|(index, _tick)| index)
+			.collect();
+		if added.is_empty() {
+			None
+		} else {
+			Some(added)
+		}
+	}
+
+	type StorageValue = &'a T;
+	#[inline]
+	fn get_storage_value_at(
+		locked_storage: &'a mut Self::StorageLocked,
+		group: usize,
+		index: usize,
+	) -> Option<Self::StorageValue> {
+		let (locked_storage, since) = locked_storage;
+		if locked_storage.insert_tick(GroupId(group), index)? > *since {
+			locked_storage.data[group].get(index)
+		} else {
+			None
+		}
+	}
+
+	type StorageLocked = (
+		OwningHandle<
+			Rc<RefCell<DensePagedDataActual<Self::RawType>>>,
+			Ref<'static, DensePagedDataActual<Self::RawType>>,
+		>,
+		u64,
+	);
+	#[inline(always)]
+	fn get_locked_storage(storage: &Self::Storage) -> Self::StorageLocked {
+		(OwningHandle::new(storage.0.clone()), storage.1.get())
+	}
+
+	type StorageMovedValue = ();
+	#[inline(always)]
+	fn into_keyset_mapidx_vec(
+		_locked_storage: &Self::StorageLocked,
+		_out: &mut BitVec<Lsb0, usize>,
+	) {
+	}
+	#[inline(always)]
+	fn resize_locked_storage_groups(_locked_storage: &mut Self::StorageLocked, _new_size: usize) {}
+	#[inline(always)]
+	fn insert(
+		_this: Self::StorageMovedValue,
+		_locked_storages: &mut Self::StorageLocked,
+		_group: usize,
+	) {
+		panic!("cannot insert added filters");
+	}
+
+	fn extend_vec(
+		_this: Vec<Self::StorageMovedValue>,
+		_locked_storage: &mut Self::StorageLocked,
+		_group: usize,
+		_count: usize,
+	) {
+		panic!("cannot insert added filters");
+	}
+
+	#[inline]
+	fn record_tick(storage: &Self::Storage) {
+		storage.1.set(storage.0.borrow().tick());
+	}
+}
+
 impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Option<&T> {
 	type RawType = T;
 	#[inline(always)]
@@ -2102,7 +2742,7 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Opti
 	type StorageMovedValue = ();
 	#[inline(always)]
 	fn into_keyset_mapidx_vec(locked_storage: &Self::StorageLocked, out: &mut BitVec<Lsb0, usize>) {
-		out.set(locked_storage.index, true);
+		out.set(locked_storage.index.0, true);
 	}
 	#[inline(always)]
 	fn resize_locked_storage_groups(_locked_storage: &mut Self::StorageLocked, _new_size: usize) {}
@@ -2237,11 +2877,11 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for &mut
 	type StorageMovedValue = T;
 	#[inline(always)]
 	fn into_keyset_mapidx_vec(locked_storage: &Self::StorageLocked, out: &mut BitVec<Lsb0, usize>) {
-		out.set(locked_storage.index, true);
+		out.set(locked_storage.index.0, true);
 	}
 	#[inline(always)]
 	fn resize_locked_storage_groups(locked_storage: &mut Self::StorageLocked, new_size: usize) {
-		locked_storage.data.resize_with(new_size, Vec::new);
+		locked_storage.resize_groups(new_size);
 	}
 	#[inline(always)]
 	fn insert(
@@ -2249,7 +2889,7 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for &mut
 		locked_storages: &mut Self::StorageLocked,
 		group: usize,
 	) {
-		locked_storages.data[group].push(this);
+		locked_storages.push(GroupId(group), this);
 	}
 
 	fn extend_vec(
@@ -2258,10 +2898,8 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for &mut
 		group: usize,
 		count: usize,
 	) {
-		let data = &mut locked_storage.data[group];
-		let len = data.len();
-		data.extend(this);
-		assert_eq!(data.len() - len, count);
+		let len = locked_storage.push_all(GroupId(group), this);
+		assert_eq!(locked_storage.data[group].len() - len, count);
 	}
 }
 
@@ -2385,11 +3023,11 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Opti
 	type StorageMovedValue = T;
 	#[inline(always)]
 	fn into_keyset_mapidx_vec(locked_storage: &Self::StorageLocked, out: &mut BitVec<Lsb0, usize>) {
-		out.set(locked_storage.index, true);
+		out.set(locked_storage.index.0, true);
 	}
 	#[inline(always)]
 	fn resize_locked_storage_groups(locked_storage: &mut Self::StorageLocked, new_size: usize) {
-		locked_storage.data.resize_with(new_size, Vec::new);
+		locked_storage.resize_groups(new_size);
 	}
 	#[inline(always)]
 	fn insert(
@@ -2397,7 +3035,7 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Opti
 		locked_storages: &mut Self::StorageLocked,
 		group: usize,
 	) {
-		locked_storages.data[group].push(this);
+		locked_storages.push(GroupId(group), this);
 	}
 
 	fn extend_vec(
@@ -2406,10 +3044,8 @@ impl<'a, EntityType: Entity, T: 'static> ComponentQuery<'a, EntityType> for Opti
 		group: usize,
 		count: usize,
 	) {
-		let data = &mut locked_storage.data[group];
-		let len = data.len();
-		data.extend(this);
-		assert_eq!(data.len() - len, count);
+		let len = locked_storage.push_all(GroupId(group), this);
+		assert_eq!(locked_storage.data[group].len() - len, count);
 	}
 }
 
@@ -2432,9 +3068,9 @@ pub trait ComponentTuple<EntityType: Entity>: Sized {
 	fn get_storages_group_mut(
 		maps: &MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 	) -> Self::StorageGroupsMut;
-	fn insert(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: usize);
+	fn insert(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: GroupId);
 	fn insert_in_group(self, groups: &mut Self::StorageGroupsMut);
 }
 
@@ -2458,11 +3094,11 @@ impl<EntityType: Entity> ComponentTuple<EntityType> for () {
 	fn get_storages_group_mut(
 		_maps: &MapIndexMap,
 		_map_idxs: &[usize],
-		_group: usize,
+		_group: GroupId,
 	) -> Self::StorageGroupsMut {
 	}
 	#[inline]
-	fn insert(self, _maps: &mut MapIndexMap, _map_idxs: &[usize], _group: usize) {}
+	fn insert(self, _maps: &mut MapIndexMap, _map_idxs: &[usize], _group: GroupId) {}
 	#[inline]
 	fn insert_in_group(self, _groups: &mut Self::StorageGroupsMut) {}
 }
@@ -2553,7 +3189,7 @@ macro_rules! impl_ComponentTuple {
 					let entry = maps.entry(std::any::TypeId::of::<$ID>());
 					let index = entry.index();
 					idxs.push(index);
-					entry.or_insert_with(|| Box::new(DensePagedDataInstance::<$ID>::with_groups(index, 0)));
+					entry.or_insert_with(|| Box::new(DensePagedDataInstance::<$ID>::with_groups(MapId(index), 0)));
 				})+
 			}
 			type StoragesMut = ($(Rc<RefCell<DensePagedDataActual<$ID>>>,)+);
@@ -2567,29 +3203,27 @@ macro_rules! impl_ComponentTuple {
 				},)+)
 			}
 			type StorageGroupsMut = (
-				$(OwningRefMut<
-					OwningHandle<
-						Rc<RefCell<DensePagedDataActual<$ID>>>,
-						RefMut<'static, DensePagedDataActual<$ID>>,
-					>,
-					Vec<$ID>,
-				>,)+
+				GroupId,
+				($(OwningHandle<
+					Rc<RefCell<DensePagedDataActual<$ID>>>,
+					RefMut<'static, DensePagedDataActual<$ID>>,
+				>,)+),
 			);
 			#[inline]
 			fn get_storages_group_mut(
 				maps: &MapIndexMap,
 				map_idxs: &[usize],
-				group: usize,
+				group: GroupId,
 			) -> Self::StorageGroupsMut {
-				($({
+				(group, ($({
 					let (_type_id, map) = maps
 						.get_index(map_idxs[$IDX])
 						.expect("Map is in invalid state!  Shouldn't happen!");
-					OwningRefMut::new(OwningHandle::new_mut(map.get_strong::<$ID>())).map_mut(|s| &mut s.data[group])
-				},)+)
+					OwningHandle::new_mut(map.get_strong::<$ID>())
+				},)+))
 			}
 			#[inline]
-			fn insert(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: usize) {
+			fn insert(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: GroupId) {
 				$({
 					let (_type_id, map) = maps
 						.get_index_mut(map_idxs[$IDX])
@@ -2599,7 +3233,7 @@ macro_rules! impl_ComponentTuple {
 			}
 			#[inline]
 			fn insert_in_group(self, groups: &mut Self::StorageGroupsMut) {
-				$(groups.$IDX.push(self.$IDX);)+
+				$((groups.1).$IDX.push(groups.0, self.$IDX);)+
 			}
 		}
 	};
@@ -2697,6 +3331,68 @@ pub mod indices {
 // 	}
 // }
 
+/// Lifetime-erased identity of a `ComponentQuery`, used to compare query elements at compile time
+/// regardless of the borrow lifetime they were requested with.
+pub trait ComponentQueryKey {
+	type Key: 'static;
+}
+
+impl ComponentQueryKey for EntityRef {
+	type Key = EntityRef;
+}
+
+impl<T: 'static> ComponentQueryKey for &T {
+	type Key = &'static T;
+}
+
+impl<T: 'static> ComponentQueryKey for &mut T {
+	type Key = &'static mut T;
+}
+
+impl<T: 'static> ComponentQueryKey for Option<&T> {
+	type Key = Option<&'static T>;
+}
+
+impl<T: 'static> ComponentQueryKey for Option<&mut T> {
+	type Key = Option<&'static mut T>;
+}
+
+impl<T: 'static> ComponentQueryKey for Exclude<T> {
+	type Key = Exclude<T>;
+}
+
+impl<T: 'static> ComponentQueryKey for Added<T> {
+	type Key = Added<T>;
+}
+
+/// Compile-time proof that the component tuple contains `Query`, found at the position described by
+/// the frunk `Index` (`Here`/`There<_>`), same as frunk's `Plucker`.
+pub trait ComponentTupleContains<Query, Index> {}
+
+impl<HEAD: ComponentQueryKey, TAIL, Query: ComponentQueryKey<Key = HEAD::Key>>
+	ComponentTupleContains<Query, Here> for (HEAD, TAIL)
+{
+}
+
+impl<HEAD, TAIL: ComponentTupleContains<Query, Index>, Query, Index>
+	ComponentTupleContains<Query, There<Index>> for (HEAD, TAIL)
+{
+}
+
+/// Compile-time proof that every element of this component tuple is also in `Super`, same as frunk's
+/// `Sculptor`, the `Indices` are inferred and should never need to be named.
+pub trait ComponentTupleSubset<Super, Indices> {}
+
+impl<Super> ComponentTupleSubset<Super, ()> for () {}
+
+impl<HEAD, TAIL, Super, HeadIndex, TailIndices>
+	ComponentTupleSubset<Super, (HeadIndex, TailIndices)> for (HEAD, TAIL)
+where
+	Super: ComponentTupleContains<HEAD, HeadIndex>,
+	TAIL: ComponentTupleSubset<Super, TailIndices>,
+{
+}
+
 pub trait ComponentTupleQuery<'a, EntityType: Entity> {
 	type LenIncludeTN: generic_array::typenum::Unsigned + generic_array::ArrayLength<TypeId>;
 	type LenExcludeTN: generic_array::typenum::Unsigned + generic_array::ArrayLength<TypeId>;
@@ -2755,11 +3451,12 @@ pub trait ComponentTupleQuery<'a, EntityType: Entity> {
 	// 	group: usize,
 	// ) -> Self::StorageSlices;
 	type StorageValues: 'a;
+	/// On failure returns the type name of the first component that has no value at the location.
 	fn get_storage_values_at(
 		locked_storages: &'a mut Self::StoragesLockedRef,
 		group: usize,
 		index: usize,
-	) -> Option<Self::StorageValues>;
+	) -> Result<Self::StorageValues, &'static str>;
 	type StoragesLocked: Sized;
 	type StoragesLockedRef: Sized;
 	fn get_locked_storages(storages: &Self::Storages) -> Self::StoragesLocked;
@@ -2785,6 +3482,7 @@ pub trait ComponentTupleQuery<'a, EntityType: Entity> {
 		group: usize,
 		count: usize,
 	);
+	fn record_ticks(storages: &Self::Storages);
 }
 
 impl<'a, EntityType: Entity> ComponentTupleQuery<'a, EntityType> for () {
@@ -2833,8 +3531,8 @@ impl<'a, EntityType: Entity> ComponentTupleQuery<'a, EntityType> for () {
 		_locked_storages: &'a mut Self::StoragesLockedRef,
 		_group: usize,
 		_index: usize,
-	) -> Option<Self::StorageValues> {
-		Some(())
+	) -> Result<Self::StorageValues, &'static str> {
+		Ok(())
 	}
 
 	type StoragesLocked = ();
@@ -2882,6 +3580,8 @@ impl<'a, EntityType: Entity> ComponentTupleQuery<'a, EntityType> for () {
 		_count: usize,
 	) {
 	}
+	#[inline]
+	fn record_ticks(_storages: &Self::Storages) {}
 }
 
 // impl<EntityType: Entity, A: 'static + ComponentQuery<EntityType>> ComponentTupleQuery<EntityType>
@@ -3020,7 +3720,8 @@ where
 		vec.push(index);
 		entry.or_insert_with(|| {
 			Box::new(DensePagedDataInstance::<HEAD::RawType>::with_groups(
-				index, 0,
+				MapId(index),
+				0,
 			))
 		});
 
@@ -3059,9 +3760,10 @@ where
 		locked_storages: &'a mut Self::StoragesLockedRef,
 		group: usize,
 		index: usize,
-	) -> Option<Self::StorageValues> {
-		Some((
-			HEAD::get_storage_value_at(&mut locked_storages.0, group, index)?,
+	) -> Result<Self::StorageValues, &'static str> {
+		Ok((
+			HEAD::get_storage_value_at(&mut locked_storages.0, group, index)
+				.ok_or_else(HEAD::get_type_name)?,
 			TAIL::get_storage_values_at(&mut locked_storages.1, group, index)?,
 		))
 	}
@@ -3143,6 +3845,11 @@ where
 		HEAD::extend_vec(this.0, &mut locked_storages.0, group, count);
 		TAIL::extend_vecs(this.1, &mut locked_storages.1, group, count);
 	}
+	#[inline]
+	fn record_ticks(storages: &Self::Storages) {
+		HEAD::record_tick(&storages.0);
+		TAIL::record_ticks(&storages.1);
+	}
 }
 
 // impl<
@@ -3540,14 +4247,14 @@ pub trait ComponentSet: 'static + TypeList {
 	}
 	fn populate_type_idx_vec(idxs: &mut Vec<usize>, maps: &mut MapIndexMap);
 	#[inline]
-	fn insert(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: usize) {
+	fn insert(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: GroupId) {
 		self.do_insert(maps, map_idxs, group, 0, 0);
 	}
 	fn do_insert(
 		self,
 		maps: &mut MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 		map_idx_idx: usize,
 		data_index: usize,
 	);
@@ -3578,7 +4285,7 @@ impl ComponentSet for HNil {
 		self,
 		_maps: &mut MapIndexMap,
 		_map_idxs: &[usize],
-		_group: usize,
+		_group: GroupId,
 		_map_idx_idx: usize,
 		_data_index: usize,
 	) {
@@ -3606,7 +4313,8 @@ where
 		let entry = maps.entry(std::any::TypeId::of::<H>());
 		let index = entry.index();
 		idxs.push(index);
-		entry.or_insert_with(|| Box::new(DensePagedDataInstance::<H>::with_groups(index, 0)));
+		entry
+			.or_insert_with(|| Box::new(DensePagedDataInstance::<H>::with_groups(MapId(index), 0)));
 		T::populate_type_idx_vec(idxs, maps);
 	}
 
@@ -3615,7 +4323,7 @@ where
 		self,
 		maps: &mut MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 		map_idx_idx: usize,
 		_data_index: usize,
 	) {
@@ -3634,7 +4342,10 @@ where
 		let tid = std::any::TypeId::of::<H>();
 		let index = maps.len();
 		maps.entry(tid).or_insert_with(|| {
-			Box::new(DensePagedDataInstance::<H>::with_groups(index, group_size))
+			Box::new(DensePagedDataInstance::<H>::with_groups(
+				MapId(index),
+				group_size,
+			))
 		});
 		T::ensure_exists(maps, group_size);
 	}
@@ -3653,6 +4364,8 @@ where
 
 pub trait ComponentSliceSet: HList + TypeList {
 	fn all_same_len(&self, len: usize) -> bool;
+	/// Pushes the `TypeId` of the item type of each iterator, `TypeList` gives the iterator types.
+	fn populate_item_type_ids(tids: &mut Vec<TypeId>);
 	#[inline]
 	fn into_type_idx_vec(&self, maps: &mut MapIndexMap) -> Vec<usize> {
 		let mut idxs = Vec::with_capacity(Self::LEN);
@@ -3661,25 +4374,25 @@ pub trait ComponentSliceSet: HList + TypeList {
 	}
 	fn populate_type_idx_vec(&self, idxs: &mut Vec<usize>, maps: &mut MapIndexMap);
 	#[inline]
-	fn insert_all(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: usize) -> usize {
+	fn insert_all(self, maps: &mut MapIndexMap, map_idxs: &[usize], group: GroupId) -> usize {
 		self.do_insert_all(maps, map_idxs, group, 0, 0)
 	}
 	fn do_insert_all(
 		self,
 		maps: &mut MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 		map_idx_idx: usize,
 		start_index: usize,
 	) -> usize;
 	#[inline]
-	fn truncate(maps: &mut MapIndexMap, map_idxs: &[usize], group: usize, size: usize) {
+	fn truncate(maps: &mut MapIndexMap, map_idxs: &[usize], group: GroupId, size: usize) {
 		Self::do_truncate(maps, map_idxs, group, size, 0);
 	}
 	fn do_truncate(
 		maps: &mut MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 		size: usize,
 		map_idxs_idx: usize,
 	);
@@ -3691,13 +4404,15 @@ impl ComponentSliceSet for HNil {
 		true
 	}
 	#[inline]
+	fn populate_item_type_ids(_tids: &mut Vec<TypeId>) {}
+	#[inline]
 	fn populate_type_idx_vec(&self, _idxs: &mut Vec<usize>, _maps: &mut MapIndexMap) {}
 	#[inline]
 	fn do_insert_all(
 		self,
 		_maps: &mut MapIndexMap,
 		_map_idxs: &[usize],
-		_group: usize,
+		_group: GroupId,
 		_map_idx_idx: usize,
 		start_index: usize,
 	) -> usize {
@@ -3707,7 +4422,7 @@ impl ComponentSliceSet for HNil {
 	fn do_truncate(
 		_maps: &mut MapIndexMap,
 		_map_idxs: &[usize],
-		_group: usize,
+		_group: GroupId,
 		_size: usize,
 		_map_idxs_idx: usize,
 	) {
@@ -3728,12 +4443,19 @@ where
 		self.head.len() == len && self.tail.all_same_len(len)
 	}
 
+	#[inline]
+	fn populate_item_type_ids(tids: &mut Vec<TypeId>) {
+		tids.push(TypeId::of::<H>());
+		T::populate_item_type_ids(tids);
+	}
+
 	#[inline]
 	fn populate_type_idx_vec(&self, idxs: &mut Vec<usize>, maps: &mut MapIndexMap) {
 		let entry = maps.entry(std::any::TypeId::of::<H>());
 		let index = entry.index();
 		idxs.push(index);
-		entry.or_insert_with(|| Box::new(DensePagedDataInstance::<H>::with_groups(index, 0)));
+		entry
+			.or_insert_with(|| Box::new(DensePagedDataInstance::<H>::with_groups(MapId(index), 0)));
 		self.tail.populate_type_idx_vec(idxs, maps);
 	}
 
@@ -3741,7 +4463,7 @@ where
 		self,
 		maps: &mut MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 		map_idx_idx: usize,
 		_start_index: usize,
 	) -> usize {
@@ -3758,7 +4480,7 @@ where
 	fn do_truncate(
 		maps: &mut MapIndexMap,
 		map_idxs: &[usize],
-		group: usize,
+		group: GroupId,
 		size: usize,
 		map_idxs_idx: usize,
 	) {
@@ -4125,19 +4847,44 @@ mod tests {
 
 	#[test]
 	fn sparse_typed_page_multimap_tests() {
-		// let mut map = SparseTypedPagedMap::<u64>::new();
-		// assert_eq!(map.insert(1, (21usize, 6.28f32, true)), Ok(()));
-		//
-		// assert!(map.remove(2).is_err());
-		//
-		// let inserts: Vec<_> = (2..10u64).map(|i| (i, (21usize, 6.28f32, true))).collect();
-		// assert_eq!(map.extend_iter(inserts), Ok(()));
-		//
-		// assert_eq!(map.contains(2), true);
-		// map.remove(2).unwrap();
-		// assert_eq!(map.contains(2), false);
-		// assert!(map.remove(2).is_err());
-		//
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		assert_eq!(
+			map.insert::<TL![&mut usize, &mut f32, &mut bool]>(1, tl![21, 6.5, true]),
+			Ok(())
+		);
+
+		assert!(map.remove(2).is_err());
+
+		for i in 2..10u64 {
+			assert_eq!(
+				map.insert::<TL![&mut usize, &mut f32, &mut bool]>(i, tl![21, 6.5, true]),
+				Ok(())
+			);
+		}
+
+		assert_eq!(map.contains(2), true);
+		map.remove(2).unwrap();
+		assert_eq!(map.contains(2), false);
+		assert!(map.remove(2).is_err());
+
+		assert_eq!(
+			map.insert::<TL![&mut usize, &mut f32, &mut bool]>(3, tl![21, 6.5, true]),
+			Err(SparseTypedPagedMapErrors::SecondaryIndexError(
+				SecondaryEntityIndexErrors::IndexAlreadyExists(3)
+			))
+		);
+		assert_eq!(
+			*map.query::<TL![&mut usize, &mut f32, &mut bool]>()
+				.unwrap()
+				.lock()
+				.get::<TL![&mut usize]>(9)
+				.unwrap()
+				.0,
+			21
+		);
+
+		// TODO:  `extend_iter` and `extend_iters` are not yet reimplemented, use `extend_vecs` on a
+		// locked query for bulk insertion instead
 		// assert_eq!(
 		// 	map.extend_iters(
 		// 		vec![11u64, 12u64, 13u64].into_iter(),
@@ -4161,7 +4908,7 @@ mod tests {
 		// 		)
 		// 	),
 		// 	Err(SparseTypedPagedMapErrors::SecondaryIndexError(
-		// 		SecondaryIndexErrors::IndexAlreadyExists(22)
+		// 		SecondaryEntityIndexErrors::IndexAlreadyExists(22)
 		// 	))
 		// );
 		// assert_eq!(
@@ -4174,11 +4921,67 @@ mod tests {
 		// 		)
 		// 	),
 		// 	Err(SparseTypedPagedMapErrors::SecondaryIndexError(
-		// 		SecondaryIndexErrors::IndexAlreadyExists(22)
+		// 		SecondaryEntityIndexErrors::IndexAlreadyExists(22)
 		// 	))
 		// );
 	}
 
+	#[test]
+	fn extend_hlist() {
+		use crate::frunk::hlist;
+
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		assert_eq!(
+			map.extend_hlist(
+				vec![11u64, 12, 13].into_iter(),
+				hlist!(
+					vec![1usize, 2, 3].into_iter(),
+					vec![1.0f32, 2.0, 3.0].into_iter(),
+					vec![true, false, true].into_iter(),
+				)
+			),
+			Ok(())
+		);
+		assert_eq!(map.query::<TL![&usize, &f32, &bool]>().unwrap().count(), 3);
+		// Same group as inserting the same component types one at a time
+		map.insert::<TL![&mut usize, &mut f32, &mut bool]>(14, tl![4, 4.0, false])
+			.unwrap();
+		assert_eq!(map.entities.borrow().len(), 1);
+		assert_eq!(
+			*map.query::<TL![&mut f32]>()
+				.unwrap()
+				.lock()
+				.get::<TL![&mut f32]>(12)
+				.unwrap()
+				.0,
+			2.0
+		);
+
+		assert_eq!(
+			map.extend_hlist(
+				vec![21u64, 22].into_iter(),
+				hlist!(
+					vec![1usize, 2, 3].into_iter(),
+					vec![true, false].into_iter(),
+				)
+			),
+			Err(SparseTypedPagedMapErrors::IteratorsNotAllSameLength)
+		);
+		assert!(!map.contains(21));
+		// A duplicate entity undoes the whole batch
+		assert_eq!(
+			map.extend_hlist(
+				vec![21u64, 12].into_iter(),
+				hlist!(vec![1usize, 2].into_iter(), vec![1.0f32, 2.0].into_iter())
+			),
+			Err(SparseTypedPagedMapErrors::SecondaryIndexError(
+				SecondaryEntityIndexErrors::IndexAlreadyExists(12)
+			))
+		);
+		assert!(!map.contains(21));
+		assert_eq!(map.query::<TL![&usize]>().unwrap().count(), 4);
+	}
+
 	#[test]
 	fn empty_entities() {
 		// let mut map = SparseTypedPagedMap::<u64>::new();
@@ -4473,7 +5276,226 @@ mod tests {
 		);
 	}
 
-	// TODO:  Figure out how to enforce this get type stuff at compile time, frunk can do it...
+	#[test]
+	fn queries_get_checked() {
+		let map = SparseTypedPagedMap::<u64>::new();
+		let mut query = map.query::<TL![&mut usize, &mut u16]>().unwrap();
+		let mut query = query.lock();
+		assert!(query.get_checked::<TL![&mut u16], _>(1).is_err());
+		query.insert(1, tl!(21, 2)).unwrap();
+		assert_eq!(*query.get_checked::<TL![&mut usize], _>(1).unwrap().0, 21);
+		assert_eq!(
+			*query
+				.get_checked::<TL![&mut u16, &mut usize], _>(1)
+				.unwrap()
+				.0,
+			2
+		);
+	}
+
+	#[test]
+	fn queries_count() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		let query = map.query::<TL![&usize]>().unwrap();
+		assert_eq!(query.count(), 0);
+		map.insert::<TL![&mut usize, &mut u8]>(1, tl![1, 1])
+			.unwrap();
+		map.insert::<TL![&mut usize, &mut u8]>(2, tl![2, 2])
+			.unwrap();
+		map.insert::<TL![&mut usize, &mut u16]>(3, tl![3, 3])
+			.unwrap();
+		map.insert::<TL![&mut u32]>(4, tl![4]).unwrap();
+		map.insert::<TL![&mut u32]>(5, tl![5]).unwrap();
+		assert_eq!(query.count(), 3);
+		assert_eq!(map.query::<TL![&u32]>().unwrap().count(), 2);
+		map.remove(1).unwrap();
+		assert_eq!(query.count(), 2);
+	}
+
+	#[test]
+	fn prepared_query_refresh() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		map.insert::<TL![&mut usize, &mut u8]>(1, tl![1, 1])
+			.unwrap();
+		let mut prepared = map.prepared_query::<TL![&usize]>();
+		assert_eq!(prepared.groups().len(), 1);
+		assert_eq!(prepared.refresh(&map), 0);
+		map.insert::<TL![&mut u16]>(2, tl![2]).unwrap();
+		map.insert::<TL![&mut usize, &mut u32]>(3, tl![3, 3])
+			.unwrap();
+		assert_eq!(prepared.refresh(&map), 1);
+		assert_eq!(prepared.groups().len(), 2);
+		assert_eq!(prepared.refresh(&map), 0);
+		// `query` creates the group of exactly its own types, which also matches
+		let query = map.query::<TL![&usize]>().unwrap();
+		assert_eq!(prepared.refresh(&map), 1);
+		assert_eq!(prepared.groups(), &*query.groups.borrow());
+	}
+
+	#[test]
+	fn queries_keyed_by_exclude() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		map.insert::<TL![&mut usize]>(1, tl![1]).unwrap();
+		map.insert::<TL![&mut usize, &mut u16]>(2, tl![2, 2])
+			.unwrap();
+		let mut all = map.prepared_query::<TL![&usize]>();
+		let mut excluding = map.prepared_query::<TL![&usize, Exclude<u16>]>();
+		assert_eq!(all.groups().len(), 2);
+		assert_eq!(excluding.groups().len(), 1);
+		assert_ne!(all.groups(), excluding.groups());
+		// Groups created afterwards are matched against the excludes too
+		map.insert::<TL![&mut usize, &mut u16, &mut u8]>(3, tl![3, 3, 3])
+			.unwrap();
+		map.insert::<TL![&mut usize, &mut u8]>(4, tl![4, 4])
+			.unwrap();
+		assert_eq!(all.refresh(&map), 2);
+		assert_eq!(excluding.refresh(&map), 1);
+		assert_eq!(all.groups().len(), 4);
+		assert_eq!(excluding.groups().len(), 2);
+	}
+
+	#[test]
+	fn iter_slices_blocks_structural_changes() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		map.insert::<TL![&mut usize]>(1, tl![1]).unwrap();
+		map.insert::<TL![&mut usize]>(2, tl![2]).unwrap();
+		let query = map.query::<TL![&usize]>().unwrap();
+		{
+			let mut iter = query.iter_slices();
+			assert!(matches!(
+				map.remove(1),
+				Err(SparseTypedPagedMapErrors::BorrowMutError)
+			));
+			assert!(matches!(
+				map.insert::<TL![&mut usize]>(3, tl![3]),
+				Err(SparseTypedPagedMapErrors::BorrowMutError)
+			));
+			assert!(iter.next().is_some());
+		}
+		map.remove(1).unwrap();
+		assert!(!map.contains(1));
+		assert_eq!(query.count(), 1);
+	}
+
+	#[test]
+	fn iter_slices_with_context() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		map.insert::<TL![&mut usize]>(1, tl![10]).unwrap();
+		map.insert::<TL![&mut usize, &mut u16]>(2, tl![20, 2])
+			.unwrap();
+		map.insert::<TL![&mut usize]>(3, tl![30]).unwrap();
+		let query = map.query::<TL![&usize]>().unwrap();
+		let mut seen = 0;
+		for (context, slices) in query.iter_slices_with_context() {
+			assert_eq!(context.entities().len(), slices.0.len());
+			for (i, &value) in slices.0.iter().enumerate() {
+				assert_eq!(context.entity_at(i) as usize * 10, value);
+				seen += 1;
+			}
+		}
+		assert_eq!(seen, 3);
+	}
+
+	#[test]
+	fn single_group_slices() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		map.insert::<TL![&mut usize]>(1, tl![1]).unwrap();
+		map.insert::<TL![&mut usize, &mut u16]>(2, tl![2, 20])
+			.unwrap();
+		map.insert::<TL![&mut usize, &mut u16]>(3, tl![3, 30])
+			.unwrap();
+		let single = map.query::<TL![&usize, &u16]>().unwrap();
+		let (usizes, (u16s, ())) = single.single_group_slices().unwrap();
+		assert_eq!(&*usizes, &[2, 3]);
+		assert_eq!(&*u16s, &[20, 30]);
+		let multiple = map.query::<TL![&usize]>().unwrap();
+		assert!(multiple.single_group_slices().is_none());
+	}
+
+	#[test]
+	fn map_slices() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		for entity in 1..=3 {
+			map.insert::<TL![&mut usize, &mut String]>(entity, tl![0, String::new()])
+				.unwrap();
+		}
+		for entity in 4..=5 {
+			map.insert::<TL![&mut usize, &mut String, &mut u16]>(entity, tl![0, String::new(), 0])
+				.unwrap();
+		}
+		map.insert::<TL![&mut usize]>(6, tl![0]).unwrap();
+		let query = map.query::<TL![&usize, &String]>().unwrap();
+		let mut counts: Vec<usize> = query
+			.map_slices(|(usizes, (strings, ()))| {
+				assert_eq!(usizes.len(), strings.len());
+				usizes.len()
+			})
+			.collect();
+		counts.sort_unstable();
+		assert_eq!(counts, vec![2, 3]);
+	}
+
+	#[test]
+	fn swap_to_group_typed_ids() {
+		let storage = DensePagedDataInstance::<usize>::with_groups(MapId(0), 3);
+		storage
+			.0
+			.borrow_mut()
+			.push_all(GroupId(0), vec![10, 11, 12]);
+		// With bare `usize`s `swap_to_group(1, 0, 2)` compiled just as well and moved the wrong value
+		assert_eq!(storage.swap_to_group(GroupId(0), 1, GroupId(2)), 0);
+		storage.truncate_group(GroupId(0), 1);
+		let actual = storage.0.borrow();
+		assert_eq!(actual.index.index(), 0);
+		assert_eq!(actual.data[0], vec![10]);
+		assert_eq!(actual.data[2], vec![11]);
+	}
+
+	#[test]
+	fn queries_group_chunks() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		let query = map.query::<TL![&usize]>().unwrap();
+		// `query` created the still empty group of exactly its own types
+		assert_eq!(query.group_chunks(3), vec![vec![0], vec![], vec![]]);
+		let mut entity = 1;
+		macro_rules! fill {
+			($T:ty, $count:expr) => {
+				for _ in 0..$count {
+					map.insert::<TL![&mut usize, &mut $T]>(entity, tl![0, Default::default()])
+						.unwrap();
+					entity += 1;
+				}
+			};
+		}
+		fill!(u8, 9);
+		fill!(u16, 7);
+		fill!(u32, 6);
+		fill!(u64, 5);
+		fill!(i8, 4);
+		fill!(i16, 2);
+		fill!(i32, 1);
+		let chunks = query.group_chunks(3);
+		assert_eq!(chunks.len(), 3);
+		let mut all: Vec<usize> = chunks.iter().flatten().copied().collect();
+		all.sort_unstable();
+		all.dedup();
+		assert_eq!(all.len(), 8);
+		let totals: Vec<usize> = chunks
+			.iter()
+			.map(|chunk| {
+				chunk
+					.iter()
+					.map(|&group| map.entities.borrow()[group].len())
+					.sum()
+			})
+			.collect();
+		assert_eq!(totals.iter().sum::<usize>(), 34);
+		let (min, max) = (totals.iter().min().unwrap(), totals.iter().max().unwrap());
+		assert!(max - min <= 2, "unbalanced chunks {:?}", totals);
+		assert!(query.group_chunks(0).is_empty());
+	}
+
+	// Use `get_checked` to have these enforced at compile time instead.
 	#[test]
 	#[should_panic]
 	fn should_panic_get_incorrect_type() {
@@ -4571,4 +5593,80 @@ mod tests {
 			//for () in query.iter() {}
 		}
 	}
+
+	#[test]
+	fn get_missing_component_error_names_type() {
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		map.insert::<TL![&mut usize]>(1, tl![21]).unwrap();
+		let mut query = map.query::<TL![&mut usize, &mut u16]>().unwrap();
+		let error = query.lock().get::<TL![&mut u16]>(1).err().unwrap();
+		assert_eq!(
+			error,
+			SparseTypedPagedMapErrors::EntityDoesNotExistInStorage(1, "u16")
+		);
+		assert!(format!("{}", error).contains("u16"));
+	}
+
+	#[test]
+	fn insert_ticks_follow_their_values() {
+		let storage = DensePagedDataInstance::<usize>::with_groups(MapId(0), 2);
+		let mut data = storage.0.borrow_mut();
+		data.push(GroupId(0), 10);
+		data.push_all(GroupId(0), vec![11, 12]);
+		data.push(GroupId(0), 13);
+		assert_eq!(data.tick(), 3);
+		assert_eq!(data.swap_remove(GroupId(0), 0), 10);
+		assert_eq!(data.data[0], vec![13, 11, 12]);
+		assert_eq!(data.ticks[0], vec![3, 2, 2]);
+		assert_eq!(data.swap_to_group(GroupId(0), 0, GroupId(1)), 0);
+		assert_eq!(data.data[1], vec![13]);
+		assert_eq!(data.insert_tick(GroupId(1), 0), Some(3));
+		data.truncate_group(GroupId(0), 1);
+		assert_eq!(data.ticks[0], vec![2]);
+		data.resize_groups(3);
+		assert_eq!(data.insert_tick(GroupId(2), 0), None);
+		assert_eq!(data.tick(), 3);
+	}
+
+	#[test]
+	fn queries_added() {
+		fn added_values(
+			query: &ComponentPagedQuery<u64, TL![&'static usize, Added<usize>]>,
+		) -> Vec<usize> {
+			let mut values = vec![];
+			for (slice, (added, ())) in query.iter_slices() {
+				values.extend(added.into_iter().map(|index| slice[index]));
+			}
+			values.sort_unstable();
+			values
+		}
+
+		let mut map = SparseTypedPagedMap::<u64>::new();
+		let mut added = map.query::<TL![&usize, Added<usize>]>().unwrap();
+		map.insert::<TL![&mut usize]>(1, tl![1]).unwrap();
+		map.insert::<TL![&mut usize]>(2, tl![2]).unwrap();
+		map.insert::<TL![&mut usize, &mut u8]>(3, tl![3, 3])
+			.unwrap();
+		assert_eq!(added_values(&added), vec![1, 2, 3]);
+		added.record_tick();
+		assert_eq!(added_values(&added), Vec::<usize>::new());
+
+		map.insert::<TL![&mut usize]>(4, tl![4]).unwrap();
+		map.insert::<TL![&mut usize]>(5, tl![5]).unwrap();
+		map.insert::<TL![&mut usize, &mut u8]>(6, tl![6, 6])
+			.unwrap();
+		map.insert::<TL![&mut u8]>(7, tl![7]).unwrap();
+		assert_eq!(added_values(&added), vec![4, 5, 6]);
+		{
+			let mut lock = added.lock();
+			assert_eq!(*lock.get::<TL![Added<usize>]>(4).unwrap().0, 4);
+			assert!(lock.get::<TL![Added<usize>]>(1).is_err());
+		}
+		// The last value of the group takes the place of the removed one along with its tick
+		map.remove(1).unwrap();
+		map.remove(4).unwrap();
+		assert_eq!(added_values(&added), vec![5, 6]);
+		added.record_tick();
+		assert_eq!(added_values(&added), Vec::<usize>::new());
+	}
 }
@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::collections::hash_map::RandomState;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 use std::fmt::Debug;
@@ -13,6 +14,7 @@ use std::fmt::Formatter;
 /// TypedIndexMap specific errors
 pub enum TypedIndexMapError<K, V, I: TypedIndexMapIndexType = usize> {
 	TypedIndexMapFull(I, K, V),
+	IndexOutOfRange(usize, usize),
 }
 
 impl<K, V, I: TypedIndexMapIndexType> Debug for TypedIndexMapError<K, V, I> {
@@ -22,6 +24,11 @@ impl<K, V, I: TypedIndexMapIndexType> Debug for TypedIndexMapError<K, V, I> {
 			TypedIndexMapFull(max, _key, _value) => {
 				f.debug_tuple("TypedIndexMapFull").field(max).finish()
 			}
+			IndexOutOfRange(index, len) => f
+				.debug_tuple("IndexOutOfRange")
+				.field(index)
+				.field(len)
+				.finish(),
 		}
 	}
 }
@@ -32,6 +39,10 @@ impl<K, V, I: TypedIndexMapIndexType> std::fmt::Display for TypedIndexMapError<K
 			TypedIndexMapError::TypedIndexMapFull(size, _k, _v) => {
 				f.write_fmt(format_args!("TypedIndexMap index is full with {:?}", size))
 			}
+			TypedIndexMapError::IndexOutOfRange(index, len) => f.write_fmt(format_args!(
+				"TypedIndexMap index {} is out of range of length {}",
+				index, len
+			)),
 		}
 	}
 }
@@ -215,6 +226,36 @@ where
 	pub fn capacity(&self) -> usize {
 		self.index_map.capacity()
 	}
+
+	/// Return a reference to the wrapped `IndexMap`.
+	#[inline]
+	pub fn as_inner(&self) -> &IndexMap<K, V, S> {
+		&self.index_map
+	}
+
+	/// Unwrap into the wrapped `IndexMap`, use `TypedIndexMap::try_from` to wrap it again.
+	#[inline]
+	pub fn into_inner(self) -> IndexMap<K, V, S> {
+		self.index_map
+	}
+}
+
+impl<T, K, V, I, S> TryFrom<IndexMap<K, V, S>> for TypedIndexMap<T, K, V, I, S>
+where
+	I: TypedIndexMapIndexType,
+{
+	/// The map is given back if it holds more entries than `I` can index.
+	type Error = IndexMap<K, V, S>;
+
+	fn try_from(index_map: IndexMap<K, V, S>) -> Result<Self, Self::Error> {
+		if I::try_from_usize(index_map.len()).is_none() {
+			return Err(index_map);
+		}
+		Ok(TypedIndexMap {
+			index_map,
+			_phantom: Default::default(),
+		})
+	}
 }
 
 impl<T, K, V, I, S> TypedIndexMap<T, K, V, I, S>
@@ -306,6 +347,15 @@ where
 		self.index_map.iter()
 	}
 
+	/// Return an iterator over the typed indices and key-value pairs of the map, in their order
+	#[inline]
+	pub fn enumerate_typed(&self) -> impl Iterator<Item = (TypedIndexMapIndex<T, I>, &K, &V)> {
+		self.index_map
+			.iter()
+			.enumerate()
+			.map(|(idx, (k, v))| (TypedIndexMapIndex::new(idx), k, v))
+	}
+
 	/// Return an iterator over the key-value pairs of the map, in their order
 	#[inline]
 	pub fn iter_mut(&mut self) -> IterMut<K, V> {
@@ -648,6 +698,24 @@ where
 		self.index_map.get_index(index.into())
 	}
 
+	/// Get a key-value pair by index, returning an error describing the requested index and the
+	/// current length when it is out of range instead of `None` like `get_index`
+	///
+	/// Valid indices are *0 <= index < self.len()*
+	///
+	/// Computes in **O(1)** time.
+	#[inline]
+	pub fn get_index_checked(
+		&self,
+		index: TypedIndexMapIndex<T, I>,
+	) -> Result<(&K, &V), TypedIndexMapError<K, V, I>> {
+		let index: usize = index.into();
+		let len = self.index_map.len();
+		self.index_map
+			.get_index(index)
+			.ok_or(TypedIndexMapError::IndexOutOfRange(index, len))
+	}
+
 	/// Get a key-value pair by index
 	///
 	/// Valid indices are *0 <= index < self.len()*
@@ -686,3 +754,60 @@ where
 		self.index_map.shift_remove_index(index.into())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	struct Tag;
+
+	#[test]
+	fn get_index_checked() {
+		let mut map = TypedIndexMap::<Tag, &'static str, usize>::new();
+		map.insert("first", 1).unwrap();
+		map.insert("second", 2).unwrap();
+		assert_eq!(
+			map.get_index_checked(TypedIndexMapIndex::new(1)).ok(),
+			Some((&"second", &2))
+		);
+		match map.get_index_checked(TypedIndexMapIndex::new(5)) {
+			Err(TypedIndexMapError::IndexOutOfRange(5, 2)) => (),
+			other => panic!("expected IndexOutOfRange(5, 2), got {:?}", other),
+		}
+		assert!(map.get_index(TypedIndexMapIndex::new(5)).is_none());
+	}
+
+	#[test]
+	fn enumerate_typed() {
+		let mut map = TypedIndexMap::<Tag, &'static str, usize, u8>::new();
+		map.insert("first", 1).unwrap();
+		map.insert("second", 2).unwrap();
+		map.insert("third", 3).unwrap();
+		let mut count = 0;
+		for (idx, key, value) in map.enumerate_typed() {
+			assert_eq!(map.get_index_of(key), Some(idx));
+			assert_eq!(map.get_index(idx), Some((key, value)));
+			count += 1;
+		}
+		assert_eq!(count, map.len());
+	}
+
+	#[test]
+	fn into_inner_round_trip() {
+		let mut map = TypedIndexMap::<Tag, usize, usize, u8>::new();
+		for i in 0..10 {
+			map.insert(i, i * 2).unwrap();
+		}
+		let inner = map.into_inner();
+		assert_eq!(inner.len(), 10);
+		let map = TypedIndexMap::<Tag, usize, usize, u8>::try_from(inner).unwrap();
+		assert_eq!(map.as_inner().len(), 10);
+		assert_eq!(map.get(&3), Some(&6));
+		assert_eq!(map.get_index(TypedIndexMapIndex::new(9)), Some((&9, &18)));
+		let mut inner = map.into_inner();
+		inner.extend((10..300).map(|i| (i, i * 2)));
+		let inner = TypedIndexMap::<Tag, usize, usize, u8>::try_from(inner).unwrap_err();
+		assert_eq!(inner.len(), 300);
+	}
+}
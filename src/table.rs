@@ -1,7 +1,7 @@
 pub mod fields;
 
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 use crate::database::{DatabaseId, TableId};
 // use crate::table::fields::IndexField;
@@ -43,6 +43,26 @@ pub trait Table: 'static {
 	fn get_database_id(&self) -> DatabaseId;
 	fn table_name(&self) -> &str;
 	fn table_id(&self) -> TableId;
+	/// Renames this table, when it is in a `Database` then use `Tables::rename` instead so the name
+	/// lookup stays in sync.  Returns false if this table cannot be renamed, which is the default.
+	fn rename(&mut self, _new_name: &str) -> bool {
+		false
+	}
+	/// Re-stamps the `DatabaseId` and `TableId` of this table, only the `Database` should call this.
+	/// Returns false if this table cannot be moved to another `Database`, which is the default.
+	fn rehome(&mut self, _database_id: DatabaseId, _table_id: TableId) -> bool {
+		false
+	}
+	/// The `EntityTable` this table registered its delete hook on, if any.
+	fn entity_table(&self) -> Option<Rc<RefCell<dyn Table>>> {
+		None
+	}
+	/// Removes every row from this table while keeping its registration and storage layout, tables
+	/// that hold no rows of their own can leave this as a no-op.
+	fn clear_table(&mut self) {}
+	/// Runs the deletions this table deferred, such as those queued by `EntityTable::defer_delete`,
+	/// tables that defer nothing can leave this as a no-op.
+	fn flush_deletions(&mut self) {}
 	// /// Get's the index count for when calling `get_index_metadata(0..indexes_len())`.
 	// /// Should always be at least 1 in length to be dynamically accessible.
 	// fn indexes_len(&self) -> usize;
@@ -64,10 +84,41 @@ impl dyn Table {
 	}
 }
 
+/// A weak reference to a table that does not keep it alive, for subsystems that cache a table but
+/// must not hold it past the teardown of its `Database`.
+pub struct TableHandle<T: ?Sized = dyn Table> {
+	table: Weak<RefCell<T>>,
+}
+
+impl<T: ?Sized> TableHandle<T> {
+	pub fn new(table: &Rc<RefCell<T>>) -> Self {
+		TableHandle {
+			table: Rc::downgrade(table),
+		}
+	}
+
+	/// Returns the table if it is still alive.
+	pub fn upgrade(&self) -> Option<Rc<RefCell<T>>> {
+		self.table.upgrade()
+	}
+
+	pub fn is_alive(&self) -> bool {
+		self.table.strong_count() > 0
+	}
+}
+
+impl<T: ?Sized> Clone for TableHandle<T> {
+	fn clone(&self) -> Self {
+		TableHandle {
+			table: self.table.clone(),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use crate::database::*;
-	use crate::table::Table;
+	use crate::table::{Table, TableHandle};
 	use crate::tables::dense_entity_value_table::DenseEntityValueTable;
 	use crate::tables::entity_table::EntityTable;
 
@@ -92,6 +143,56 @@ mod tests {
 		assert_eq!(entity, 1);
 	}
 
+	#[test]
+	fn table_handle() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let typed_handle = TableHandle::new(&entities_storage);
+		drop(entities_storage);
+		let handle = database.tables.weak_handle("entities").unwrap();
+		assert!(database.tables.weak_handle("missing").is_err());
+		assert!(handle.is_alive());
+		assert_eq!(handle.upgrade().unwrap().borrow().table_name(), "entities");
+		assert!(!typed_handle.upgrade().unwrap().borrow().contains(1));
+		drop(database);
+		assert!(!handle.is_alive());
+		assert!(handle.upgrade().is_none());
+		assert!(!typed_handle.is_alive());
+	}
+
+	#[test]
+	fn table_handle_of_removed_table() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ints_storage = database
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let entity = entities_storage.borrow_mut().insert().raw();
+		ints_storage
+			.borrow_mut()
+			.insert(entities_storage.borrow().valid(entity).unwrap(), 42)
+			.unwrap();
+		let handle = database.tables.weak_handle("ints").unwrap();
+		let typed_handle = TableHandle::new(&ints_storage);
+		let removed = database.tables.remove("ints").unwrap();
+		assert!(handle.is_alive());
+		drop((removed, ints_storage));
+		// Only the delete hook on the entity table is left and it does not keep the table alive
+		assert!(!handle.is_alive());
+		assert!(!typed_handle.is_alive());
+		entities_storage.borrow_mut().delete(entity).unwrap();
+	}
+
 	#[test]
 	#[should_panic]
 	fn cannot_mutate_entities_with_valid_active() {
@@ -5,13 +5,19 @@ use crate::tables::{EntityTable, ValidEntity};
 use crate::utils::secondary_entity_index::{SecondaryEntityIndex, SecondaryEntityIndexErrors};
 use crate::utils::unique_hasher::UniqueHasherBuilder;
 use arrayvec::ArrayVec;
+use bitvec::slice::BitSlice;
+use bitvec::vec::BitVec;
 use indexmap::map::IndexMap;
 use owning_ref::OwningHandle;
 use smallvec::SmallVec;
 use smol_str::SmolStr;
 use std::any::{Any, TypeId};
 use std::cell::{BorrowMutError, Ref, RefCell, RefMut};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::rc::{Rc, Weak};
 
 #[derive(Debug)]
@@ -25,6 +31,12 @@ pub enum DenseEntityDynamicPagedMultiValueTableErrors<EntityType: Entity> {
 	EntityDoesNotExistInStorage(EntityType, &'static str),
 	EntityGenerationMismatch(EntityType, EntityType),
 	IteratorsNotAllSameLength,
+	GroupDoesNotExist(usize),
+	EntitiesNotInSameGroup(EntityType, EntityType),
+	StorageNotCoveredByTypes(usize, TypeId),
+	EntityLocationInvalid(EntityType, usize, usize),
+	ComponentTypeRequestedTwice(&'static str),
+	IoError(io::Error),
 }
 
 impl<EntityType: Entity> std::error::Error
@@ -42,6 +54,12 @@ impl<EntityType: Entity> std::error::Error
 			EntityDoesNotExistInStorage(_entity, _name) => None,
 			EntityGenerationMismatch(_requested_entity, _existing_entity) => None,
 			IteratorsNotAllSameLength => None,
+			GroupDoesNotExist(_group) => None,
+			EntitiesNotInSameGroup(_first, _second) => None,
+			StorageNotCoveredByTypes(_group, _tid) => None,
+			EntityLocationInvalid(_entity, _group, _index) => None,
+			ComponentTypeRequestedTwice(_name) => None,
+			IoError(source) => Some(source),
 		}
 	}
 }
@@ -80,6 +98,28 @@ impl<EntityType: Entity> std::fmt::Display
 				f,
 				"Passed in iterators must all be the same length as the entities iterator"
 			),
+			GroupDoesNotExist(group) => write!(f, "Group does not exist: {}", group),
+			EntitiesNotInSameGroup(first, second) => write!(
+				f,
+				"Entities `{:?}` and `{:?}` are not in the same group",
+				first, second
+			),
+			StorageNotCoveredByTypes(group, tid) => write!(
+				f,
+				"Storage in group {} is not one of the requested types: {:?}",
+				group, tid
+			),
+			EntityLocationInvalid(entity, group, index) => write!(
+				f,
+				"Entity `{:?}` maps to the invalid location of index {} in group {}",
+				entity, index, group
+			),
+			ComponentTypeRequestedTwice(name) => write!(
+				f,
+				"Component type requested mutably twice at once: {}",
+				name
+			),
+			IoError(source) => write!(f, "IO error: {}", source),
 		}
 	}
 }
@@ -100,6 +140,14 @@ impl<EntityType: Entity> From<BorrowMutError>
 	}
 }
 
+impl<EntityType: Entity> From<io::Error>
+	for DenseEntityDynamicPagedMultiValueTableErrors<EntityType>
+{
+	fn from(source: io::Error) -> Self {
+		DenseEntityDynamicPagedMultiValueTableErrors::IoError(source)
+	}
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ComponentLocations {
 	group: usize,
@@ -111,52 +159,380 @@ impl ComponentLocations {
 		group: usize::MAX,
 		index: usize::MAX,
 	};
+
+	/// Returns false if either half is the `INVALID` sentinel, such a location must never be used
+	/// to index or offset.
+	pub fn is_valid(&self) -> bool {
+		self.group != usize::MAX && self.index != usize::MAX
+	}
 }
 
 pub trait DynDensePagedData {
 	fn get_type_id(&self) -> TypeId;
 	fn as_any(&self) -> &dyn std::any::Any;
 	fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
-	fn get_strong(&self) -> Rc<RefCell<dyn DynDensePagedData>>;
 	fn get_idx(&self) -> usize;
+	fn group_count(&self) -> usize;
+	fn group_len(&self, group: usize) -> usize;
+	fn get_any(&self, group: usize, index: usize) -> Option<&dyn Any>;
 	fn ensure_group_count(&mut self, group_count: usize);
+	/// Reserves room for at least `additional` more groups past the current group count.
+	fn reserve_groups(&mut self, additional: usize);
+	/// Reserves room for at least `additional` more values in `group`.
+	fn reserve_in_group(&mut self, group: usize, additional: usize);
 	fn swap_remove(&mut self, group: usize, index: usize);
+	/// Removes the value at `index` of `group` the way `delete_policy` says to.
+	fn remove(&mut self, group: usize, index: usize, delete_policy: DeletePolicy);
+	fn swap(&mut self, group: usize, a: usize, b: usize);
+	/// Reorders `group` so the value at each index `i` is the one that was at `order[i]`.
+	fn permute(&mut self, group: usize, order: &[usize]);
 	fn move_groups(&mut self, group: usize, index: usize, new_group: usize);
+	/// Same as `move_groups` for a batch of indexes, they must be in descending order so that each
+	/// swap remove leaves the remaining indexes pointing at the same values.
+	fn move_groups_many(&mut self, group: usize, indices: &[usize], new_group: usize);
+	/// Removes every value from every group, the group count stays the same.
+	fn clear(&mut self);
+	/// Returns the summed `(capacity, len)` of every group, zero sized values take no memory so
+	/// their capacity is their len.
+	fn capacity_len(&self) -> (usize, usize);
+	/// Returns how many bytes the values of every group have allocated, always 0 for zero sized
+	/// values such as marker components.
+	fn heap_bytes(&self) -> usize;
+	/// Shrinks every group to fit its values.
+	fn shrink_to_fit(&mut self);
 }
 
 trait DynDensePagedDataCastable: 'static {
 	fn get_strong_self(&self) -> Rc<RefCell<Self>>;
 }
 
+/// The groups of values of a single component type that a `DensePagedData` keeps its values in,
+/// the heap by default or a custom one registered with `with_component_storage`.
+pub trait DensePagedColumns<ValueType>: 'static {
+	fn group_count(&self) -> usize;
+	/// Adds empty groups until there are `group_count`, never removes any.
+	fn ensure_group_count(&mut self, group_count: usize);
+	fn reserve_groups(&mut self, additional: usize);
+	fn reserve_in_group(&mut self, group: usize, additional: usize);
+	fn group(&self, group: usize) -> &[ValueType];
+	fn group_mut(&mut self, group: usize) -> &mut [ValueType];
+	fn push(&mut self, group: usize, value: ValueType);
+	fn swap_remove(&mut self, group: usize, index: usize) -> ValueType;
+	fn remove(&mut self, group: usize, index: usize) -> ValueType;
+	fn truncate(&mut self, group: usize, len: usize);
+	/// Removes every value of `group`, returning them in order.
+	fn take_group(&mut self, group: usize) -> Vec<ValueType>;
+	/// Same as `DynDensePagedData::capacity_len`.
+	fn capacity_len(&self) -> (usize, usize);
+	/// Same as `DynDensePagedData::heap_bytes`.
+	fn heap_bytes(&self) -> usize;
+	fn shrink_to_fit(&mut self);
+}
+
+pub(crate) struct HeapPagedColumns<ValueType>(pub(crate) Vec<Vec<ValueType>>);
+
+impl<ValueType: 'static> DensePagedColumns<ValueType> for HeapPagedColumns<ValueType> {
+	fn group_count(&self) -> usize {
+		self.0.len()
+	}
+
+	fn ensure_group_count(&mut self, group_count: usize) {
+		if self.0.len() < group_count {
+			self.0.resize_with(group_count, || Vec::new());
+		}
+	}
+
+	fn reserve_groups(&mut self, additional: usize) {
+		self.0.reserve(additional);
+	}
+
+	fn reserve_in_group(&mut self, group: usize, additional: usize) {
+		self.0[group].reserve(additional);
+	}
+
+	fn group(&self, group: usize) -> &[ValueType] {
+		&self.0[group]
+	}
+
+	fn group_mut(&mut self, group: usize) -> &mut [ValueType] {
+		&mut self.0[group]
+	}
+
+	fn push(&mut self, group: usize, value: ValueType) {
+		self.0[group].push(value);
+	}
+
+	fn swap_remove(&mut self, group: usize, index: usize) -> ValueType {
+		self.0[group].swap_remove(index)
+	}
+
+	fn remove(&mut self, group: usize, index: usize) -> ValueType {
+		self.0[group].remove(index)
+	}
+
+	fn truncate(&mut self, group: usize, len: usize) {
+		self.0[group].truncate(len);
+	}
+
+	fn take_group(&mut self, group: usize) -> Vec<ValueType> {
+		std::mem::take(&mut self.0[group])
+	}
+
+	fn capacity_len(&self) -> (usize, usize) {
+		// A `Vec` of a zero sized type never allocates and reports a capacity of `usize::MAX`
+		let zero_sized = std::mem::size_of::<ValueType>() == 0;
+		self.0.iter().fold((0, 0), |(capacity, len), data| {
+			let data_capacity = if zero_sized {
+				data.len()
+			} else {
+				data.capacity()
+			};
+			(capacity + data_capacity, len + data.len())
+		})
+	}
+
+	fn heap_bytes(&self) -> usize {
+		let (capacity, _len) = self.capacity_len();
+		capacity * std::mem::size_of::<ValueType>()
+	}
+
+	fn shrink_to_fit(&mut self) {
+		for data in self.0.iter_mut() {
+			data.shrink_to_fit();
+		}
+	}
+}
+
+pub(crate) enum PagedColumns<ValueType: 'static> {
+	Heap(HeapPagedColumns<ValueType>),
+	Custom(Box<dyn DensePagedColumns<ValueType>>),
+}
+
+/// Runs `$body` with `$columns` bound to the columns of `$data`, so the heap columns are called
+/// directly instead of through a vtable.
+macro_rules! with_columns {
+	($data:expr, $columns:ident => $body:expr) => {
+		match $data {
+			PagedColumns::Heap($columns) => $body,
+			PagedColumns::Custom($columns) => $body,
+		}
+	};
+}
+
+impl<ValueType: 'static> DensePagedColumns<ValueType> for PagedColumns<ValueType> {
+	fn group_count(&self) -> usize {
+		with_columns!(self, columns => columns.group_count())
+	}
+
+	fn ensure_group_count(&mut self, group_count: usize) {
+		with_columns!(self, columns => columns.ensure_group_count(group_count))
+	}
+
+	fn reserve_groups(&mut self, additional: usize) {
+		with_columns!(self, columns => columns.reserve_groups(additional))
+	}
+
+	fn reserve_in_group(&mut self, group: usize, additional: usize) {
+		with_columns!(self, columns => columns.reserve_in_group(group, additional))
+	}
+
+	fn group(&self, group: usize) -> &[ValueType] {
+		with_columns!(self, columns => columns.group(group))
+	}
+
+	fn group_mut(&mut self, group: usize) -> &mut [ValueType] {
+		with_columns!(self, columns => columns.group_mut(group))
+	}
+
+	fn push(&mut self, group: usize, value: ValueType) {
+		with_columns!(self, columns => columns.push(group, value))
+	}
+
+	fn swap_remove(&mut self, group: usize, index: usize) -> ValueType {
+		with_columns!(self, columns => columns.swap_remove(group, index))
+	}
+
+	fn remove(&mut self, group: usize, index: usize) -> ValueType {
+		with_columns!(self, columns => columns.remove(group, index))
+	}
+
+	fn truncate(&mut self, group: usize, len: usize) {
+		with_columns!(self, columns => columns.truncate(group, len))
+	}
+
+	fn take_group(&mut self, group: usize) -> Vec<ValueType> {
+		with_columns!(self, columns => columns.take_group(group))
+	}
+
+	fn capacity_len(&self) -> (usize, usize) {
+		with_columns!(self, columns => columns.capacity_len())
+	}
+
+	fn heap_bytes(&self) -> usize {
+		with_columns!(self, columns => columns.heap_bytes())
+	}
+
+	fn shrink_to_fit(&mut self) {
+		with_columns!(self, columns => columns.shrink_to_fit())
+	}
+}
+
+/// A storage of a single value type that keeps its values in `DensePagedColumns`, it gets its
+/// `DynDensePagedData` from them whether it is shared through an `Rc<RefCell<_>>` like
+/// `DensePagedData` or an `Arc<RwLock<_>>` like `SyncDensePagedData`.
+pub(crate) trait ColumnStorage: 'static {
+	type Value: 'static;
+	type Columns: DensePagedColumns<Self::Value>;
+	fn idx(&self) -> usize;
+	fn columns(&self) -> &Self::Columns;
+	fn columns_mut(&mut self) -> &mut Self::Columns;
+}
+
 pub struct DensePagedData<ValueType: 'static> {
 	this: Weak<RefCell<Self>>,
 	idx: usize,
-	data: Vec<Vec<ValueType>>,
+	data: PagedColumns<ValueType>,
 }
 
 impl<ValueType: 'static> DensePagedData<ValueType> {
 	pub fn new(idx: usize) -> Rc<RefCell<Self>> {
+		Self::wrap(idx, PagedColumns::Heap(HeapPagedColumns(vec![])))
+	}
+
+	/// Same as `new` but keeps the values in `columns` instead of on the heap, `columns` must not
+	/// have any groups yet.
+	pub fn with_columns(
+		idx: usize,
+		columns: Box<dyn DensePagedColumns<ValueType>>,
+	) -> Rc<RefCell<Self>> {
+		assert_eq!(
+			columns.group_count(),
+			0,
+			"the columns of a new DensePagedData<{}> must not have any groups",
+			std::any::type_name::<ValueType>()
+		);
+		Self::wrap(idx, PagedColumns::Custom(columns))
+	}
+
+	fn wrap(idx: usize, data: PagedColumns<ValueType>) -> Rc<RefCell<Self>> {
 		let this = Rc::new(RefCell::new(DensePagedData {
 			this: Weak::new(),
 			idx,
-			data: vec![],
+			data,
 		}));
 		this.borrow_mut().this = Rc::downgrade(&this);
 		this
 	}
 
+	/// Returns the storage of `ValueType` in `storages`, creating it if needed, a placeholder
+	/// pre-registered with `with_component_types` is replaced at the same storage index.
+	///
+	/// In debug builds panics if the storage of its `TypeId` is anything else, rather than silently
+	/// sharing or replacing the storage of another type.
+	fn get_or_create(
+		storages: &mut IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
+	) -> Rc<RefCell<Self>> {
+		let tid = TypeId::of::<ValueType>();
+		if let Some((idx, _tid, storage)) = storages.get_full_mut(&tid) {
+			if let Some(existing) = storage.borrow().as_any().downcast_ref::<Self>() {
+				return existing.get_strong_self();
+			}
+			debug_assert!(
+				storage.borrow().as_any().is::<PlaceholderPagedData>(),
+				"storage index {} of the TypeId of {} does not hold a DensePagedData<{}>, two types collided on the same TypeId",
+				idx,
+				std::any::type_name::<ValueType>(),
+				std::any::type_name::<ValueType>()
+			);
+			let group_count = storage.borrow().group_count();
+			let replacement = Self::new(idx);
+			replacement.borrow_mut().ensure_group_count(group_count);
+			*storage = replacement.clone();
+			replacement
+		} else {
+			let storage = Self::new(storages.len());
+			storages.insert(tid, storage.clone());
+			storage
+		}
+	}
+
 	pub fn push(&mut self, group: usize, data: ValueType) {
-		self.data[group].push(data);
+		with_columns!(&mut self.data, columns => columns.push(group, data))
 	}
 
 	pub fn extend(&mut self, group: usize, data: impl IntoIterator<Item = ValueType>) {
-		self.data[group].extend(data);
+		let data = data.into_iter();
+		with_columns!(&mut self.data, columns => {
+			columns.reserve_in_group(group, data.size_hint().0);
+			for value in data {
+				columns.push(group, value);
+			}
+		})
+	}
+
+	/// Replaces the value at `index` of `group`, returning the old value.
+	pub fn replace(&mut self, group: usize, index: usize, data: ValueType) -> ValueType {
+		with_columns!(&mut self.data, columns => {
+			std::mem::replace(&mut columns.group_mut(group)[index], data)
+		})
+	}
+
+	/// Returns the values of `group`, or `None` if there is no such group.
+	pub fn group(&self, group: usize) -> Option<&[ValueType]> {
+		with_columns!(&self.data, columns => {
+			if group < columns.group_count() {
+				Some(columns.group(group))
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Same as `group` but mutable.
+	pub fn group_mut(&mut self, group: usize) -> Option<&mut [ValueType]> {
+		with_columns!(&mut self.data, columns => {
+			if group < columns.group_count() {
+				Some(columns.group_mut(group))
+			} else {
+				None
+			}
+		})
+	}
+
+	pub fn get(&self, group: usize, index: usize) -> Option<&ValueType> {
+		self.group(group)?.get(index)
+	}
+
+	pub fn get_mut(&mut self, group: usize, index: usize) -> Option<&mut ValueType> {
+		self.group_mut(group)?.get_mut(index)
+	}
+
+	/// Removes every value of `group`, returning them in order.
+	pub fn take_group(&mut self, group: usize) -> Vec<ValueType> {
+		with_columns!(&mut self.data, columns => columns.take_group(group))
+	}
+}
+
+impl<ValueType: 'static> ColumnStorage for DensePagedData<ValueType> {
+	type Value = ValueType;
+	type Columns = PagedColumns<ValueType>;
+
+	fn idx(&self) -> usize {
+		self.idx
+	}
+
+	fn columns(&self) -> &Self::Columns {
+		&self.data
+	}
+
+	fn columns_mut(&mut self) -> &mut Self::Columns {
+		&mut self.data
 	}
 }
 
-impl<ValueType: 'static> DynDensePagedData for DensePagedData<ValueType> {
+impl<Storage: ColumnStorage> DynDensePagedData for Storage {
 	fn get_type_id(&self) -> TypeId {
-		TypeId::of::<ValueType>()
+		TypeId::of::<Storage::Value>()
 	}
 
 	fn as_any(&self) -> &dyn Any {
@@ -167,41 +543,227 @@ impl<ValueType: 'static> DynDensePagedData for DensePagedData<ValueType> {
 		self
 	}
 
-	fn get_strong(&self) -> Rc<RefCell<dyn DynDensePagedData>> {
-		self.get_strong_self()
+	fn get_idx(&self) -> usize {
+		self.idx()
 	}
 
-	fn get_idx(&self) -> usize {
-		self.idx
+	fn group_count(&self) -> usize {
+		self.columns().group_count()
+	}
+
+	fn group_len(&self, group: usize) -> usize {
+		self.columns().group(group).len()
+	}
+
+	fn get_any(&self, group: usize, index: usize) -> Option<&dyn Any> {
+		let columns = self.columns();
+		if group < columns.group_count() {
+			columns
+				.group(group)
+				.get(index)
+				.map(|value| value as &dyn Any)
+		} else {
+			None
+		}
 	}
 
 	fn ensure_group_count(&mut self, group_count: usize) {
-		self.data.resize_with(group_count, || Vec::new());
+		self.columns_mut().ensure_group_count(group_count);
+	}
+
+	fn reserve_groups(&mut self, additional: usize) {
+		self.columns_mut().reserve_groups(additional);
+	}
+
+	fn reserve_in_group(&mut self, group: usize, additional: usize) {
+		self.columns_mut().reserve_in_group(group, additional);
 	}
 
 	fn swap_remove(&mut self, group: usize, index: usize) {
-		self.data[group].swap_remove(index);
+		self.columns_mut().swap_remove(group, index);
+	}
+
+	fn remove(&mut self, group: usize, index: usize, delete_policy: DeletePolicy) {
+		let columns = self.columns_mut();
+		match delete_policy {
+			DeletePolicy::SwapRemove => {
+				columns.swap_remove(group, index);
+			}
+			DeletePolicy::ShiftRemove => {
+				columns.remove(group, index);
+			}
+		}
+	}
+
+	fn swap(&mut self, group: usize, a: usize, b: usize) {
+		self.columns_mut().group_mut(group).swap(a, b);
+	}
+
+	fn permute(&mut self, group: usize, order: &[usize]) {
+		let columns = self.columns_mut();
+		let mut old: Vec<Option<Storage::Value>> =
+			columns.take_group(group).into_iter().map(Some).collect();
+		columns.reserve_in_group(group, order.len());
+		for &index in order {
+			columns.push(
+				group,
+				old[index].take().expect("order must be a permutation"),
+			);
+		}
 	}
 
 	fn move_groups(&mut self, group: usize, index: usize, new_group: usize) {
-		let value = self.data[group].swap_remove(index);
-		self.data[new_group].push(value);
+		let columns = self.columns_mut();
+		let value = columns.swap_remove(group, index);
+		columns.push(new_group, value);
+	}
+
+	fn move_groups_many(&mut self, group: usize, indices: &[usize], new_group: usize) {
+		debug_assert!(indices.windows(2).all(|w| w[0] > w[1]));
+		let columns = self.columns_mut();
+		for &index in indices {
+			let value = columns.swap_remove(group, index);
+			columns.push(new_group, value);
+		}
 	}
-}
 
+	fn clear(&mut self) {
+		let columns = self.columns_mut();
+		for group in 0..columns.group_count() {
+			columns.truncate(group, 0);
+		}
+	}
+
+	fn capacity_len(&self) -> (usize, usize) {
+		self.columns().capacity_len()
+	}
+
+	fn heap_bytes(&self) -> usize {
+		self.columns().heap_bytes()
+	}
+
+	fn shrink_to_fit(&mut self) {
+		self.columns_mut().shrink_to_fit();
+	}
+}
 impl<ValueType: 'static> DynDensePagedDataCastable for DensePagedData<ValueType> {
 	fn get_strong_self(&self) -> Rc<RefCell<Self>> {
 		self.this.upgrade().unwrap() // It's obviously valid since it's obviously self
 	}
 }
 
+/// Holds the storage index of a component type pre-registered with `with_component_types` until
+/// its first typed use replaces it with its `DensePagedData`.
+///
+/// No group holds a type before that first use so this never holds any values.
+struct PlaceholderPagedData {
+	idx: usize,
+	tid: TypeId,
+	group_count: usize,
+}
+
+impl PlaceholderPagedData {
+	fn new(idx: usize, tid: TypeId) -> Rc<RefCell<Self>> {
+		Rc::new(RefCell::new(PlaceholderPagedData {
+			idx,
+			tid,
+			group_count: 0,
+		}))
+	}
+}
+
+impl DynDensePagedData for PlaceholderPagedData {
+	fn get_type_id(&self) -> TypeId {
+		self.tid
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
+
+	fn get_idx(&self) -> usize {
+		self.idx
+	}
+
+	fn group_count(&self) -> usize {
+		self.group_count
+	}
+
+	fn group_len(&self, _group: usize) -> usize {
+		0
+	}
+
+	fn get_any(&self, _group: usize, _index: usize) -> Option<&dyn Any> {
+		None
+	}
+
+	fn ensure_group_count(&mut self, group_count: usize) {
+		self.group_count = self.group_count.max(group_count);
+	}
+
+	fn reserve_groups(&mut self, _additional: usize) {}
+
+	fn reserve_in_group(&mut self, _group: usize, _additional: usize) {}
+
+	fn swap_remove(&mut self, _group: usize, _index: usize) {
+		unreachable!("placeholder storages are in no group");
+	}
+
+	fn remove(&mut self, _group: usize, _index: usize, _delete_policy: DeletePolicy) {
+		unreachable!("placeholder storages are in no group");
+	}
+
+	fn swap(&mut self, _group: usize, _a: usize, _b: usize) {
+		unreachable!("placeholder storages are in no group");
+	}
+
+	fn permute(&mut self, _group: usize, _order: &[usize]) {
+		unreachable!("placeholder storages are in no group");
+	}
+
+	fn move_groups(&mut self, _group: usize, _index: usize, _new_group: usize) {
+		unreachable!("placeholder storages are in no group");
+	}
+
+	fn move_groups_many(&mut self, _group: usize, _indices: &[usize], _new_group: usize) {
+		unreachable!("placeholder storages are in no group");
+	}
+
+	fn clear(&mut self) {}
+
+	fn capacity_len(&self) -> (usize, usize) {
+		(0, 0)
+	}
+
+	fn heap_bytes(&self) -> usize {
+		0
+	}
+
+	fn shrink_to_fit(&mut self) {}
+}
+
 trait DynGroup {
 	fn as_any(&self) -> &dyn std::any::Any;
 	fn get_idx(&self) -> usize;
 }
 
+/// The untyped side of a `GroupInsert`, for locking several of them at once through
+/// `DenseEntityDynamicPagedMultiValueTable::lock_inserters`.
+pub trait DynGroupInsert {
+	/// The archetype group this inserts into.
+	fn group(&self) -> usize;
+	/// The storages this inserts values into.
+	fn storage_idxs(&self) -> &[usize];
+}
+
 pub struct GroupQuery<EntityType: Entity, VTs: ValueTypes> {
 	group: usize,
+	/// When set the query only sees this one archetype group, see `group_query_by_index`.
+	only_group: Option<usize>,
 	storage: VTs::Storage,
 	_phantom: PhantomData<EntityType>,
 }
@@ -210,6 +772,7 @@ impl<EntityType: Entity, VTs: ValueTypes> Clone for GroupQuery<EntityType, VTs>
 	fn clone(&self) -> Self {
 		GroupQuery {
 			group: self.group,
+			only_group: self.only_group,
 			storage: self.storage.clone(),
 			_phantom: PhantomData,
 		}
@@ -242,6 +805,7 @@ impl<EntityType: Entity, VTs: ValueTypes> GroupQuery<EntityType, VTs> {
 		if let Ok(storage_locked) = VTs::try_storage_locked(&self.storage) {
 			Some(GroupQueryLock {
 				//group: self.group,
+				only_group: self.only_group,
 				storage_locked,
 				table,
 				_phantom: PhantomData,
@@ -286,38 +850,189 @@ impl<EntityType: Entity, VTs: InsertValueTypes> GroupInsert<EntityType, VTs> {
 
 pub struct GroupQueryLock<'a, 's, EntityType: Entity, VTs: ValueTypes> {
 	//group: usize,
+	only_group: Option<usize>,
 	storage_locked: VTs::StorageLocked, // When GAT's exist then pass `'a` into StorageLocked
 	table: &'s DenseEntityDynamicPagedMultiValueTable<EntityType>,
 	_phantom: PhantomData<&'a EntityType>,
 }
 
-pub struct GroupInsertLock<'a, 's, EntityType: Entity, VTs: InsertValueTypes> {
-	group: usize,
-	storage_locked: VTs::StorageLocked, // When GAT's exist then pass `'a` into StorageLocked
-	table: &'s mut DenseEntityDynamicPagedMultiValueTable<EntityType>,
-	_phantom: PhantomData<&'a ()>,
+pub struct GroupQueryEntityIter<'a, EntityType: Entity, VTs: GetValueTypes<'a>> {
+	entities: &'a Vec<Vec<EntityType>>,
+	storages: VTs::StoragesLockedRef,
+	groups: std::vec::IntoIter<usize>,
+	group: Option<usize>,
+	index: usize,
+	/// Only the entities with a raw index in this range are yielded.
+	index_range: Range<usize>,
 }
 
-impl<'a, 's, EntityType: Entity, VTs: ValueTypes> GroupQueryLock<'a, 's, EntityType, VTs> {
-	pub fn get_all(&'a mut self, entity: ValidEntity<EntityType>) -> Option<VTs::GetRef>
-	where
-		VTs: GetValueTypes<'a>,
-	{
-		if let Ok(location) =
-			DenseEntityDynamicPagedMultiValueTable::<EntityType>::get_valid_location(
-				&self.table.reverse,
-				&self.table.entities,
-				entity.raw(),
-			) {
-			let mut cast_storages = VTs::cast_locked_storages::<VTs>(&mut self.storage_locked);
-			VTs::get::<EntityType>(
-				// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
-				// This 'should' be safeish as it's just casting lifetimes to a more constrained lifetime
-				unsafe { &mut *(&mut cast_storages as *mut VTs::StoragesLockedRef) },
-				location.group,
-				location.index,
-			)
-		} else {
+impl<'a, EntityType: Entity, VTs: GetValueTypes<'a>> Iterator
+	for GroupQueryEntityIter<'a, EntityType, VTs>
+{
+	type Item = (EntityType, VTs::GetRef);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let group = match self.group {
+				Some(group) => group,
+				None => {
+					let group = self.groups.next()?;
+					self.group = Some(group);
+					self.index = 0;
+					group
+				}
+			};
+			let index = self.index;
+			if let Some(&entity) = self.entities[group].get(index) {
+				self.index += 1;
+				if !self.index_range.contains(&entity.idx()) {
+					continue;
+				}
+				let values = VTs::get::<EntityType>(
+					// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+					// This 'should' be safeish as each index is only handed out once
+					unsafe { &mut *(&mut self.storages as *mut VTs::StoragesLockedRef) },
+					group,
+					index,
+				)
+				.expect("storage is in an invalid state with its entities");
+				return Some((entity, values));
+			}
+			self.group = None;
+		}
+	}
+}
+
+pub struct GroupQueryGroupIter<'a, EntityType: Entity, VTs: GetValueTypes<'a>> {
+	table: &'a DenseEntityDynamicPagedMultiValueTable<EntityType>,
+	storages: VTs::StoragesLockedRef,
+	groups: std::vec::IntoIter<usize>,
+}
+
+impl<'a, EntityType: Entity, VTs: GetValueTypes<'a>> Iterator
+	for GroupQueryGroupIter<'a, EntityType, VTs>
+{
+	type Item = (usize, &'a [TypeId], VTs::StorageSlices);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let group = self.groups.next()?;
+		let (group_key, _group_value) = self.table.group_inserts.get_index(group)?;
+		let slices = VTs::get_slices(
+			// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+			// This 'should' be safeish as each group is only handed out once
+			unsafe { &mut *(&mut self.storages as *mut VTs::StoragesLockedRef) },
+			group,
+		)
+		.expect("storage is in an invalid state with its groups");
+		Some((group, &group_key.include, slices))
+	}
+}
+
+pub struct GroupQueryChunkIter<'a, EntityType: Entity, VTs: GetValueTypes<'a>> {
+	groups: GroupQueryGroupIter<'a, EntityType, VTs>,
+	chunk: usize,
+	/// The rest of the current group and its length.
+	current: Option<(VTs::StorageSlices, usize)>,
+}
+
+impl<'a, EntityType: Entity, VTs: GetValueTypes<'a>> Iterator
+	for GroupQueryChunkIter<'a, EntityType, VTs>
+{
+	type Item = VTs::StorageSlices;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some((slices, len)) = self.current.take() {
+				if len > self.chunk {
+					let (chunk, rest) = VTs::split_slices_at(slices, self.chunk);
+					self.current = Some((rest, len - self.chunk));
+					return Some(chunk);
+				} else if len > 0 {
+					return Some(slices);
+				}
+			}
+			let (group, _include, slices) = self.groups.next()?;
+			self.current = Some((slices, self.groups.table.entities[group].len()));
+		}
+	}
+}
+
+pub struct GroupQueryJoinIter<'a, EntityType: Entity, A: GetValueTypes<'a>, B: GetValueTypes<'a>> {
+	iter: GroupQueryEntityIter<'a, EntityType, A>,
+	table: &'a DenseEntityDynamicPagedMultiValueTable<EntityType>,
+	only_group: Option<usize>,
+	storages: B::StoragesLockedRef,
+}
+
+impl<'a, EntityType: Entity, A: GetValueTypes<'a>, B: GetValueTypes<'a>> Iterator
+	for GroupQueryJoinIter<'a, EntityType, A, B>
+{
+	type Item = (A::GetRef, Option<B::GetRef>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (entity, values) = self.iter.next()?;
+		let joined = if let Ok(location) =
+			DenseEntityDynamicPagedMultiValueTable::<EntityType>::get_valid_location(
+				&self.table.reverse,
+				&self.table.entities,
+				entity,
+			) {
+			if self
+				.only_group
+				.map_or(false, |only_group| only_group != location.group)
+			{
+				return Some((values, None));
+			}
+			B::get::<EntityType>(
+				// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+				// This 'should' be safeish as each entity is only handed out once
+				unsafe { &mut *(&mut self.storages as *mut B::StoragesLockedRef) },
+				location.group,
+				location.index,
+			)
+		} else {
+			None
+		};
+		Some((values, joined))
+	}
+}
+
+pub struct GroupInsertLock<'a, 's, EntityType: Entity, VTs: InsertValueTypes> {
+	group: usize,
+	storage_locked: VTs::StorageLocked, // When GAT's exist then pass `'a` into StorageLocked
+	table: &'s mut DenseEntityDynamicPagedMultiValueTable<EntityType>,
+	_phantom: PhantomData<&'a ()>,
+}
+
+impl<'a, 's, EntityType: Entity, VTs: ValueTypes> GroupQueryLock<'a, 's, EntityType, VTs> {
+	fn includes_group(&self, group: usize) -> bool {
+		self.only_group
+			.map_or(true, |only_group| only_group == group)
+	}
+
+	pub fn get_all(&'a mut self, entity: ValidEntity<EntityType>) -> Option<VTs::GetRef>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		let entity = entity.raw_in(self.table.world_id).ok()?;
+		if let Ok(location) =
+			DenseEntityDynamicPagedMultiValueTable::<EntityType>::get_valid_location(
+				&self.table.reverse,
+				&self.table.entities,
+				entity,
+			) {
+			if !self.includes_group(location.group) {
+				return None;
+			}
+			let mut cast_storages = VTs::cast_locked_storages::<VTs>(&mut self.storage_locked);
+			VTs::get::<EntityType>(
+				// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+				// This 'should' be safeish as it's just casting lifetimes to a more constrained lifetime
+				unsafe { &mut *(&mut cast_storages as *mut VTs::StoragesLockedRef) },
+				location.group,
+				location.index,
+			)
+		} else {
 			None
 		}
 	}
@@ -326,12 +1041,16 @@ impl<'a, 's, EntityType: Entity, VTs: ValueTypes> GroupQueryLock<'a, 's, EntityT
 		&'a mut self,
 		entity: ValidEntity<EntityType>,
 	) -> Option<GTs::GetRef> {
+		let entity = entity.raw_in(self.table.world_id).ok()?;
 		if let Ok(location) =
 			DenseEntityDynamicPagedMultiValueTable::<EntityType>::get_valid_location(
 				&self.table.reverse,
 				&self.table.entities,
-				entity.raw(),
+				entity,
 			) {
+			if !self.includes_group(location.group) {
+				return None;
+			}
 			let mut cast_storages = GTs::cast_locked_storages::<VTs>(&mut self.storage_locked);
 			GTs::get::<EntityType>(
 				// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
@@ -344,6 +1063,245 @@ impl<'a, 's, EntityType: Entity, VTs: ValueTypes> GroupQueryLock<'a, 's, EntityT
 			None
 		}
 	}
+
+	/// Returns the contiguous values of component `T` in `group`, in the same order as that group's
+	/// entities, for handing whole columns to SIMD or other slice based routines.
+	///
+	/// Returns `None` if `T` is not one of the types of this query or the query does not see
+	/// `group`.
+	pub fn column<T: 'static>(&self, group: usize) -> Option<&[T]> {
+		if !self.includes_group(group) {
+			return None;
+		}
+		VTs::get_column::<T>(&self.storage_locked, group)
+	}
+
+	/// Same as `column` but mutable, `T` must be accessed as `&mut T` by this query.
+	pub fn column_mut<T: 'static>(&mut self, group: usize) -> Option<&mut [T]> {
+		if !self.includes_group(group) {
+			return None;
+		}
+		VTs::get_column_mut::<T>(&mut self.storage_locked, group)
+	}
+
+	/// Gathers a `&mut T` for each of `entities`, in the same order, for batch access to a single
+	/// component type, `T` must be accessed as `&mut T` by this query.
+	///
+	/// Returns `None` if any of `entities` does not have `T` or is not seen by this query, or if the
+	/// same entity is passed more than once as that would alias the mutable references.
+	pub fn get_homogeneous<T: 'static>(
+		&mut self,
+		entities: &[ValidEntity<EntityType>],
+	) -> Option<SmallVec<[&mut T; 16]>> {
+		let mut locations: SmallVec<[(usize, usize); 16]> = SmallVec::with_capacity(entities.len());
+		for entity in entities {
+			let location =
+				DenseEntityDynamicPagedMultiValueTable::<EntityType>::get_valid_location(
+					&self.table.reverse,
+					&self.table.entities,
+					entity.raw_in(self.table.world_id).ok()?,
+				)
+				.ok()?;
+			if !self.includes_group(location.group) {
+				return None;
+			}
+			locations.push((location.group, location.index));
+		}
+		let mut sorted = locations.clone();
+		sorted.sort_unstable();
+		if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+			return None;
+		}
+		// The base pointer of each group's column is taken once, so no reborrow of a column can
+		// invalidate the references already handed out into it
+		let mut columns: SmallVec<[(usize, *mut T, usize); 4]> = SmallVec::new();
+		for &(group, _index) in sorted.iter() {
+			if columns.last().map_or(true, |&(last, _, _)| last != group) {
+				let column = VTs::get_column_mut::<T>(&mut self.storage_locked, group)?;
+				columns.push((group, column.as_mut_ptr(), column.len()));
+			}
+		}
+		let mut values = SmallVec::with_capacity(locations.len());
+		for (group, index) in locations {
+			let column = columns
+				.binary_search_by_key(&group, |&(group, _, _)| group)
+				.ok()?;
+			let (_group, ptr, len) = columns[column];
+			if index >= len {
+				return None;
+			}
+			// This 'should' be safeish as every location was checked to be distinct above
+			values.push(unsafe { &mut *ptr.add(index) });
+		}
+		Some(values)
+	}
+
+	/// Iterates every entity that has all the components of `VTs` in ascending order of `key`.
+	///
+	/// This gathers every matching entity and its values into a `Vec` and sorts it, so it costs
+	/// O(n log n) and allocates up front, the returned iterator is a snapshot of the order at the time
+	/// of the call.
+	pub fn iter_sorted_by<K: Ord>(
+		&'a mut self,
+		key: impl Fn(&VTs::GetRef) -> K,
+	) -> std::vec::IntoIter<(ValidEntity<'a, EntityType>, VTs::GetRef)>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		let table = self.table;
+		let mut cast_storages = VTs::cast_locked_storages::<VTs>(&mut self.storage_locked);
+		let mut sorted = Vec::new();
+		for (group, (group_key, _group_value)) in table.group_inserts.iter().enumerate() {
+			if !self.includes_group(group)
+				|| !include.iter().all(|tid| group_key.include.contains(tid))
+			{
+				continue;
+			}
+			for (index, &entity) in table.entities[group].iter().enumerate() {
+				let values = VTs::get::<EntityType>(
+					// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+					// This 'should' be safeish as it's just casting lifetimes to a more constrained lifetime
+					unsafe { &mut *(&mut cast_storages as *mut VTs::StoragesLockedRef) },
+					group,
+					index,
+				)
+				.expect("storage is in an invalid state with its entities");
+				sorted.push((ValidEntity::new_unchecked(entity, table.world_id), values));
+			}
+		}
+		sorted.sort_by(|(_, a), (_, b)| key(a).cmp(&key(b)));
+		sorted.into_iter()
+	}
+
+	/// The indexes of every group this query sees that has all the components of `VTs`.
+	fn matching_groups(&self) -> std::vec::IntoIter<usize> {
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		self.table
+			.group_inserts
+			.keys()
+			.enumerate()
+			.filter(|(group, group_key)| {
+				self.includes_group(*group)
+					&& include.iter().all(|tid| group_key.include.contains(tid))
+			})
+			.map(|(group, _group_key)| group)
+			.collect::<Vec<_>>()
+			.into_iter()
+	}
+
+	/// Returns the slices of every `VTs` component of `group` at once as a struct of arrays, for
+	/// processing a single archetype column by column.
+	///
+	/// The slices are aligned, every one of them is exactly as long as the entity count of `group`
+	/// and index `i` of each holds a component of the same entity, the `i`th of `group`.
+	///
+	/// Returns `None` if the query does not see `group` or it lacks any of the components of `VTs`.
+	pub fn group_soa(&'a mut self, group: usize) -> Option<VTs::StorageSlices>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		let (group_key, _group_value) = self.table.group_inserts.get_index(group)?;
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		if !self.includes_group(group) || !include.iter().all(|tid| group_key.include.contains(tid))
+		{
+			return None;
+		}
+		let mut cast_storages = VTs::cast_locked_storages::<VTs>(&mut self.storage_locked);
+		VTs::get_slices(
+			// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+			// This 'should' be safeish as it's just casting lifetimes to a more constrained lifetime
+			unsafe { &mut *(&mut cast_storages as *mut VTs::StoragesLockedRef) },
+			group,
+		)
+	}
+
+	/// Iterates every entity that has all the components of `VTs` along with its values, walking
+	/// each matching group's entities alongside its component storages.
+	pub fn iter_with_entities(&'a mut self) -> GroupQueryEntityIter<'a, EntityType, VTs>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		let table: &'a DenseEntityDynamicPagedMultiValueTable<EntityType> = self.table;
+		let groups = self.matching_groups();
+		GroupQueryEntityIter {
+			entities: &table.entities,
+			storages: VTs::cast_locked_storages::<VTs>(&mut self.storage_locked),
+			groups,
+			group: None,
+			index: 0,
+			index_range: 0..usize::MAX,
+		}
+	}
+
+	/// Same as `iter_with_entities` but only yields the entities whose raw index is in `range`, so
+	/// work can be sharded by entity id.
+	///
+	/// Every entity of every matching group is still checked against `range` so this costs
+	/// O(entities in matching groups) no matter how small `range` is.
+	pub fn query_entity_range(
+		&'a mut self,
+		range: Range<usize>,
+	) -> GroupQueryEntityIter<'a, EntityType, VTs>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		let mut iter = self.iter_with_entities();
+		iter.index_range = range;
+		iter
+	}
+
+	/// Iterates the slices of every `VTs` component of every group this query sees in chunks of
+	/// `chunk` values, for SIMD processing in fixed lanes without caring where groups end.
+	///
+	/// A chunk never spans two groups so the last chunk of each group may be shorter, empty groups
+	/// yield nothing.  Panics if `chunk` is 0.
+	pub fn iter_chunks(&'a mut self, chunk: usize) -> GroupQueryChunkIter<'a, EntityType, VTs>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		assert!(chunk > 0, "chunk size must be greater than 0");
+		GroupQueryChunkIter {
+			groups: self.iter_groups(),
+			chunk,
+			current: None,
+		}
+	}
+
+	/// Iterates every group that has all the components of `VTs` as its group index, the full set of
+	/// component types of that archetype, and the slices of the `VTs` components in that group, so
+	/// systems can branch on what else an archetype contains.
+	pub fn iter_groups(&'a mut self) -> GroupQueryGroupIter<'a, EntityType, VTs>
+	where
+		VTs: GetValueTypes<'a>,
+	{
+		let table: &'a DenseEntityDynamicPagedMultiValueTable<EntityType> = self.table;
+		let groups = self.matching_groups();
+		GroupQueryGroupIter {
+			table,
+			storages: VTs::cast_locked_storages::<VTs>(&mut self.storage_locked),
+			groups,
+		}
+	}
+}
+
+/// Iterates every entity matched by `a` along with the values of that same entity in `b`, which is
+/// usually a query on another table sharing the same `EntityType`, or `None` if `b` does not match
+/// it.
+pub fn join<'a, 's, 't, EntityType: Entity, A: GetValueTypes<'a>, B: GetValueTypes<'a>>(
+	a: &'a mut GroupQueryLock<'a, 's, EntityType, A>,
+	b: &'a mut GroupQueryLock<'a, 't, EntityType, B>,
+) -> GroupQueryJoinIter<'a, EntityType, A, B> {
+	let table: &'a DenseEntityDynamicPagedMultiValueTable<EntityType> = b.table;
+	GroupQueryJoinIter {
+		iter: a.iter_with_entities(),
+		table,
+		only_group: b.only_group,
+		storages: B::cast_locked_storages::<B>(&mut b.storage_locked),
+	}
 }
 
 impl<'g, 's, EntityType: Entity, VTs: InsertValueTypes> GroupInsertLock<'g, 's, EntityType, VTs> {
@@ -352,26 +1310,37 @@ impl<'g, 's, EntityType: Entity, VTs: InsertValueTypes> GroupInsertLock<'g, 's,
 		entity: ValidEntity<EntityType>,
 		data: VTs::MoveData,
 	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let entity = entity.raw_in(self.table.world_id)?;
 		let location =
 			DenseEntityDynamicPagedMultiValueTable::<EntityType>::insert_valid_location_mut(
 				&mut self.table.reverse,
 				&mut self.table.entities,
-				entity.raw(),
+				entity,
 				self.group,
 			)?;
 		VTs::push(&mut self.storage_locked, location.group, data);
+		if let Some(masks) = &mut self.table.component_masks {
+			let (group_key, _group_value) = self.table.group_inserts.get_index(self.group).unwrap();
+			masks.set(entity.idx(), &group_key.include_storage_idxs);
+		}
 		Ok(())
 	}
 
+	/// Inserts every entity of `entity_slice` with the values of `data` at the same index, none of
+	/// the entities may already be in the table.
+	///
+	/// Errors with `IteratorsNotAllSameLength` without inserting anything if any column of `data`
+	/// is not the same length as `entity_slice`.
 	pub fn extend_slices(
 		&mut self,
 		entity_slice: &[ValidEntity<EntityType>],
 		data: VTs::MoveDataVec,
 	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
 		if !VTs::ensure_vec_length(&data, entity_slice.len()) {
-			panic!(
-				"All vecs passed to DenseEntityDynamicPagedMultiValueTable must be the same length"
-			);
+			return Err(DenseEntityDynamicPagedMultiValueTableErrors::IteratorsNotAllSameLength);
+		}
+		for entity in entity_slice {
+			entity.raw_in(self.table.world_id)?;
 		}
 		VTs::extend(&mut self.storage_locked, self.group, data);
 		for entity in entity_slice {
@@ -382,6 +1351,12 @@ impl<'g, 's, EntityType: Entity, VTs: InsertValueTypes> GroupInsertLock<'g, 's,
 					self.group,
 				).expect("Entity Already exists, when extending a DenseEntityDynamicPagedMultiValueTable then all entities must be new to it, else use `transform`");
 		}
+		if let Some(masks) = &mut self.table.component_masks {
+			let (group_key, _group_value) = self.table.group_inserts.get_index(self.group).unwrap();
+			for entity in entity_slice {
+				masks.set(entity.raw().idx(), &group_key.include_storage_idxs);
+			}
+		}
 
 		Ok(())
 	}
@@ -407,19 +1382,36 @@ impl<EntityType: Entity, VTs: InsertValueTypes> DynGroup for GroupInsert<EntityT
 	}
 }
 
+impl<EntityType: Entity, VTs: InsertValueTypes> DynGroupInsert for GroupInsert<EntityType, VTs> {
+	fn group(&self) -> usize {
+		self.group
+	}
+
+	fn storage_idxs(&self) -> &[usize] {
+		&self.storage_idxs
+	}
+}
+
 #[derive(PartialEq, Eq, Hash)]
 struct QueryTypedPagedKey<'a> {
 	include: &'a [TypeId],
 	//exclude: &'a [TypeId],
 }
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq)]
 struct QueryTypedPagedKeyBoxed {
 	include: Box<[TypeId]>,
 	//exclude: Box<[TypeId]>,
 	include_storage_idxs: Box<[usize]>,
 }
 
+// Has to hash the same as `QueryTypedPagedKey` so it can look up the boxed keys by `include` alone
+impl Hash for QueryTypedPagedKeyBoxed {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.include.hash(state);
+	}
+}
+
 impl<'a> QueryTypedPagedKey<'a> {
 	fn to_box(
 		self,
@@ -454,6 +1446,25 @@ impl<'a> QueryTypedPagedKey<'a> {
 	}
 }
 
+impl QueryTypedPagedKeyBoxed {
+	/// Returns the type and storage index of each value of this group that moves along with an
+	/// entity when `Remove` is taken away and `Add` is added.
+	fn moving_storages<Remove: RemoveTypes, Add: InsertValueTypes>(
+		&self,
+	) -> ArrayVec<[(TypeId, usize); 32]> {
+		let mut moving = ArrayVec::<[(TypeId, usize); 32]>::new();
+		moving.extend(
+			self.include
+				.iter()
+				.copied()
+				.zip(self.include_storage_idxs.iter().copied()),
+		);
+		Remove::swap_remove_type_ids(&mut moving);
+		Add::swap_remove_type_ids(&mut moving);
+		moving
+	}
+}
+
 // impl<'a> Hash for QueryTypedPagedKey<'a> {
 // 	fn hash<H: Hasher>(&self, state: &mut H) {
 // 		self.include.hash(state);
@@ -474,17 +1485,26 @@ impl<'a> indexmap::Equivalent<QueryTypedPagedKeyBoxed> for QueryTypedPagedKey<'a
 	}
 }
 
+type ArchetypeCreatedCallback = Box<dyn FnMut(usize, &[TypeId])>;
+
 pub struct DenseEntityDynamicPagedMultiValueTable<EntityType: Entity> {
 	this: Weak<RefCell<Self>>,
 	database_id: DatabaseId,
 	table_name: SmolStr,
 	table_id: TableId,
-	//entity_table: EntityTable<EntityType>,
+	entity_table: Weak<RefCell<EntityTable<EntityType>>>,
+	/// The `world_id` of `entity_table`, for the `ValidEntity`s this table hands out itself.
+	world_id: u32,
 	reverse: SecondaryEntityIndex<EntityType, ComponentLocations>,
 	entities: Vec<Vec<EntityType>>,
 	storages: IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
 	group_inserts: IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
-	group_queries: IndexMap<TypeId, Box<dyn DynGroup>, UniqueHasherBuilder>,
+	/// Keyed by the sorted type ids of the query so every order of the same types shares a group.
+	group_queries: IndexMap<Box<[TypeId]>, Box<dyn DynGroup>>,
+	page_config: PageConfig,
+	delete_policy: DeletePolicy,
+	component_masks: Option<ComponentMasks>,
+	on_archetype_created: Vec<ArchetypeCreatedCallback>,
 }
 
 impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
@@ -495,6 +1515,7 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 		group: usize,
 	) -> Result<&'a mut ComponentLocations, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>>
 	{
+		debug_assert_ne!(group, usize::MAX, "cannot insert into the INVALID group");
 		let location = reverse.insert_mut(entity)?;
 		location.group = group;
 		// This should already be in sync so no resizing ever needed
@@ -513,14 +1534,7 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 		entity: EntityType,
 	) -> Result<&'a ComponentLocations, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
 		let location = reverse.get(entity)?;
-		if entities[location.group][location.index] != entity {
-			return Err(
-				DenseEntityDynamicPagedMultiValueTableErrors::EntityGenerationMismatch(
-					entity,
-					entities[location.group][location.index],
-				),
-			);
-		}
+		Self::check_location(entities, entity, location)?;
 		Ok(location)
 	}
 
@@ -531,50 +1545,280 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 	) -> Result<&'a mut ComponentLocations, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>>
 	{
 		let location = reverse.get_mut(entity)?;
-		if entities[location.group][location.index] != entity {
-			return Err(
+		Self::check_location(entities, entity, location)?;
+		Ok(location)
+	}
+
+	/// Checks that `location` is in bounds and holds `entity`, so a sentinel or stale location is
+	/// reported instead of indexing out of bounds.
+	fn check_location(
+		entities: &[Vec<EntityType>],
+		entity: EntityType,
+		location: &ComponentLocations,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let existing = if location.is_valid() {
+			entities
+				.get(location.group)
+				.and_then(|group| group.get(location.index))
+		} else {
+			None
+		};
+		match existing {
+			Some(&existing) if existing == entity => Ok(()),
+			Some(&existing) => Err(
 				DenseEntityDynamicPagedMultiValueTableErrors::EntityGenerationMismatch(
+					entity, existing,
+				),
+			),
+			None => Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::EntityLocationInvalid(
 					entity,
-					entities[location.group][location.index],
+					location.group,
+					location.index,
 				),
-			);
+			),
 		}
-		Ok(location)
 	}
 
 	fn remove_valid_location(
 		reverse: &mut SecondaryEntityIndex<EntityType, ComponentLocations>,
 		entities: &mut Vec<Vec<EntityType>>,
 		entity: EntityType,
+		delete_policy: DeletePolicy,
 	) -> Result<ComponentLocations, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
 		let location = reverse.get_mut(entity)?;
+		Self::check_location(entities, entity, location)?;
 		let entities_group = &mut entities[location.group];
-		if entities_group[location.index] != entity {
-			return Err(
-				DenseEntityDynamicPagedMultiValueTableErrors::EntityGenerationMismatch(
-					entity,
-					entities[location.group][location.index],
-				),
-			);
-		}
 		let loc = *location;
+		debug_assert!(loc.is_valid());
 		*location = ComponentLocations::INVALID;
-		entities_group.swap_remove(loc.index);
-		if entities_group.len() > loc.index {
-			let replacement_entity = entities_group[loc.index];
-			reverse
-				.get_mut(replacement_entity)
-				.expect("SecondaryIndex is in invalid state")
-				.index = loc.index;
+		match delete_policy {
+			DeletePolicy::SwapRemove => {
+				entities_group.swap_remove(loc.index);
+				if entities_group.len() > loc.index {
+					let replacement_entity = entities_group[loc.index];
+					reverse
+						.get_mut(replacement_entity)
+						.expect("SecondaryIndex is in invalid state")
+						.index = loc.index;
+				}
+			}
+			DeletePolicy::ShiftRemove => {
+				entities_group.remove(loc.index);
+				for (index, &shifted_entity) in entities_group.iter().enumerate().skip(loc.index) {
+					reverse
+						.get_mut(shifted_entity)
+						.expect("SecondaryIndex is in invalid state")
+						.index = index;
+				}
+			}
 		}
 		Ok(loc)
 	}
 
+	/// Reserves room for at least `additional` more groups in every storage at once, so creating
+	/// many archetypes in a row does not regrow each storage's group list one group at a time.
+	pub fn reserve_groups(&mut self, additional: usize) {
+		self.group_inserts.reserve(additional);
+		self.entities.reserve(additional);
+		let capacity = self.entities.capacity();
+		for storage in self.storages.values() {
+			let mut storage = storage.borrow_mut();
+			let count = storage.group_count();
+			storage.reserve_groups(capacity - count);
+		}
+	}
+
+	/// Returns the `TypeId` of every component type that has a storage in this table, in the order
+	/// the storages were created.
+	pub fn registered_component_types(&self) -> Vec<TypeId> {
+		self.storages.keys().copied().collect()
+	}
+
+	/// Returns how many values of the component type `tid` are stored across all groups, or `None`
+	/// if that type has no storage in this table.
+	///
+	/// Panics if that storage is already mutably locked.
+	pub fn storage_len(&self, tid: TypeId) -> Option<usize> {
+		self.storages
+			.get(&tid)
+			.map(|storage| storage.borrow().capacity_len().1)
+	}
+
+	/// Returns how many bytes the values of the component type `tid` have allocated across all
+	/// groups, or `None` if that type has no storage in this table.
+	///
+	/// Zero sized marker components never allocate, only their group membership is tracked.
+	///
+	/// Panics if that storage is already mutably locked.
+	pub fn storage_heap_bytes(&self, tid: TypeId) -> Option<usize> {
+		self.storages
+			.get(&tid)
+			.map(|storage| storage.borrow().heap_bytes())
+	}
+
+	/// Returns the internal `(group, index)` that the components of `entity` are stored at, or
+	/// `None` if it is not in this table, for building external acceleration structures.
+	///
+	/// **These indices are only valid until the next change to that group**, any insert, delete,
+	/// or transform of any entity in it may move other entities to a different index, so they must
+	/// be looked up again afterwards instead of cached.
+	pub fn raw_location(&self, entity: ValidEntity<EntityType>) -> Option<(usize, usize)> {
+		let entity = entity.raw_in(self.world_id).ok()?;
+		Self::get_valid_location(&self.reverse, &self.entities, entity)
+			.ok()
+			.map(|location| (location.group, location.index))
+	}
+
+	/// Returns which components `entity` has as a bitset indexed by storage index, the order of
+	/// `registered_component_types`, bits past its end are unset.  This is an O(1) presence test
+	/// that does not resolve the group of `entity`.
+	///
+	/// Returns `None` if `entity` is not in this table or the table was not built with
+	/// `component_masks` enabled.
+	pub fn component_mask(&self, entity: ValidEntity<EntityType>) -> Option<&BitSlice> {
+		let entity = entity.raw_in(self.world_id).ok()?;
+		self.component_masks.as_ref()?.get(entity.idx())
+	}
+
+	/// Returns the entity stored at the internal `(group, index)`, the reverse of `raw_location`,
+	/// with the same invalidation caveats.
+	pub fn entity_at(&self, group: usize, index: usize) -> Option<EntityType> {
+		self.entities.get(group)?.get(index).copied()
+	}
+
+	/// Returns how many distinct component types `entity` has, or `None` if it is not in this table.
+	pub fn component_count(&self, entity: ValidEntity<EntityType>) -> Option<usize> {
+		let entity = entity.raw_in(self.world_id).ok()?;
+		let location = Self::get_valid_location(&self.reverse, &self.entities, entity).ok()?;
+		self.group_inserts
+			.get_index(location.group)
+			.map(|(group_key, _group_value)| group_key.include.len())
+	}
+
+	/// Calls `f` with the `TypeId` and value of every component of `entity`, in the order of its
+	/// group's types, each value can then be downcast to the actual type.  Does nothing if `entity`
+	/// is not in this table.
+	///
+	/// Panics if any of its storages is already mutably locked.
+	pub fn for_each_component(
+		&self,
+		entity: ValidEntity<EntityType>,
+		mut f: impl FnMut(TypeId, &dyn Any),
+	) {
+		let entity = match entity.raw_in(self.world_id) {
+			Ok(entity) => entity,
+			Err(_) => return,
+		};
+		let location = match Self::get_valid_location(&self.reverse, &self.entities, entity) {
+			Ok(location) => location,
+			Err(_) => return,
+		};
+		let (group_key, _group_value) = self
+			.group_inserts
+			.get_index(location.group)
+			.expect("group location is in invalid state with the groups");
+		for (&tid, &storage_idx) in group_key
+			.include
+			.iter()
+			.zip(group_key.include_storage_idxs.iter())
+		{
+			let storage = self.storages[storage_idx].borrow();
+			let value = storage
+				.get_any(location.group, location.index)
+				.expect("storage is in an invalid state with its group");
+			f(tid, value);
+		}
+	}
+
+	/// Iterates the `(group, entity_count)` of every group that holds at least one entity.
+	pub fn group_sizes(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+		self.entities
+			.iter()
+			.map(Vec::len)
+			.enumerate()
+			.filter(|&(_group, len)| len > 0)
+	}
+
+	/// Returns the `(group, entity_count)` of the group with the most entities, the lowest group on
+	/// ties, or `None` when the table is empty.
+	pub fn largest_group(&self) -> Option<(usize, usize)> {
+		self.group_sizes()
+			.max_by(|(a_group, a_len), (b_group, b_len)| {
+				a_len.cmp(b_len).then(b_group.cmp(a_group))
+			})
+	}
+
+	/// Returns the `(group, entity_count)` of the group with the fewest entities but at least one, the
+	/// lowest group on ties, or `None` when the table is empty.
+	pub fn smallest_nonempty_group(&self) -> Option<(usize, usize)> {
+		self.group_sizes().min_by_key(|&(_group, len)| len)
+	}
+
+	/// Returns the fraction of allocated component capacity across all storages that holds no value,
+	/// from `0.0` when every group is packed full to near `1.0` when mostly empty.
+	///
+	/// Panics if any storage is already mutably locked.
+	pub fn fragmentation(&self) -> f32 {
+		let (capacity, len) = self
+			.storages
+			.values()
+			.fold((0, 0), |(capacity, len), storage| {
+				let (c, l) = storage.borrow().capacity_len();
+				(capacity + c, len + l)
+			});
+		if capacity == 0 {
+			0.0
+		} else {
+			(capacity - len) as f32 / capacity as f32
+		}
+	}
+
+	/// Shrinks every group of every storage, and of the entities, down to what it holds.
+	///
+	/// Group vecs never give back memory on their own, so after deleting or transforming away most
+	/// of an archetype that won't refill soon this returns that memory.  It is not worthwhile on
+	/// groups that will grow again, as that just reallocates them, so check `fragmentation` first
+	/// and call this at a quiet point, such as after a level unload.
+	///
+	/// Panics if any storage is already locked.
+	pub fn defragment(&mut self) {
+		for entities in self.entities.iter_mut() {
+			entities.shrink_to_fit();
+		}
+		for storage in self.storages.values() {
+			storage.borrow_mut().shrink_to_fit();
+		}
+	}
+
 	fn ensure_group_count_on_storages(&mut self) {
 		let groups = self.group_inserts.len();
-		self.entities.resize(groups, Vec::new());
+		if self.entities.len() < groups {
+			self.entities.resize(groups, Vec::new());
+		}
+		// Storages created after a `reserve_groups` get the same spare capacity as the rest
+		let capacity = self.entities.capacity();
 		for storage in self.storages.values() {
-			storage.borrow_mut().ensure_group_count(groups);
+			let mut storage = storage.borrow_mut();
+			let count = storage.group_count();
+			if count < groups {
+				storage.reserve_groups(capacity - count);
+				storage.ensure_group_count(groups);
+			}
+		}
+	}
+
+	/// Pre-sizes a newly created `group` to the `PageConfig::group_capacity_hint`.
+	fn presize_group(&mut self, group: usize) {
+		let hint = self.page_config.group_capacity_hint;
+		if hint == 0 {
+			return;
+		}
+		self.entities[group].reserve(hint);
+		let (group_key, _group_value) = self.group_inserts.get_index(group).unwrap();
+		for &idx in group_key.include_storage_idxs.iter() {
+			let (_tid, storage) = self.storages.get_index(idx).unwrap();
+			storage.borrow_mut().reserve_in_group(group, hint);
 		}
 	}
 
@@ -584,6 +1828,11 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 		DenseEntityPagedMultiValueTableBuilder {
 			entity_table,
 			capacity: 0,
+			page_config: PageConfig::default(),
+			delete_policy: DeletePolicy::default(),
+			component_masks: false,
+			component_types: Vec::new(),
+			component_storages: Vec::new(),
 		}
 	}
 
@@ -594,6 +1843,11 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 		DenseEntityPagedMultiValueTableBuilder {
 			entity_table,
 			capacity,
+			page_config: PageConfig::default(),
+			delete_policy: DeletePolicy::default(),
+			component_masks: false,
+			component_types: Vec::new(),
+			component_storages: Vec::new(),
 		}
 	}
 
@@ -601,30 +1855,137 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 		&mut self,
 	) -> Result<GroupQuery<EntityType, VTs>, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>>
 	{
-		let group = if let Some(group) = self.group_queries.get(&TypeId::of::<VTs::Raw>()) {
-			group
-				.as_any()
-				.downcast_ref::<GroupQuery<EntityType, VTs>>()
-				.expect("failed to cast type to itself")
-				.clone()
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		include.sort();
+		let group = if let Some((idx, _key, group)) = self.group_queries.get_full(&include[..]) {
+			if let Some(group) = group.as_any().downcast_ref::<GroupQuery<EntityType, VTs>>() {
+				group.clone()
+			} else {
+				// The same types in another order, the storages only need to be gathered in its order
+				GroupQuery::<EntityType, VTs> {
+					group: idx,
+					only_group: None,
+					storage: VTs::get_or_create_storage(&mut self.storages),
+					_phantom: PhantomData,
+				}
+			}
 		} else {
 			let group = GroupQuery::<EntityType, VTs> {
 				group: self.group_queries.len(),
+				only_group: None,
 				storage: VTs::get_or_create_storage(&mut self.storages),
 				_phantom: PhantomData,
 			};
 			self.group_queries
-				.insert(TypeId::of::<VTs::Raw>(), Box::new(group.clone()));
+				.insert(include.as_slice().into(), Box::new(group.clone()));
 			self.ensure_group_count_on_storages();
 			group
 		};
 		Ok(group)
 	}
 
-	pub fn group_insert<VTs: InsertValueTypes>(
+	/// Returns a query bound to only the single archetype `group`, such as one found through
+	/// `find_archetype`, rather than to every group holding the types of `VTs`.
+	///
+	/// Errors if `group` does not exist or does not hold every type of `VTs`.
+	pub fn group_query_by_index<VTs: ValueTypes>(
 		&mut self,
-	) -> Result<
-		GroupInsert<EntityType, VTs>,
+		group: usize,
+	) -> Result<GroupQuery<EntityType, VTs>, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>>
+	{
+		let (group_key, _group_value) = self
+			.group_inserts
+			.get_index(group)
+			.ok_or(DenseEntityDynamicPagedMultiValueTableErrors::GroupDoesNotExist(group))?;
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		if let Some(&tid) = include.iter().find(|&tid| !group_key.include.contains(tid)) {
+			return Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(
+					group, tid,
+				),
+			);
+		}
+		Ok(GroupQuery {
+			group,
+			only_group: Some(group),
+			storage: VTs::get_or_create_storage(&mut self.storages),
+			_phantom: PhantomData,
+		})
+	}
+
+	/// Returns the group index of the archetype `group_insert::<VTs>` would use, or `None` if
+	/// that archetype has not been created yet.
+	pub fn find_archetype<VTs: InsertValueTypes>(&self) -> Option<usize> {
+		let include_tids = VTs::get_include_type_ids();
+		let key = QueryTypedPagedKey {
+			include: include_tids.as_slice(),
+		};
+		self.group_inserts
+			.get_full(&key)
+			.map(|(idx, _key, _group_page)| idx)
+	}
+
+	/// Returns how well a query for `VTs` narrows the table down, for tuning the component layout.
+	///
+	/// Takes no locks, it only looks at which groups hold every type of `VTs` and their sizes.
+	pub fn query_stats<VTs: ValueTypes>(&self) -> QueryStats {
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		let mut stats = QueryStats::default();
+		for (group, group_key) in self.group_inserts.keys().enumerate() {
+			if !include.iter().all(|tid| group_key.include.contains(tid)) {
+				continue;
+			}
+			stats.matching_groups += 1;
+			stats.total_entities += self.entities[group].len();
+			if self.entities[group].is_empty() {
+				stats.skipped_empty_groups += 1;
+			}
+		}
+		stats
+	}
+
+	/// Calls `callback` with the index and the component types of every group created from now on,
+	/// whether by `group_insert` or by transforming entities into a new set of types, such as to
+	/// warn when the archetype count explodes.  Queries never create groups.
+	pub fn on_archetype_created(&mut self, callback: impl FnMut(usize, &[TypeId]) + 'static) {
+		self.on_archetype_created.push(Box::new(callback));
+	}
+
+	fn archetype_created(
+		callbacks: &mut [ArchetypeCreatedCallback],
+		group_inserts: &IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
+		group: usize,
+	) {
+		if let Some((group_key, _group_value)) = group_inserts.get_index(group) {
+			for callback in callbacks.iter_mut() {
+				callback(group, &group_key.include);
+			}
+		}
+	}
+
+	/// Returns how many storages would move each entity's value when transforming entities of
+	/// `from_group` by removing `Remove` and adding `Add`, for batching transforms that share a
+	/// transition.
+	///
+	/// Panics if `from_group` does not exist.
+	pub fn transition_cost<Remove: RemoveTypes, Add: InsertValueTypes>(
+		&self,
+		from_group: usize,
+	) -> usize {
+		let (group_key, _group_value) = self
+			.group_inserts
+			.get_index(from_group)
+			.expect("transition_cost called with a group that does not exist");
+		group_key.moving_storages::<Remove, Add>().len()
+	}
+
+	pub fn group_insert<VTs: InsertValueTypes>(
+		&mut self,
+	) -> Result<
+		GroupInsert<EntityType, VTs>,
 		DenseEntityDynamicPagedMultiValueTableErrors<EntityType>,
 	> {
 		let include_tids = VTs::get_include_type_ids();
@@ -660,35 +2021,788 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 				storage_idxs: VTs::get_storage_idxs(&self.storages, Vec::new()).into_boxed_slice(),
 				_phantom: PhantomData,
 			};
-			self.group_inserts
-				.insert(key.to_box(&self.storages), Some(Box::new(group.clone())));
+			let (_group_idx, existing) = self
+				.group_inserts
+				.insert_full(key.to_box(&self.storages), Some(Box::new(group.clone())));
 			self.ensure_group_count_on_storages();
+			self.presize_group(group.group);
+			if existing.is_none() {
+				Self::archetype_created(
+					&mut self.on_archetype_created,
+					&self.group_inserts,
+					group.group,
+				);
+			}
 			group
 		};
 		Ok(group)
 	}
 
+	/// Creates the group for `VTs` if it does not exist yet and fills it with `entities` and their
+	/// `columns` in one shot, for restoring a group from a save.
+	///
+	/// The entities are taken as already valid, such as when loading them alongside their
+	/// `EntityTable`, and must not already be in this table.  Panics if any column is not the same
+	/// length as `entities`.
+	pub fn load_archetype<VTs: InsertValueTypes>(
+		&mut self,
+		entities: &[EntityType],
+		columns: VTs::MoveDataVec,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		if !VTs::ensure_vec_length(&columns, entities.len()) {
+			panic!(
+				"All columns passed to `load_archetype` must be the same length as the entities"
+			);
+		}
+		let mut inserter = self.group_insert::<VTs>()?;
+		let mut lock = inserter.lock(self);
+		let group = lock.group;
+		VTs::extend(&mut lock.storage_locked, group, columns);
+		lock.table.entities[group].reserve(entities.len());
+		for &entity in entities {
+			Self::insert_valid_location_mut(
+				&mut lock.table.reverse,
+				&mut lock.table.entities,
+				entity,
+				group,
+			)
+			.expect("Entity already exists, all entities passed to `load_archetype` must be new to the table");
+		}
+		if let Some(masks) = &mut lock.table.component_masks {
+			let (group_key, _group_value) = lock.table.group_inserts.get_index(group).unwrap();
+			for &entity in entities {
+				masks.set(entity.idx(), &group_key.include_storage_idxs);
+			}
+		}
+		Ok(())
+	}
+
+	/// Writes the entities of `group` and each of its component columns to `w` as raw bytes, behind
+	/// a small header of the entity count and the size and `TypeId` of each type, for fast binary
+	/// snapshots that `read_archetype` restores.
+	///
+	/// The group must hold exactly the types of `VTs`, in any order.  Panics if any of its storages
+	/// is mutably locked.
+	pub fn write_archetype<VTs: PodInsertValueTypes, W: Write>(
+		&self,
+		group: usize,
+		w: &mut W,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let (group_key, _group_value) = self
+			.group_inserts
+			.get_index(group)
+			.ok_or(DenseEntityDynamicPagedMultiValueTableErrors::GroupDoesNotExist(group))?;
+		let include_tids = VTs::get_include_type_ids();
+		if let Some(&tid) = include_tids
+			.iter()
+			.find(|&tid| !group_key.include.contains(tid))
+		{
+			return Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(
+					group, tid,
+				),
+			);
+		}
+		if let Some(&tid) = group_key
+			.include
+			.iter()
+			.find(|&tid| !include_tids.contains(tid))
+		{
+			return Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::StorageNotCoveredByTypes(group, tid),
+			);
+		}
+		let mut layouts = Vec::new();
+		VTs::push_layouts(&mut layouts);
+		write_u64(w, self.entities[group].len() as u64)?;
+		write_u64(w, std::mem::size_of::<EntityType>() as u64)?;
+		write_u64(w, layouts.len() as u64)?;
+		for &(size, tid_hash) in layouts.iter() {
+			write_u64(w, size)?;
+			write_u64(w, tid_hash)?;
+		}
+		write_pod_slice(w, &self.entities[group])?;
+		VTs::write_columns(&self.storages, group, w)?;
+		Ok(())
+	}
+
+	/// Restores an archetype written by `write_archetype` into the group of `VTs`, the same way as
+	/// `load_archetype`.
+	///
+	/// Errors with an `IoError` of `InvalidData` if the header does not match the types of `VTs`.
+	///
+	/// # Safety
+	///
+	/// The bytes of each value are taken as they are, `r` must hold an archetype written by
+	/// `write_archetype` for the same types by a build with the same type layouts.  The header
+	/// only catches mismatched types and sizes, not corrupted values.
+	pub unsafe fn read_archetype<VTs: PodInsertValueTypes, R: Read>(
+		&mut self,
+		r: &mut R,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let count = read_u64(r)? as usize;
+		let entity_size = read_u64(r)?;
+		let layout_count = read_u64(r)?;
+		let mut read_layouts = Vec::new();
+		for _ in 0..layout_count {
+			read_layouts.push((read_u64(r)?, read_u64(r)?));
+		}
+		let mut layouts = Vec::new();
+		VTs::push_layouts(&mut layouts);
+		if entity_size != std::mem::size_of::<EntityType>() as u64 || read_layouts != layouts {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"archetype was written with different types",
+			)
+			.into());
+		}
+		let entities = read_pod_vec::<EntityType, R>(r, count)?;
+		let columns = VTs::read_columns(r, count)?;
+		self.load_archetype::<VTs>(&entities, columns)
+	}
+
+	/// Inserts `entity` into the group of `VTs` with the `Default` value of each of its types, for
+	/// spawning an entity from a template without supplying its values.
+	pub fn insert_with_defaults<VTs: DefaultInsertValueTypes>(
+		&mut self,
+		entity: ValidEntity<EntityType>,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let mut inserter = self.group_insert::<VTs>()?;
+		inserter.lock(self).insert(entity, VTs::default_move_data())
+	}
+
 	pub fn delete(
 		&mut self,
 		entity: ValidEntity<EntityType>,
 	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
-		let location =
-			Self::remove_valid_location(&mut self.reverse, &mut self.entities, entity.raw())?;
+		self.delete_raw(entity.raw_in(self.world_id)?)
+	}
+
+	/// Inserts `entity` with the values of `VTs` if it is not in this table yet, otherwise gives it
+	/// those values, overwriting the ones it already has.
+	///
+	/// An entity already in the group of `VTs` has its values overwritten in place, one in any other
+	/// group is moved with `AllLock::transform` to the group with the types of `VTs` added.
+	pub fn upsert<VTs: InsertValueTypes>(
+		&mut self,
+		entity: ValidEntity<EntityType>,
+		data: VTs::MoveData,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let raw = entity.raw_in(self.world_id)?;
+		let mut inserter = self.group_insert::<VTs>()?;
+		if self.reverse.get(raw).is_err() {
+			return inserter.lock(self).insert(entity, data);
+		}
+		let location = *Self::get_valid_location(&self.reverse, &self.entities, raw)?;
+		if location.group == inserter.group {
+			let mut lock = inserter.lock(self);
+			VTs::replace(
+				&mut lock.storage_locked,
+				location.group,
+				location.index,
+				data,
+			);
+			Ok(())
+		} else {
+			self.lock()?.transform::<(), VTs>(entity, &inserter, data)
+		}
+	}
+
+	/// Moves every entity and its values out of this table in group order, leaving it empty, for
+	/// teardown that needs the owned values.
+	///
+	/// Every group with entities must have exactly the types of `VTs`, otherwise this errors
+	/// without removing anything.
+	pub fn drain_all<VTs: InsertValueTypes>(
+		&mut self,
+	) -> Result<
+		std::vec::IntoIter<(EntityType, VTs::MoveData)>,
+		DenseEntityDynamicPagedMultiValueTableErrors<EntityType>,
+	> {
+		let include_tids = VTs::get_include_type_ids();
+		for (group, group_key) in self.group_inserts.keys().enumerate() {
+			if self.entities[group].is_empty() {
+				continue;
+			}
+			if let Some(&tid) = include_tids
+				.iter()
+				.find(|&&tid| !group_key.include.contains(&tid))
+			{
+				return Err(
+					DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(
+						group, tid,
+					),
+				);
+			}
+			if let Some(&tid) = group_key
+				.include
+				.iter()
+				.find(|&&tid| !include_tids.contains(&tid))
+			{
+				return Err(
+					DenseEntityDynamicPagedMultiValueTableErrors::StorageNotCoveredByTypes(
+						group, tid,
+					),
+				);
+			}
+		}
+		let storage = VTs::get_or_create_storage(&mut self.storages);
+		self.ensure_group_count_on_storages();
+		let mut storage_locked =
+			VTs::try_storage_locked(&storage).expect("unable to lock storages for drain_all");
+		let mut drained = Vec::new();
+		for group in 0..self.entities.len() {
+			let entities = std::mem::take(&mut self.entities[group]);
+			if entities.is_empty() {
+				continue;
+			}
+			let mut data = VTs::take_group(&mut storage_locked, group);
+			let start = drained.len();
+			// Popped from the back so reversed after to keep the group order
+			for entity in entities.into_iter().rev() {
+				let values =
+					VTs::pop(&mut data).expect("storage is in an invalid state with its entities");
+				drained.push((entity, values));
+			}
+			drained[start..].reverse();
+		}
+		self.reverse = SecondaryEntityIndex::new(ComponentLocations::INVALID);
+		if let Some(masks) = &mut self.component_masks {
+			masks.clear();
+		}
+		Ok(drained.into_iter())
+	}
+
+	/// Deletes every entity that has a `T` component, returning how many were deleted.
+	pub fn delete_all_with<T: 'static>(&mut self) -> usize {
+		let tid = TypeId::of::<T>();
+		let mut deleted = 0;
+		for group in 0..self.group_inserts.len() {
+			let (group_key, _group_value) = self.group_inserts.get_index(group).unwrap();
+			if !group_key.include.contains(&tid) {
+				continue;
+			}
+			// In reverse so each swap-remove only ever removes the last value of the group
+			for index in (0..self.entities[group].len()).rev() {
+				let entity = self.entities[group][index];
+				self.delete_raw(entity)
+					.expect("entity in a group must be valid");
+				deleted += 1;
+			}
+		}
+		deleted
+	}
+
+	fn delete_raw(
+		&mut self,
+		entity: EntityType,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let location = Self::remove_valid_location(
+			&mut self.reverse,
+			&mut self.entities,
+			entity,
+			self.delete_policy,
+		)?;
 		let storage_idxs = &self
 			.group_inserts
 			.get_index(location.group)
 			.unwrap()
 			.0
 			.include_storage_idxs;
+		for idx in storage_idxs.iter().copied() {
+			self.storages[idx].borrow_mut().remove(
+				location.group,
+				location.index,
+				self.delete_policy,
+			);
+		}
+		if let Some(masks) = &mut self.component_masks {
+			masks.remove(entity.idx());
+		}
+
+		Ok(())
+	}
+
+	/// Swaps the storage slots of two entities of the same group, reordering them within the group
+	/// without a delete and reinsert, each entity keeps its own component values.
+	///
+	/// Errors with `EntitiesNotInSameGroup` if the entities have different archetypes.
+	pub fn swap_components(
+		&mut self,
+		a: ValidEntity<EntityType>,
+		b: ValidEntity<EntityType>,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let a_location =
+			*Self::get_valid_location(&self.reverse, &self.entities, a.raw_in(self.world_id)?)?;
+		let b_location =
+			*Self::get_valid_location(&self.reverse, &self.entities, b.raw_in(self.world_id)?)?;
+		if a_location.group != b_location.group {
+			return Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::EntitiesNotInSameGroup(
+					a.raw(),
+					b.raw(),
+				),
+			);
+		}
+		let group = a_location.group;
+		let storage_idxs = &self
+			.group_inserts
+			.get_index(group)
+			.unwrap()
+			.0
+			.include_storage_idxs;
 		for idx in storage_idxs.iter().copied() {
 			self.storages[idx]
 				.borrow_mut()
-				.swap_remove(location.group, location.index);
+				.swap(group, a_location.index, b_location.index);
+		}
+		self.entities[group].swap(a_location.index, b_location.index);
+		self.reverse
+			.get_mut(a.raw())
+			.expect("SecondaryIndex is in invalid state")
+			.index = b_location.index;
+		self.reverse
+			.get_mut(b.raw())
+			.expect("SecondaryIndex is in invalid state")
+			.index = a_location.index;
+		Ok(())
+	}
+
+	/// Reorders every storage of `group` and its entities in lockstep into ascending order of their
+	/// `T` component, so iterating that group walks the values of `T` in sorted order.
+	///
+	/// The sort is stable.  Panics if any storage of the group is already locked.
+	pub fn sort_group_by<T: Ord + 'static>(
+		&mut self,
+		group: usize,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let (group_key, _group_value) = self
+			.group_inserts
+			.get_index(group)
+			.ok_or(DenseEntityDynamicPagedMultiValueTableErrors::GroupDoesNotExist(group))?;
+		let tid = TypeId::of::<T>();
+		let position = group_key
+			.include
+			.iter()
+			.position(|&include_tid| include_tid == tid)
+			.ok_or(
+				DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(
+					group, tid,
+				),
+			)?;
+		let order = {
+			let storage = self.storages[group_key.include_storage_idxs[position]].borrow();
+			let values = storage
+				.as_any()
+				.downcast_ref::<DensePagedData<T>>()
+				.expect("storage is in an invalid state with its type")
+				.group(group)
+				.expect("storage is in an invalid state with its groups");
+			let mut order: Vec<usize> = (0..values.len()).collect();
+			order.sort_by(|&a, &b| values[a].cmp(&values[b]));
+			order
+		};
+		for idx in group_key.include_storage_idxs.iter().copied() {
+			self.storages[idx].borrow_mut().permute(group, &order);
+		}
+		let entities = &mut self.entities[group];
+		*entities = order.iter().map(|&index| entities[index]).collect();
+		for (index, &entity) in entities.iter().enumerate() {
+			self.reverse
+				.get_mut(entity)
+				.expect("SecondaryIndex is in invalid state")
+				.index = index;
+		}
+		Ok(())
+	}
+
+	/// Replaces the `Old` component of every entity with the `New` one made from it by `convert`,
+	/// for renaming or refactoring a component type, so queries on `New` then match them.
+	///
+	/// This is a heavy structural operation, every archetype holding `Old` gets `New` in its place
+	/// in its key, or if that archetype already exists its entities are moved into it.  Any
+	/// `GroupInsert` for an archetype holding `Old` must be fetched again afterwards.
+	///
+	/// Errors without changing anything if any archetype holds both `Old` and `New`.  Panics if any
+	/// storage is already locked.
+	pub fn remap_component<Old: 'static, New: 'static>(
+		&mut self,
+		convert: impl Fn(Old) -> New,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let old_tid = TypeId::of::<Old>();
+		let new_tid = TypeId::of::<New>();
+		let old_idx = match self.storages.get_full(&old_tid) {
+			Some((old_idx, _tid, _storage)) => old_idx,
+			None => return Ok(()),
+		};
+		// Each group holding `Old` along with the position of `Old` in its key and the existing
+		// group it merges into, if any
+		let mut remapping = Vec::new();
+		for (group, group_key) in self.group_inserts.keys().enumerate() {
+			let position = match group_key.include.iter().position(|&tid| tid == old_tid) {
+				Some(position) => position,
+				None => continue,
+			};
+			if group_key.include.contains(&new_tid) {
+				return Err(
+					DenseEntityDynamicPagedMultiValueTableErrors::StorageAlreadyExistsInGroup(
+						group, new_tid,
+					),
+				);
+			}
+			let mut include = group_key.include.to_vec();
+			include[position] = new_tid;
+			let key = QueryTypedPagedKey {
+				include: include.as_slice(),
+			};
+			let target = self
+				.group_inserts
+				.get_full(&key)
+				.map(|(target, _key, _group_value)| target);
+			remapping.push((group, position, target));
+		}
+		if remapping.is_empty() {
+			return Ok(());
+		}
+
+		<(&'static mut New, ())>::get_or_create_storage(&mut self.storages);
+		self.ensure_group_count_on_storages();
+		let new_idx = self.storages.get_full(&new_tid).unwrap().0;
+		let old_storage = self.storages[old_idx].clone();
+		let new_storage = self.storages[new_idx].clone();
+		let mut old_storage = old_storage.borrow_mut();
+		let mut new_storage = new_storage.borrow_mut();
+		let old_data = old_storage
+			.as_any_mut()
+			.downcast_mut::<DensePagedData<Old>>()
+			.expect("storage is in an invalid state with its type");
+		let new_data = new_storage
+			.as_any_mut()
+			.downcast_mut::<DensePagedData<New>>()
+			.expect("storage is in an invalid state with its type");
+
+		let mut rekeying = vec![None; self.group_inserts.len()];
+		for &(group, position, target) in remapping.iter() {
+			let target = if let Some(target) = target {
+				target
+			} else {
+				// The group keeps its place and only its `Old` values become `New` values
+				new_data.extend(group, old_data.take_group(group).into_iter().map(&convert));
+				rekeying[group] = Some(position);
+				continue;
+			};
+			// The group keeps its key but is emptied into the existing group with `New` instead,
+			// every value is popped off the end so each storage moves them in the same order
+			let indices: Vec<usize> = (0..self.entities[group].len()).rev().collect();
+			let (group_key, _group_value) = self.group_inserts.get_index(group).unwrap();
+			for &idx in group_key.include_storage_idxs.iter() {
+				if idx != old_idx {
+					self.storages[idx]
+						.borrow_mut()
+						.move_groups_many(group, &indices, target);
+				}
+			}
+			new_data.extend(
+				target,
+				old_data.take_group(group).into_iter().rev().map(&convert),
+			);
+			let entities = std::mem::take(&mut self.entities[group]);
+			for entity in entities.into_iter().rev() {
+				self.entities[target].push(entity);
+				let location = self
+					.reverse
+					.get_mut(entity)
+					.expect("SecondaryIndex is in invalid state");
+				location.group = target;
+				location.index = self.entities[target].len() - 1;
+			}
+		}
+
+		// Keys can't be changed in place so the groups are reinserted in the same order, the cached
+		// inserters of the rekeyed groups are for `Old` so they are dropped to be recreated on demand
+		let group_inserts = std::mem::take(&mut self.group_inserts);
+		self.group_inserts = group_inserts
+			.into_iter()
+			.zip(rekeying)
+			.map(|((mut group_key, group_value), position)| {
+				if let Some(position) = position {
+					group_key.include[position] = new_tid;
+					group_key.include_storage_idxs[position] = new_idx;
+					(group_key, None)
+				} else {
+					(group_key, group_value)
+				}
+			})
+			.collect();
+		if let Some(masks) = &mut self.component_masks {
+			for &(group, _position, target) in remapping.iter() {
+				let group = target.unwrap_or(group);
+				let (group_key, _group_value) = self.group_inserts.get_index(group).unwrap();
+				for entity in self.entities[group].iter() {
+					masks.set(entity.idx(), &group_key.include_storage_idxs);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Merges archetypes holding the same set of components under a different order, such as one
+	/// from `group_insert` and one from adding and removing components, into the first of them so
+	/// each set of components is only a single group.
+	///
+	/// The emptied groups keep their keys, so inserting through their `GroupInsert` fills them
+	/// again.  Panics if any storage is already locked.
+	pub fn coalesce_archetypes(&mut self) {
+		let mut targets: IndexMap<Box<[TypeId]>, usize> = IndexMap::new();
+		for group in 0..self.group_inserts.len() {
+			let (group_key, _group_value) = self.group_inserts.get_index(group).unwrap();
+			let mut include: Box<[TypeId]> = group_key.include.clone();
+			include.sort();
+			let target = *targets.entry(include).or_insert(group);
+			if target == group || self.entities[group].is_empty() {
+				continue;
+			}
+			// Every value is popped off the end so each storage moves them in the same order
+			let indices: Vec<usize> = (0..self.entities[group].len()).rev().collect();
+			for &idx in group_key.include_storage_idxs.iter() {
+				self.storages[idx]
+					.borrow_mut()
+					.move_groups_many(group, &indices, target);
+			}
+			let entities = std::mem::take(&mut self.entities[group]);
+			for entity in entities.into_iter().rev() {
+				self.entities[target].push(entity);
+				let location = self
+					.reverse
+					.get_mut(entity)
+					.expect("SecondaryIndex is in invalid state");
+				location.group = target;
+				location.index = self.entities[target].len() - 1;
+			}
+		}
+	}
+
+	/// Deletes every entity that has all the components of `VTs` for which `keep` returns false,
+	/// entities without all of the components of `VTs` are kept.
+	///
+	/// `keep` is given references that only live for that one call, as each group may be changed by
+	/// the deletes right after.
+	pub fn retain<VTs>(
+		&mut self,
+		mut keep: impl for<'a> FnMut(
+			ValidEntity<EntityType>,
+			<VTs as GetValueTypes<'a>>::GetRef,
+		) -> bool,
+	) where
+		VTs: for<'a> GetValueTypes<'a>,
+	{
+		let storage = VTs::get_or_create_storage(&mut self.storages);
+		self.ensure_group_count_on_storages();
+		let mut include = TypeIdCacheVec::new();
+		VTs::push_type_ids(&mut include);
+		for group in 0..self.group_inserts.len() {
+			let (group_key, _group_value) = self.group_inserts.get_index(group).unwrap();
+			if !include.iter().all(|tid| group_key.include.contains(tid)) {
+				continue;
+			}
+			let mut deleting = Vec::new();
+			{
+				let mut storage_locked =
+					VTs::try_storage_locked(&storage).expect("unable to lock storages for retain");
+				let mut cast_storages = VTs::cast_locked_storages::<VTs>(&mut storage_locked);
+				// In reverse so the swap-removes when deleting only move already visited entities
+				for index in (0..self.entities[group].len()).rev() {
+					let entity = self.entities[group][index];
+					let values = VTs::get::<EntityType>(
+						// TODO:  LACK OF GAT's IS SO PAINFUL!  FIX THIS WHEN GAT's EXIST!
+						// This 'should' be safeish as it's just casting lifetimes to a more constrained lifetime
+						unsafe { &mut *(&mut cast_storages as *mut VTs::StoragesLockedRef) },
+						group,
+						index,
+					)
+					.expect("storage is in an invalid state with its entities");
+					if !keep(ValidEntity::new_unchecked(entity, self.world_id), values) {
+						deleting.push(entity);
+					}
+				}
+			}
+			for entity in deleting {
+				self.delete_raw(entity)
+					.expect("entity in a group must be valid");
+			}
+		}
+	}
+
+	/// Returns every entity whose reverse location does not point back to itself, the
+	/// `EntityGenerationMismatch` condition, so monitoring can detect a corrupted table without
+	/// changing it.
+	///
+	/// This finds the entities held by a group whose reverse location is missing or points elsewhere,
+	/// and the reverse locations that point outside of every group or at a different entity index.
+	/// As the reverse locations only know the entity index the latter are returned with a version of
+	/// 0, unless the entity was already found by the former.
+	pub fn audit(&self) -> Vec<EntityType> {
+		let mut dangling = Vec::new();
+		for (group, group_entities) in self.entities.iter().enumerate() {
+			for (index, &entity) in group_entities.iter().enumerate() {
+				match self.reverse.get(entity) {
+					Ok(location) if location.group == group && location.index == index => (),
+					_ => dangling.push(entity),
+				}
+			}
+		}
+		for (entity, location) in self.reverse.iter() {
+			let points_back = self
+				.entity_at(location.group, location.index)
+				.map_or(false, |found| found.idx() == entity.idx());
+			if !points_back && dangling.iter().all(|found| found.idx() != entity.idx()) {
+				dangling.push(entity);
+			}
 		}
+		dangling
+	}
 
+	/// Verifies the internal state of this table is consistent, returning a description of the first
+	/// inconsistency found.  All storages must be unlocked.
+	///
+	/// This is for tracking down bugs and is not fast, it checks that:
+	/// * Every entity's reverse location points to the slot holding that same entity.
+	/// * Every storage in a group holds the same number of values as there are entities in it, and
+	///   storages not in a group hold none.
+	/// * The storage indexes of every group line up with the storages of the same type.
+	pub fn debug_check_invariants(&self) -> Result<(), String> {
+		let group_count = self.group_inserts.len();
+		if self.entities.len() != group_count {
+			return Err(format!(
+				"entity group count {} does not match the group count {}",
+				self.entities.len(),
+				group_count
+			));
+		}
+		for (group, entities) in self.entities.iter().enumerate() {
+			for (index, &entity) in entities.iter().enumerate() {
+				match self.reverse.get(entity) {
+					Ok(&location) if location == ComponentLocations { group, index } => (),
+					Ok(location) => {
+						return Err(format!(
+							"entity {:?} at group {} index {} has reverse location {:?}",
+							entity, group, index, location
+						))
+					}
+					Err(_) => {
+						return Err(format!(
+							"entity {:?} at group {} index {} has no reverse location",
+							entity, group, index
+						))
+					}
+				}
+			}
+		}
+		for (storage_idx, (tid, storage)) in self.storages.iter().enumerate() {
+			let storage = storage
+				.try_borrow()
+				.map_err(|_| format!("storage {} is locked", storage_idx))?;
+			if storage.get_idx() != storage_idx || storage.get_type_id() != *tid {
+				return Err(format!(
+					"storage {} has index {} and a mismatched type",
+					storage_idx,
+					storage.get_idx()
+				));
+			}
+			if storage.group_count() != group_count {
+				return Err(format!(
+					"storage {} has {} groups but there are {} groups",
+					storage_idx,
+					storage.group_count(),
+					group_count
+				));
+			}
+		}
+		for (group, (key, _group_insert)) in self.group_inserts.iter().enumerate() {
+			if key.include.len() != key.include_storage_idxs.len() {
+				return Err(format!(
+					"group {} has {} types but {} storage indexes",
+					group,
+					key.include.len(),
+					key.include_storage_idxs.len()
+				));
+			}
+			for (tid, &storage_idx) in key.include.iter().zip(key.include_storage_idxs.iter()) {
+				match self.storages.get_index(storage_idx) {
+					Some((storage_tid, _storage)) if storage_tid == tid => (),
+					_ => {
+						return Err(format!(
+							"group {} has storage index {} not matching its type",
+							group, storage_idx
+						))
+					}
+				}
+			}
+			for (storage_idx, storage) in self.storages.values().enumerate() {
+				let len = storage.borrow().group_len(group);
+				let expected = if key.include_storage_idxs.contains(&storage_idx) {
+					self.entities[group].len()
+				} else {
+					0
+				};
+				if len != expected {
+					return Err(format!(
+						"storage {} has {} values in group {} but should have {}",
+						storage_idx, len, group, expected
+					));
+				}
+			}
+		}
 		Ok(())
 	}
 
+	fn sorted_entities(&self) -> Vec<EntityType> {
+		let mut entities: Vec<_> = self.entities.iter().flatten().copied().collect();
+		entities.sort_unstable();
+		entities
+	}
+
+	/// Returns true if both tables hold the same entities, regardless of which groups they are in
+	/// or the order they were inserted.
+	pub fn entities_eq(&self, other: &Self) -> bool {
+		self.sorted_entities() == other.sorted_entities()
+	}
+
+	/// Returns true if the same entities have a `T` in both tables and those values are equal,
+	/// regardless of which groups they are in or the order they were inserted.
+	///
+	/// Panics if the `T` storage of either table is already mutably locked.
+	pub fn component_eq<T: PartialEq + 'static>(&self, other: &Self) -> bool {
+		fn sorted_values<'a, EntityType: Entity, T: 'static>(
+			table: &DenseEntityDynamicPagedMultiValueTable<EntityType>,
+			storage: &'a Option<Ref<dyn DynDensePagedData>>,
+		) -> Vec<(EntityType, &'a T)> {
+			// A placeholder from `with_component_types` holds no values either
+			let storage = if let Some(storage) = storage
+				.as_ref()
+				.and_then(|storage| storage.as_any().downcast_ref::<DensePagedData<T>>())
+			{
+				storage
+			} else {
+				return vec![];
+			};
+			let mut values: Vec<_> = table
+				.entities
+				.iter()
+				.enumerate()
+				.filter_map(|(group, entities)| Some((entities, storage.group(group)?)))
+				.flat_map(|(entities, data)| entities.iter().copied().zip(data.iter()))
+				.collect();
+			values.sort_unstable_by_key(|(entity, _value)| *entity);
+			values
+		}
+		let tid = TypeId::of::<T>();
+		let self_storage = self.storages.get(&tid).map(|storage| storage.borrow());
+		let other_storage = other.storages.get(&tid).map(|storage| storage.borrow());
+		sorted_values::<EntityType, T>(self, &self_storage)
+			== sorted_values::<EntityType, T>(other, &other_storage)
+	}
+
 	pub fn lock(
 		&mut self,
 	) -> Result<AllLock<EntityType>, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
@@ -704,8 +2818,103 @@ impl<EntityType: Entity> DenseEntityDynamicPagedMultiValueTable<EntityType> {
 			entities: &mut self.entities,
 			group_inserts: &mut self.group_inserts,
 			storages,
+			page_config: self.page_config,
+			delete_policy: self.delete_policy,
+			component_masks: &mut self.component_masks,
+			on_archetype_created: &mut self.on_archetype_created,
+			world_id: self.world_id,
 		})
 	}
+
+	/// Locks the storages of all of `inserters` at once, each only once, so entities can be inserted
+	/// into any of their archetypes without locking again for each.
+	///
+	/// Panics if any of those storages is already locked.
+	pub fn lock_inserters<'a>(
+		&'a mut self,
+		inserters: &mut [&mut dyn DynGroupInsert],
+	) -> MultiInsertLock<'a, EntityType> {
+		let mut locked_idxs: SmallVec<[usize; 32]> = inserters
+			.iter()
+			.flat_map(|inserter| inserter.storage_idxs().iter().copied())
+			.collect();
+		locked_idxs.sort_unstable();
+		locked_idxs.dedup();
+		let mut storages = SmallVec::with_capacity(locked_idxs.len());
+		for &idx in locked_idxs.iter() {
+			storages.push(OwningHandle::new_with_fn(
+				self.storages[idx].clone(),
+				|storage| {
+					// This `unsafe` is required because OwningHandle doesn't handle dyn traits on an inner type as it requires Sized needlessly
+					unsafe { RefCell::borrow_mut(&*storage) }
+				},
+			));
+		}
+		// The storage indexes of each inserter as positions in `storages` instead
+		let groups = inserters
+			.iter()
+			.map(|inserter| {
+				let positions = inserter
+					.storage_idxs()
+					.iter()
+					.map(|idx| locked_idxs.binary_search(idx).unwrap())
+					.collect();
+				(inserter.group(), positions)
+			})
+			.collect();
+		MultiInsertLock {
+			reverse: &mut self.reverse,
+			entities: &mut self.entities,
+			storages,
+			groups,
+			component_masks: &mut self.component_masks,
+			world_id: self.world_id,
+		}
+	}
+
+	/// Immutably locks every storage for reading any component of any entity by its `TypeId`.
+	///
+	/// Panics if any storage is already mutably locked.
+	pub fn query_all(&self) -> AllReadLock<EntityType> {
+		AllReadLock {
+			reverse: &self.reverse,
+			entities: &self.entities,
+			group_inserts: &self.group_inserts,
+			storages: self
+				.storages
+				.values()
+				.map(|storage| storage.borrow())
+				.collect(),
+			world_id: self.world_id,
+		}
+	}
+}
+
+pub struct AllReadLock<'a, EntityType: Entity> {
+	reverse: &'a SecondaryEntityIndex<EntityType, ComponentLocations>,
+	entities: &'a Vec<Vec<EntityType>>,
+	group_inserts: &'a IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
+	storages: SmallVec<[Ref<'a, dyn DynDensePagedData>; 32]>,
+	world_id: u32,
+}
+
+impl<'a, EntityType: Entity> AllReadLock<'a, EntityType> {
+	/// Get the component of the given type of the entity, it can then be downcast to the actual type.
+	pub fn get_any(&self, entity: ValidEntity<EntityType>, tid: TypeId) -> Option<&dyn Any> {
+		let location = DenseEntityDynamicPagedMultiValueTable::get_valid_location(
+			self.reverse,
+			self.entities,
+			entity.raw_in(self.world_id).ok()?,
+		)
+		.ok()?;
+		let (group_key, _group_value) = self.group_inserts.get_index(location.group)?;
+		let storage_idx = group_key
+			.include
+			.iter()
+			.position(|&t| t == tid)
+			.map(|i| group_key.include_storage_idxs[i])?;
+		self.storages[storage_idx].get_any(location.group, location.index)
+	}
 }
 
 // If this is worth increasing then please request with a reason
@@ -714,22 +2923,130 @@ type AllLockedStorages<'a> = SmallVec<
 		32],
 >;
 
-pub struct AllLock<'a, EntityType: Entity> {
+pub struct MultiInsertLock<'a, EntityType: Entity> {
+	reverse: &'a mut SecondaryEntityIndex<EntityType, ComponentLocations>,
+	entities: &'a mut Vec<Vec<EntityType>>,
+	/// Only the storages of the inserters, in storage index order.
+	storages: AllLockedStorages<'a>,
+	/// Each locked group with the positions in `storages` of the storages of its inserter.
+	groups: Vec<(usize, Box<[usize]>)>,
+	component_masks: &'a mut Option<ComponentMasks>,
+	world_id: u32,
+}
+
+impl<'a, EntityType: Entity> MultiInsertLock<'a, EntityType> {
+	/// Same as `GroupInsertLock::insert` into the group of `inserter`, panics if `inserter` was not
+	/// one of those this was locked with.
+	pub fn insert<VTs: InsertValueTypes>(
+		&mut self,
+		inserter: &GroupInsert<EntityType, VTs>,
+		entity: ValidEntity<EntityType>,
+		data: VTs::MoveData,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let (_group, positions) = self
+			.groups
+			.iter()
+			.find(|(group, _positions)| *group == inserter.group)
+			.expect("GroupInsert was not locked by this MultiInsertLock");
+		let entity = entity.raw_in(self.world_id)?;
+		let location =
+			DenseEntityDynamicPagedMultiValueTable::<EntityType>::insert_valid_location_mut(
+				self.reverse,
+				self.entities,
+				entity,
+				inserter.group,
+			)?;
+		VTs::push_prelocked(&mut self.storages, positions, location.group, data);
+		if let Some(masks) = self.component_masks.as_mut() {
+			masks.set(entity.idx(), &inserter.storage_idxs);
+		}
+		Ok(())
+	}
+}
+
+pub struct AllLock<'a, EntityType: Entity> {
 	reverse: &'a mut SecondaryEntityIndex<EntityType, ComponentLocations>,
 	entities: &'a mut Vec<Vec<EntityType>>,
 	group_inserts: &'a mut IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
 	storages: AllLockedStorages<'a>,
+	page_config: PageConfig,
+	delete_policy: DeletePolicy,
+	component_masks: &'a mut Option<ComponentMasks>,
+	on_archetype_created: &'a mut Vec<ArchetypeCreatedCallback>,
+	world_id: u32,
 }
 
 impl<'a, EntityType: Entity> AllLock<'a, EntityType> {
+	/// Returns mutable references to the `A` and the `B` component of `entity` at once, from their
+	/// two separate storages.
+	///
+	/// Errors with `ComponentTypeRequestedTwice` if `A` and `B` are the same type, as they would
+	/// alias, or with `StorageDoesNotExistInGroup` if the entity lacks either component.
+	pub fn get_two_mut<A: 'static, B: 'static>(
+		&mut self,
+		entity: ValidEntity<EntityType>,
+	) -> Result<(&mut A, &mut B), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		if TypeId::of::<A>() == TypeId::of::<B>() {
+			return Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::ComponentTypeRequestedTwice(
+					std::any::type_name::<A>(),
+				),
+			);
+		}
+		let location = *DenseEntityDynamicPagedMultiValueTable::get_valid_location(
+			self.reverse,
+			self.entities,
+			entity.raw_in(self.world_id)?,
+		)?;
+		let (group_key, _group_value) = self.group_inserts.get_index(location.group).unwrap();
+		let storage_idx_of = |tid: TypeId| {
+			group_key
+				.include
+				.iter()
+				.position(|&t| t == tid)
+				.map(|i| group_key.include_storage_idxs[i])
+				.ok_or(
+					DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(
+						location.group,
+						tid,
+					),
+				)
+		};
+		let a_idx = storage_idx_of(TypeId::of::<A>())?;
+		let b_idx = storage_idx_of(TypeId::of::<B>())?;
+		// Distinct types always have distinct storages so these never overlap
+		let (a_storage, b_storage) = if a_idx < b_idx {
+			let (low, high) = self.storages.split_at_mut(b_idx);
+			(&mut low[a_idx], &mut high[0])
+		} else {
+			let (low, high) = self.storages.split_at_mut(a_idx);
+			(&mut high[0], &mut low[b_idx])
+		};
+		let a = a_storage
+			.as_any_mut()
+			.downcast_mut::<DensePagedData<A>>()
+			.expect("storage is in an invalid state with its type")
+			.get_mut(location.group, location.index)
+			.expect("storage is in an invalid state with its entities");
+		let b = b_storage
+			.as_any_mut()
+			.downcast_mut::<DensePagedData<B>>()
+			.expect("storage is in an invalid state with its type")
+			.get_mut(location.group, location.index)
+			.expect("storage is in an invalid state with its entities");
+		Ok((a, b))
+	}
+
 	pub fn delete(
 		&mut self,
 		entity: ValidEntity<EntityType>,
 	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let entity = entity.raw_in(self.world_id)?;
 		let location = DenseEntityDynamicPagedMultiValueTable::remove_valid_location(
 			self.reverse,
 			self.entities,
-			entity.raw(),
+			entity,
+			self.delete_policy,
 		)?;
 		let storage_idxs = &self
 			.group_inserts
@@ -738,60 +3055,134 @@ impl<'a, EntityType: Entity> AllLock<'a, EntityType> {
 			.0
 			.include_storage_idxs;
 		for idx in storage_idxs.iter().copied() {
-			self.storages[idx].swap_remove(location.group, location.index);
+			self.storages[idx].remove(location.group, location.index, self.delete_policy);
+		}
+		if let Some(masks) = self.component_masks.as_mut() {
+			masks.remove(entity.idx());
 		}
 
 		Ok(())
 	}
 
+	/// Deletes a batch of entities, returning how many were deleted.
+	///
+	/// Entities that are not in this table, or are passed more than once, are skipped and not
+	/// counted, but one from a different `EntityTable` errors without deleting any.  The deletions
+	/// are grouped by group and done in descending index order so no remove ever moves an entity
+	/// that is still waiting to be deleted.
+	pub fn delete_many(
+		&mut self,
+		entities: &[ValidEntity<EntityType>],
+	) -> Result<usize, DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let mut batch = Vec::with_capacity(entities.len());
+		for entity in entities {
+			let entity = entity.raw_in(self.world_id)?;
+			if let Ok(location) = DenseEntityDynamicPagedMultiValueTable::get_valid_location(
+				self.reverse,
+				self.entities,
+				entity,
+			) {
+				batch.push((*location, entity));
+			}
+		}
+		batch.sort_unstable_by(|(a, _), (b, _)| a.group.cmp(&b.group).then(b.index.cmp(&a.index)));
+		batch.dedup_by_key(|(location, _entity)| *location);
+
+		for &(location, entity) in batch.iter() {
+			DenseEntityDynamicPagedMultiValueTable::remove_valid_location(
+				self.reverse,
+				self.entities,
+				entity,
+				self.delete_policy,
+			)?;
+			let storage_idxs = &self
+				.group_inserts
+				.get_index(location.group)
+				.unwrap()
+				.0
+				.include_storage_idxs;
+			for idx in storage_idxs.iter().copied() {
+				self.storages[idx].remove(location.group, location.index, self.delete_policy);
+			}
+			if let Some(masks) = self.component_masks.as_mut() {
+				masks.remove(entity.idx());
+			}
+		}
+
+		Ok(batch.len())
+	}
+
 	fn ensure_group_count_on_storages(
 		group_inserts: &mut IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
 		entities: &mut Vec<Vec<EntityType>>,
 		storages: &mut AllLockedStorages,
 	) {
 		let groups = group_inserts.len();
-		entities.resize(groups, Vec::new());
+		if entities.len() < groups {
+			entities.resize(groups, Vec::new());
+		}
+		let capacity = entities.capacity();
 		for storage in storages.iter_mut() {
-			storage.ensure_group_count(groups);
+			let count = storage.group_count();
+			if count < groups {
+				storage.reserve_groups(capacity - count);
+				storage.ensure_group_count(groups);
+			}
 		}
 	}
 
-	pub fn transform<Remove: RemoveTypes, Add: InsertValueTypes>(
-		&mut self,
-		entity: ValidEntity<EntityType>,
-		inserter: &GroupInsert<EntityType, Add>, // Not actually used, but its existence means the type storages exist
-		add: Add::MoveData,
-	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
-		let location = DenseEntityDynamicPagedMultiValueTable::get_valid_location_mut(
-			self.reverse,
-			self.entities,
-			entity.raw(),
-		)?;
-		let (group_key, _group_value) = self.group_inserts.get_index(location.group).unwrap();
-		let mut moving = ArrayVec::<[(TypeId, usize); 32]>::new();
-		moving.extend(
+	/// Pre-sizes the last, newly created, group to the `PageConfig::group_capacity_hint`.
+	fn presize_group(
+		page_config: PageConfig,
+		group_inserts: &IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
+		entities: &mut Vec<Vec<EntityType>>,
+		storages: &mut AllLockedStorages,
+	) {
+		let hint = page_config.group_capacity_hint;
+		if hint == 0 {
+			return;
+		}
+		let group = group_inserts.len() - 1;
+		entities[group].reserve(hint);
+		let (group_key, _group_value) = group_inserts.get_index(group).unwrap();
+		for &idx in group_key.include_storage_idxs.iter() {
+			storages[idx].reserve_in_group(group, hint);
+		}
+	}
+
+	/// Returns the storages that move along with an entity out of `group`, the storages being
+	/// removed, and the group it moves to once `Remove` is taken away and `Add` is added, creating
+	/// that group if it does not yet exist.
+	fn transform_target_group<Remove: RemoveTypes, Add: InsertValueTypes>(
+		group_inserts: &mut IndexMap<QueryTypedPagedKeyBoxed, Option<Box<dyn DynGroup>>>,
+		entities: &mut Vec<Vec<EntityType>>,
+		storages: &mut AllLockedStorages,
+		page_config: PageConfig,
+		on_archetype_created: &mut [ArchetypeCreatedCallback],
+		group: usize,
+	) -> (
+		ArrayVec<[(TypeId, usize); 32]>,
+		ArrayVec<[usize; 32]>,
+		usize,
+	) {
+		let (group_key, _group_value) = group_inserts.get_index(group).unwrap();
+		let moving = group_key.moving_storages::<Remove, Add>();
+
+		let mut removing_tids = TypeIdCacheVec::new();
+		Remove::push_type_ids(&mut removing_tids);
+		// Along with the ones `add` replaces, as those are pushed fresh into the new group
+		Add::push_type_ids(&mut removing_tids);
+		let mut removing = ArrayVec::<[usize; 32]>::new();
+		removing.extend(
 			group_key
 				.include
 				.iter()
 				.copied()
-				.zip(group_key.include_storage_idxs.iter().copied()),
+				.zip(group_key.include_storage_idxs.iter().copied())
+				.filter(|(tid, _idx)| removing_tids.iter().any(|t| t == tid))
+				.map(|(_tid, idx)| idx),
 		);
-		Remove::swap_remove_type_ids(&mut moving);
-		Add::swap_remove_type_ids(&mut moving);
-
-		// First remove the ones being perma-removed...
-		let mut removing = TypeIdCacheVec::new();
-		Remove::push_type_ids(&mut removing);
-		let storages = &mut self.storages;
-		group_key
-			.include
-			.iter()
-			.copied()
-			.zip(group_key.include_storage_idxs.iter().copied())
-			.filter(|(tid, _idx)| removing.iter().any(|t| t == tid))
-			.for_each(|(_tid, idx)| storages[idx].swap_remove(location.group, location.index));
 
-		// Then figure out where to move/add to...
 		let mut new_include = TypeIdCacheVec::new();
 		new_include.extend(moving.iter().map(|(tid, _idx)| *tid));
 		Add::push_type_ids(&mut new_include);
@@ -799,20 +3190,53 @@ impl<'a, EntityType: Entity> AllLock<'a, EntityType> {
 		let key = QueryTypedPagedKey {
 			include: new_include.as_slice(),
 		};
-		let new_group_idx = if let Some((group_idx, _group_key, _group_value)) =
-			self.group_inserts.get_full(&key)
-		{
-			group_idx
-		} else {
-			self.group_inserts
-				.insert(key.to_box_from_locked(&self.storages), None);
-			Self::ensure_group_count_on_storages(
-				&mut self.group_inserts,
-				&mut self.entities,
-				&mut self.storages,
-			);
-			self.group_inserts.len() - 1
-		};
+		let new_group_idx =
+			if let Some((group_idx, _group_key, _group_value)) = group_inserts.get_full(&key) {
+				group_idx
+			} else {
+				let (group_idx, existing) =
+					group_inserts.insert_full(key.to_box_from_locked(storages), None);
+				Self::ensure_group_count_on_storages(group_inserts, entities, storages);
+				Self::presize_group(page_config, group_inserts, entities, storages);
+				if existing.is_none() {
+					DenseEntityDynamicPagedMultiValueTable::<EntityType>::archetype_created(
+						on_archetype_created,
+						group_inserts,
+						group_idx,
+					);
+				}
+				group_idx
+			};
+		(moving, removing, new_group_idx)
+	}
+
+	/// Moves `entity` to the group with the types of `Remove` taken away and those of `Add` added,
+	/// the values of `Add` types that it already has are overwritten.
+	pub fn transform<Remove: RemoveTypes, Add: InsertValueTypes>(
+		&mut self,
+		entity: ValidEntity<EntityType>,
+		inserter: &GroupInsert<EntityType, Add>, // Not actually used, but its existence means the type storages exist
+		add: Add::MoveData,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		let entity = entity.raw_in(self.world_id)?;
+		let location = DenseEntityDynamicPagedMultiValueTable::get_valid_location_mut(
+			self.reverse,
+			self.entities,
+			entity,
+		)?;
+		let (moving, removing, new_group_idx) = Self::transform_target_group::<Remove, Add>(
+			&mut self.group_inserts,
+			&mut self.entities,
+			&mut self.storages,
+			self.page_config,
+			self.on_archetype_created,
+			location.group,
+		);
+
+		// First remove the ones being perma-removed...
+		for idx in removing {
+			self.storages[idx].swap_remove(location.group, location.index);
+		}
 
 		// Then add the new ones to the new location
 		Add::push_prelocked(
@@ -829,10 +3253,15 @@ impl<'a, EntityType: Entity> AllLock<'a, EntityType> {
 
 		// And move the entity itself in the index
 		let old_location = *location;
+		debug_assert!(old_location.is_valid());
 		self.entities[old_location.group].swap_remove(old_location.index);
-		self.entities[new_group_idx].push(entity.raw());
+		self.entities[new_group_idx].push(entity);
 		location.group = new_group_idx;
 		location.index = self.entities[new_group_idx].len() - 1;
+		if let Some(masks) = self.component_masks.as_mut() {
+			let (group_key, _group_value) = self.group_inserts.get_index(new_group_idx).unwrap();
+			masks.set(entity.idx(), &group_key.include_storage_idxs);
+		}
 		// While also fixing the moved entity that took its old place if it exists
 		let old_entity_group = &mut self.entities[old_location.group];
 		if old_location.index < old_entity_group.len() {
@@ -845,6 +3274,207 @@ impl<'a, EntityType: Entity> AllLock<'a, EntityType> {
 		}
 		Ok(())
 	}
+
+	/// Same as `transform` for many entities at once, each paired with the values of `Add` to give
+	/// it, the entities may come from different groups.
+	///
+	/// Entities are batched by their current group so each component storage moves the whole batch
+	/// in one `move_groups_many` call.  If any entity is invalid then nothing is transformed and the
+	/// error is returned, each entity must only be passed once.
+	pub fn transform_many<'e, Remove: RemoveTypes, Add: InsertValueTypes, I>(
+		&mut self,
+		inserter: &GroupInsert<EntityType, Add>, // Not actually used, but its existence means the type storages exist
+		entities: I,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>>
+	where
+		I: IntoIterator<Item = (ValidEntity<'e, EntityType>, Add::MoveData)>,
+	{
+		let mut batch = Vec::new();
+		for (entity, add) in entities {
+			let entity = entity.raw_in(self.world_id)?;
+			let location = *DenseEntityDynamicPagedMultiValueTable::get_valid_location(
+				self.reverse,
+				self.entities,
+				entity,
+			)?;
+			batch.push((location, entity, add));
+		}
+		// Grouped by group, then descending by index so swap removes don't disturb later indexes
+		batch.sort_unstable_by(|(a, _, _), (b, _, _)| {
+			a.group.cmp(&b.group).then(b.index.cmp(&a.index))
+		});
+		#[cfg(not(enrs_disable_asserts))]
+		assert!(
+			batch.windows(2).all(|w| w[0].0 != w[1].0),
+			"an entity was passed more than once to `transform_many`"
+		);
+
+		let mut indices = Vec::new();
+		let mut batch = batch.into_iter().peekable();
+		while let Some(&(ComponentLocations { group, .. }, _, _)) = batch.peek() {
+			let (moving, removing, new_group_idx) = Self::transform_target_group::<Remove, Add>(
+				&mut self.group_inserts,
+				&mut self.entities,
+				&mut self.storages,
+				self.page_config,
+				self.on_archetype_created,
+				group,
+			);
+			let mut group_batch = Vec::new();
+			while batch
+				.peek()
+				.map_or(false, |(location, _, _)| location.group == group)
+			{
+				let (location, entity, add) = batch.next().expect("was just peeked");
+				indices.push(location.index);
+				group_batch.push((entity, add));
+			}
+
+			self.transform_group_batch(
+				inserter,
+				group,
+				new_group_idx,
+				moving,
+				removing,
+				&indices,
+				group_batch,
+			);
+			indices.clear();
+		}
+		Ok(())
+	}
+
+	/// Same as `transform_many` for entities that all share the same group, given along with a
+	/// column of values of `Add` per type, the entity at each index getting the values at that same
+	/// index.
+	///
+	/// The target group is only resolved once for the whole batch.  Errors with
+	/// `IteratorsNotAllSameLength` if any column is not as long as `entities`, or with
+	/// `EntitiesNotInSameGroup` if they do not all share the same group, and then nothing is
+	/// transformed.
+	pub fn transform_batch<Remove: RemoveTypes, Add: InsertValueTypes>(
+		&mut self,
+		inserter: &GroupInsert<EntityType, Add>, // Not actually used, but its existence means the type storages exist
+		entities: &[ValidEntity<EntityType>],
+		mut adds: Add::MoveDataVec,
+	) -> Result<(), DenseEntityDynamicPagedMultiValueTableErrors<EntityType>> {
+		if !Add::ensure_vec_length(&adds, entities.len()) {
+			return Err(DenseEntityDynamicPagedMultiValueTableErrors::IteratorsNotAllSameLength);
+		}
+		let mut batch: Vec<(ComponentLocations, EntityType, Option<Add::MoveData>)> =
+			Vec::with_capacity(entities.len());
+		for entity in entities {
+			let entity = entity.raw_in(self.world_id)?;
+			let location = *DenseEntityDynamicPagedMultiValueTable::get_valid_location(
+				self.reverse,
+				self.entities,
+				entity,
+			)?;
+			if let Some((first, first_entity, _)) = batch.first() {
+				if first.group != location.group {
+					return Err(
+						DenseEntityDynamicPagedMultiValueTableErrors::EntitiesNotInSameGroup(
+							*first_entity,
+							entity,
+						),
+					);
+				}
+			}
+			batch.push((location, entity, None));
+		}
+		for (_location, _entity, add) in batch.iter_mut().rev() {
+			*add = Add::pop(&mut adds);
+		}
+		let group = match batch.first() {
+			Some((location, _, _)) => location.group,
+			None => return Ok(()),
+		};
+		// Descending by index so swap removes don't disturb later indexes
+		batch.sort_unstable_by(|(a, _, _), (b, _, _)| b.index.cmp(&a.index));
+		#[cfg(not(enrs_disable_asserts))]
+		assert!(
+			batch.windows(2).all(|w| w[0].0 != w[1].0),
+			"an entity was passed more than once to `transform_batch`"
+		);
+
+		let (moving, removing, new_group_idx) = Self::transform_target_group::<Remove, Add>(
+			&mut self.group_inserts,
+			&mut self.entities,
+			&mut self.storages,
+			self.page_config,
+			self.on_archetype_created,
+			group,
+		);
+		let indices: Vec<_> = batch
+			.iter()
+			.map(|(location, _, _)| location.index)
+			.collect();
+		self.transform_group_batch(
+			inserter,
+			group,
+			new_group_idx,
+			moving,
+			removing,
+			&indices,
+			batch.into_iter().map(|(_location, entity, add)| {
+				(entity, add.expect("columns were checked to be long enough"))
+			}),
+		);
+		Ok(())
+	}
+
+	/// Moves the entities at `indices` of `group`, which must be in descending order, to
+	/// `new_group_idx`, each with the values of `Add` paired with it in `batch`.
+	#[allow(clippy::too_many_arguments)]
+	fn transform_group_batch<Add: InsertValueTypes>(
+		&mut self,
+		inserter: &GroupInsert<EntityType, Add>,
+		group: usize,
+		new_group_idx: usize,
+		moving: ArrayVec<[(TypeId, usize); 32]>,
+		removing: ArrayVec<[usize; 32]>,
+		indices: &[usize],
+		batch: impl IntoIterator<Item = (EntityType, Add::MoveData)>,
+	) {
+		for idx in removing {
+			for &index in indices.iter() {
+				self.storages[idx].swap_remove(group, index);
+			}
+		}
+		for (_tid, idx) in moving {
+			self.storages[idx].move_groups_many(group, indices, new_group_idx);
+		}
+
+		for (&index, (entity, add)) in indices.iter().zip(batch) {
+			Add::push_prelocked(
+				&mut self.storages,
+				&inserter.storage_idxs,
+				new_group_idx,
+				add,
+			);
+			self.entities[group].swap_remove(index);
+			self.entities[new_group_idx].push(entity);
+			let location = self
+				.reverse
+				.get_mut(entity)
+				.expect("This should always exist as it was validated above");
+			location.group = new_group_idx;
+			location.index = self.entities[new_group_idx].len() - 1;
+			if let Some(masks) = self.component_masks.as_mut() {
+				let (group_key, _group_value) =
+					self.group_inserts.get_index(new_group_idx).unwrap();
+				masks.set(entity.idx(), &group_key.include_storage_idxs);
+			}
+			let old_entity_group = &mut self.entities[group];
+			if index < old_entity_group.len() {
+				let moved_entity = old_entity_group[index];
+				self.reverse
+					.get_mut(moved_entity)
+					.expect("This should always exist as it was just got from the entity array")
+					.index = index;
+			}
+		}
+	}
 }
 
 pub trait RemoveTypes: 'static {
@@ -899,6 +3529,13 @@ pub trait ValueTypes: 'static {
 	fn get_locked_storage_ref_mut<'s, TT: ValueTypes>(
 		storages: &mut Self::StorageLocked,
 	) -> &'s mut TT::SingleStorageLocked;
+	/// The values of `T` in `group`, or `None` if `T` is not one of these types.
+	fn get_column<T: 'static>(storages: &Self::StorageLocked, group: usize) -> Option<&[T]>;
+	/// The values of `T` in `group`, or `None` if `T` is not one of these types or is not mutable.
+	fn get_column_mut<T: 'static>(
+		storages: &mut Self::StorageLocked,
+		group: usize,
+	) -> Option<&mut [T]>;
 }
 
 // Ask if this should be increased in size, but honestly, more tables should probably be used instead
@@ -922,6 +3559,13 @@ pub trait InsertValueTypes: ValueTypes {
 	type MoveData: 'static;
 	type MoveDataVec: 'static;
 	fn push(storage_locked: &mut Self::StorageLocked, group: usize, data: Self::MoveData);
+	/// Overwrites the values at `index` of `group`, dropping the old values.
+	fn replace(
+		storage_locked: &mut Self::StorageLocked,
+		group: usize,
+		index: usize,
+		data: Self::MoveData,
+	);
 	fn push_prelocked(
 		storage_locked: &mut AllLockedStorages,
 		idxs: &[usize],
@@ -930,6 +3574,159 @@ pub trait InsertValueTypes: ValueTypes {
 	);
 	fn ensure_vec_length(data: &Self::MoveDataVec, len: usize) -> bool;
 	fn extend(storage_locked: &mut Self::StorageLocked, group: usize, data: Self::MoveDataVec);
+	/// Moves every value of `group` out, leaving it empty.
+	fn take_group(storage_locked: &mut Self::StorageLocked, group: usize) -> Self::MoveDataVec;
+	/// Removes the last values of `data`, or `None` if any of its vecs are empty.
+	fn pop(data: &mut Self::MoveDataVec) -> Option<Self::MoveData>;
+}
+
+/// A named set of components inserted together, usually declared with the `bundle!` macro.
+pub trait Bundle {
+	/// The types to pass to `DenseEntityDynamicPagedMultiValueTable::group_insert`.
+	type Insert: InsertValueTypes;
+	/// Converts this bundle into the values to pass to `GroupInsertLock::insert`.
+	fn into_data(self) -> <Self::Insert as InsertValueTypes>::MoveData;
+}
+
+/// `InsertValueTypes` where every type implements `Default`, for
+/// `DenseEntityDynamicPagedMultiValueTable::insert_with_defaults`.
+pub trait DefaultInsertValueTypes: InsertValueTypes {
+	/// The `Default` value of every type.
+	fn default_move_data() -> Self::MoveData;
+}
+
+impl DefaultInsertValueTypes for () {
+	#[inline(always)]
+	fn default_move_data() -> Self::MoveData {}
+}
+
+impl<HEAD: 'static + Default, TAIL: DefaultInsertValueTypes> DefaultInsertValueTypes
+	for (&'static mut HEAD, TAIL)
+{
+	#[inline(always)]
+	fn default_move_data() -> Self::MoveData {
+		(HEAD::default(), TAIL::default_move_data())
+	}
+}
+
+/// Types that are all plain `Copy` data, so their columns can be written as raw bytes by
+/// `DenseEntityDynamicPagedMultiValueTable::write_archetype` and read back by `read_archetype`.
+pub trait PodInsertValueTypes: InsertValueTypes {
+	/// Pushes the size and the hash of the `TypeId` of every type, to check a snapshot against.
+	fn push_layouts(arr: &mut Vec<(u64, u64)>);
+	/// Writes the values of `group` of every type as one column of raw bytes each.
+	fn write_columns<W: Write>(
+		storages: &IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
+		group: usize,
+		w: &mut W,
+	) -> io::Result<()>;
+	/// Reads `count` values of every type as written by `write_columns`.
+	///
+	/// # Safety
+	///
+	/// The bytes read must be valid values of each type.
+	unsafe fn read_columns<R: Read>(r: &mut R, count: usize) -> io::Result<Self::MoveDataVec>;
+}
+
+impl PodInsertValueTypes for () {
+	#[inline(always)]
+	fn push_layouts(_arr: &mut Vec<(u64, u64)>) {}
+
+	#[inline(always)]
+	fn write_columns<W: Write>(
+		_storages: &IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
+		_group: usize,
+		_w: &mut W,
+	) -> io::Result<()> {
+		Ok(())
+	}
+
+	#[inline(always)]
+	unsafe fn read_columns<R: Read>(_r: &mut R, _count: usize) -> io::Result<Self::MoveDataVec> {
+		Ok(())
+	}
+}
+
+impl<HEAD: 'static + Copy, TAIL: PodInsertValueTypes> PodInsertValueTypes
+	for (&'static mut HEAD, TAIL)
+{
+	#[inline]
+	fn push_layouts(arr: &mut Vec<(u64, u64)>) {
+		let mut hasher = DefaultHasher::new();
+		TypeId::of::<HEAD>().hash(&mut hasher);
+		arr.push((std::mem::size_of::<HEAD>() as u64, hasher.finish()));
+		TAIL::push_layouts(arr);
+	}
+
+	#[inline]
+	fn write_columns<W: Write>(
+		storages: &IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
+		group: usize,
+		w: &mut W,
+	) -> io::Result<()> {
+		{
+			let storage = storages
+				.get(&TypeId::of::<HEAD>())
+				.expect("storage does not exist for a type of its group")
+				.borrow();
+			let storage = storage
+				.as_any()
+				.downcast_ref::<DensePagedData<HEAD>>()
+				.expect("storage is in an invalid state with its type");
+			write_pod_slice(
+				w,
+				storage
+					.group(group)
+					.expect("storage is in an invalid state with its groups"),
+			)?;
+		}
+		TAIL::write_columns(storages, group, w)
+	}
+
+	#[inline]
+	unsafe fn read_columns<R: Read>(r: &mut R, count: usize) -> io::Result<Self::MoveDataVec> {
+		let column = read_pod_vec::<HEAD, R>(r, count)?;
+		Ok((column, TAIL::read_columns(r, count)?))
+	}
+}
+
+fn write_u64<W: Write>(w: &mut W, value: u64) -> io::Result<()> {
+	w.write_all(&value.to_ne_bytes())
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+	let mut bytes = [0; 8];
+	r.read_exact(&mut bytes)?;
+	Ok(u64::from_ne_bytes(bytes))
+}
+
+fn write_pod_slice<T: Copy, W: Write>(w: &mut W, values: &[T]) -> io::Result<()> {
+	// Any padding bytes of `T` are written as they are, they are never read as values
+	let bytes = unsafe {
+		std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+	};
+	w.write_all(bytes)
+}
+
+/// The bytes read must be valid values of `T`.
+unsafe fn read_pod_vec<T: Copy, R: Read>(r: &mut R, count: usize) -> io::Result<Vec<T>> {
+	let len = count
+		.checked_mul(std::mem::size_of::<T>())
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "value count is too large"))?;
+	let mut bytes = vec![0u8; len];
+	r.read_exact(&mut bytes)?;
+	let mut values = Vec::<T>::with_capacity(count);
+	std::ptr::copy_nonoverlapping(bytes.as_ptr(), values.as_mut_ptr() as *mut u8, len);
+	values.set_len(count);
+	Ok(values)
+}
+
+/// Returns the include `TypeId`s that form the group key of the archetype for `VTs`, the same key
+/// `DenseEntityDynamicPagedMultiValueTable::group_insert` looks its group up by.
+///
+/// The key is in the order the types appear in `VTs`, as `group_insert` does not sort it.
+pub fn archetype_key_of<VTs: InsertValueTypes>() -> Box<[TypeId]> {
+	VTs::get_include_type_ids().as_slice().into()
 }
 
 impl ValueTypes for () {
@@ -981,6 +3778,19 @@ impl ValueTypes for () {
 			std::any::type_name::<TT::SelfRaw>()
 		)
 	}
+
+	#[inline]
+	fn get_column<T: 'static>(_storages: &Self::StorageLocked, _group: usize) -> Option<&[T]> {
+		None
+	}
+
+	#[inline]
+	fn get_column_mut<T: 'static>(
+		_storages: &mut Self::StorageLocked,
+		_group: usize,
+	) -> Option<&mut [T]> {
+		None
+	}
 }
 
 impl InsertValueTypes for () {
@@ -996,6 +3806,15 @@ impl InsertValueTypes for () {
 	#[inline]
 	fn push(_storage_locked: &mut Self::StorageLocked, _group: usize, _data: Self::MoveData) {}
 
+	#[inline]
+	fn replace(
+		_storage_locked: &mut Self::StorageLocked,
+		_group: usize,
+		_index: usize,
+		_data: Self::MoveData,
+	) {
+	}
+
 	#[inline]
 	fn push_prelocked(
 		_storage_locked: &mut AllLockedStorages,
@@ -1012,6 +3831,14 @@ impl InsertValueTypes for () {
 
 	#[inline]
 	fn extend(_storage_locked: &mut Self::StorageLocked, _group: usize, _data: Self::MoveDataVec) {}
+
+	#[inline]
+	fn take_group(_storage_locked: &mut Self::StorageLocked, _group: usize) -> Self::MoveDataVec {}
+
+	#[inline]
+	fn pop(_data: &mut Self::MoveDataVec) -> Option<Self::MoveData> {
+		Some(())
+	}
 }
 
 pub enum CannotMoveGroupWithImmutableType {}
@@ -1055,27 +3882,19 @@ impl<HEAD: 'static, TAIL: ValueTypes> ValueTypes for (&'static HEAD, TAIL) {
 	fn get_or_create_storage(
 		storages: &mut IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
 	) -> Self::Storage {
-		let storage = if let Some(storage) = storages.get(&TypeId::of::<HEAD>()) {
-			storage
-				.borrow()
-				.as_any()
-				.downcast_ref::<DensePagedData<HEAD>>()
-				.expect("Failed to cast type to itself?")
-				.get_strong_self()
-		} else {
-			let storage = DensePagedData::<HEAD>::new(storages.len());
-			storages.insert(TypeId::of::<HEAD>(), storage.clone());
-			storage
-		};
+		let storage = DensePagedData::<HEAD>::get_or_create(storages);
 		(storage, TAIL::get_or_create_storage(storages))
 	}
 
 	#[inline]
 	fn try_storage_locked(storage: &Self::Storage) -> Result<Self::StorageLocked, ()> {
-		Ok((
-			OwningHandle::new(storage.0.clone()),
-			TAIL::try_storage_locked(&storage.1)?,
-		))
+		// Fails rather than panics when the storage is already mutably locked, such as by another
+		// query over overlapping groups, so they can never alias
+		let locked = OwningHandle::try_new(storage.0.clone(), |storage| unsafe {
+			(*storage).try_borrow()
+		})
+		.map_err(|_| ())?;
+		Ok((locked, TAIL::try_storage_locked(&storage.1)?))
 	}
 
 	#[inline]
@@ -1104,6 +3923,24 @@ impl<HEAD: 'static, TAIL: ValueTypes> ValueTypes for (&'static HEAD, TAIL) {
 			std::any::type_name::<TT::SelfRaw>()
 		)
 	}
+
+	#[inline]
+	fn get_column<T: 'static>(storages: &Self::StorageLocked, group: usize) -> Option<&[T]> {
+		let storage: &DensePagedData<HEAD> = &storages.0;
+		if let Some(storage) = (storage as &dyn Any).downcast_ref::<DensePagedData<T>>() {
+			storage.group(group)
+		} else {
+			TAIL::get_column::<T>(&storages.1, group)
+		}
+	}
+
+	#[inline]
+	fn get_column_mut<T: 'static>(
+		storages: &mut Self::StorageLocked,
+		group: usize,
+	) -> Option<&mut [T]> {
+		TAIL::get_column_mut::<T>(&mut storages.1, group)
+	}
 }
 
 impl<HEAD: 'static, TAIL: ValueTypes> ValueTypes for (&'static mut HEAD, TAIL) {
@@ -1145,27 +3982,19 @@ impl<HEAD: 'static, TAIL: ValueTypes> ValueTypes for (&'static mut HEAD, TAIL) {
 	fn get_or_create_storage(
 		storages: &mut IndexMap<TypeId, Rc<RefCell<dyn DynDensePagedData>>, UniqueHasherBuilder>,
 	) -> Self::Storage {
-		let storage = if let Some(storage) = storages.get(&TypeId::of::<HEAD>()) {
-			storage
-				.borrow()
-				.as_any()
-				.downcast_ref::<DensePagedData<HEAD>>()
-				.expect("Failed to cast type to itself?")
-				.get_strong_self()
-		} else {
-			let storage = DensePagedData::<HEAD>::new(storages.len());
-			storages.insert(TypeId::of::<HEAD>(), storage.clone());
-			storage
-		};
+		let storage = DensePagedData::<HEAD>::get_or_create(storages);
 		(storage, TAIL::get_or_create_storage(storages))
 	}
 
 	#[inline]
 	fn try_storage_locked(storage: &Self::Storage) -> Result<Self::StorageLocked, ()> {
-		Ok((
-			OwningHandle::new_mut(storage.0.clone()),
-			TAIL::try_storage_locked(&storage.1)?,
-		))
+		// Fails rather than panics when the storage is already locked, such as by another query
+		// over overlapping groups, so two `&mut` of the same values can never exist at once
+		let locked = OwningHandle::try_new(storage.0.clone(), |storage| unsafe {
+			(*storage).try_borrow_mut()
+		})
+		.map_err(|_| ())?;
+		Ok((locked, TAIL::try_storage_locked(&storage.1)?))
 	}
 
 	#[inline]
@@ -1203,6 +4032,29 @@ impl<HEAD: 'static, TAIL: ValueTypes> ValueTypes for (&'static mut HEAD, TAIL) {
 			TAIL::get_locked_storage_ref_mut::<TT>(&mut storages.1)
 		}
 	}
+
+	#[inline]
+	fn get_column<T: 'static>(storages: &Self::StorageLocked, group: usize) -> Option<&[T]> {
+		let storage: &DensePagedData<HEAD> = &storages.0;
+		if let Some(storage) = (storage as &dyn Any).downcast_ref::<DensePagedData<T>>() {
+			storage.group(group)
+		} else {
+			TAIL::get_column::<T>(&storages.1, group)
+		}
+	}
+
+	#[inline]
+	fn get_column_mut<T: 'static>(
+		storages: &mut Self::StorageLocked,
+		group: usize,
+	) -> Option<&mut [T]> {
+		let storage: &mut DensePagedData<HEAD> = &mut storages.0;
+		if let Some(storage) = (storage as &mut dyn Any).downcast_mut::<DensePagedData<T>>() {
+			storage.group_mut(group)
+		} else {
+			TAIL::get_column_mut::<T>(&mut storages.1, group)
+		}
+	}
 }
 
 impl<HEAD: 'static, TAIL: InsertValueTypes> InsertValueTypes for (&'static mut HEAD, TAIL) {
@@ -1225,6 +4077,17 @@ impl<HEAD: 'static, TAIL: InsertValueTypes> InsertValueTypes for (&'static mut H
 		TAIL::push(&mut storage_locked.1, group, data.1);
 	}
 
+	#[inline]
+	fn replace(
+		storage_locked: &mut Self::StorageLocked,
+		group: usize,
+		index: usize,
+		data: Self::MoveData,
+	) {
+		storage_locked.0.replace(group, index, data.0);
+		TAIL::replace(&mut storage_locked.1, group, index, data.1);
+	}
+
 	#[inline]
 	fn push_prelocked(
 		storage_locked: &mut AllLockedStorages,
@@ -1232,10 +4095,26 @@ impl<HEAD: 'static, TAIL: InsertValueTypes> InsertValueTypes for (&'static mut H
 		group: usize,
 		data: Self::MoveData,
 	) {
-		storage_locked[idxs[0]]
+		let storage = &mut storage_locked[idxs[0]];
+		let storage_tid = storage.get_type_id();
+		debug_assert_eq!(
+			storage_tid,
+			TypeId::of::<HEAD>(),
+			"storage index {} does not hold values of {}",
+			idxs[0],
+			std::any::type_name::<HEAD>(),
+		);
+		storage
 			.as_any_mut()
 			.downcast_mut::<DensePagedData<HEAD>>()
-			.expect("failed to cast type into self?")
+			.unwrap_or_else(|| {
+				panic!(
+					"storage index {} holds {:?} but {:?} was pushed",
+					idxs[0],
+					storage_tid,
+					TypeId::of::<HEAD>()
+				)
+			})
 			.push(group, data.0);
 		TAIL::push_prelocked(storage_locked, &idxs[1..], group, data.1)
 	}
@@ -1250,6 +4129,20 @@ impl<HEAD: 'static, TAIL: InsertValueTypes> InsertValueTypes for (&'static mut H
 		storage_locked.0.extend(group, data.0);
 		TAIL::extend(&mut storage_locked.1, group, data.1);
 	}
+
+	#[inline]
+	fn take_group(storage_locked: &mut Self::StorageLocked, group: usize) -> Self::MoveDataVec {
+		(
+			storage_locked.0.take_group(group),
+			TAIL::take_group(&mut storage_locked.1, group),
+		)
+	}
+
+	#[inline]
+	fn pop(data: &mut Self::MoveDataVec) -> Option<Self::MoveData> {
+		let value = data.0.pop()?;
+		Some((value, TAIL::pop(&mut data.1)?))
+	}
 }
 
 pub trait GetValueTypes<'a>: ValueTypes {
@@ -1267,6 +4160,16 @@ pub trait GetValueTypes<'a>: ValueTypes {
 		group: usize,
 		index: usize,
 	) -> Option<Self::GetRef>;
+	type StorageSlices: 'a;
+	fn get_slices(
+		storage_locked: &'a mut Self::StoragesLockedRef,
+		group: usize,
+	) -> Option<Self::StorageSlices>;
+	/// Splits every slice at `mid`, as `<[T]>::split_at` does.
+	fn split_slices_at(
+		slices: Self::StorageSlices,
+		mid: usize,
+	) -> (Self::StorageSlices, Self::StorageSlices);
 }
 
 impl<'a> GetValueTypes<'a> for () {
@@ -1288,6 +4191,24 @@ impl<'a> GetValueTypes<'a> for () {
 	) -> Option<Self::GetRef> {
 		Some(())
 	}
+
+	type StorageSlices = ();
+
+	#[inline]
+	fn get_slices(
+		_storage_locked: &'a mut Self::StoragesLockedRef,
+		_group: usize,
+	) -> Option<Self::StorageSlices> {
+		Some(())
+	}
+
+	#[inline]
+	fn split_slices_at(
+		_slices: Self::StorageSlices,
+		_mid: usize,
+	) -> (Self::StorageSlices, Self::StorageSlices) {
+		((), ())
+	}
 }
 
 impl<'a, HEAD: 'static, TAIL: GetValueTypes<'a>> GetValueTypes<'a> for (&'static HEAD, TAIL) {
@@ -1315,7 +4236,7 @@ impl<'a, HEAD: 'static, TAIL: GetValueTypes<'a>> GetValueTypes<'a> for (&'static
 		index: usize,
 	) -> Option<Self::GetRef> {
 		// TODO:  Maybe make the `group` access unchecked?
-		if let Some(found) = storage_locked.0.data[group].get(index) {
+		if let Some(found) = storage_locked.0.get(group, index) {
 			if let Some(rest) = TAIL::get::<EntityType>(&mut storage_locked.1, group, index) {
 				Some((found, rest))
 			} else {
@@ -1325,6 +4246,28 @@ impl<'a, HEAD: 'static, TAIL: GetValueTypes<'a>> GetValueTypes<'a> for (&'static
 			None
 		}
 	}
+
+	type StorageSlices = (&'a [HEAD], TAIL::StorageSlices);
+
+	#[inline]
+	fn get_slices(
+		storage_locked: &'a mut Self::StoragesLockedRef,
+		group: usize,
+	) -> Option<Self::StorageSlices> {
+		let found = storage_locked.0.group(group)?;
+		let rest = TAIL::get_slices(&mut storage_locked.1, group)?;
+		Some((found, rest))
+	}
+
+	#[inline]
+	fn split_slices_at(
+		slices: Self::StorageSlices,
+		mid: usize,
+	) -> (Self::StorageSlices, Self::StorageSlices) {
+		let (found_first, found_second) = slices.0.split_at(mid);
+		let (rest_first, rest_second) = TAIL::split_slices_at(slices.1, mid);
+		((found_first, rest_first), (found_second, rest_second))
+	}
 }
 
 impl<'a, HEAD: 'static, TAIL: GetValueTypes<'a>> GetValueTypes<'a> for (&'static mut HEAD, TAIL) {
@@ -1355,7 +4298,7 @@ impl<'a, HEAD: 'static, TAIL: GetValueTypes<'a>> GetValueTypes<'a> for (&'static
 		index: usize,
 	) -> Option<Self::GetRef> {
 		// TODO:  Maybe make the `group` access unchecked?
-		if let Some(found) = storage_locked.0.data[group].get_mut(index) {
+		if let Some(found) = storage_locked.0.get_mut(group, index) {
 			if let Some(rest) = TAIL::get::<EntityType>(&mut storage_locked.1, group, index) {
 				Some((found, rest))
 			} else {
@@ -1365,11 +4308,157 @@ impl<'a, HEAD: 'static, TAIL: GetValueTypes<'a>> GetValueTypes<'a> for (&'static
 			None
 		}
 	}
+
+	type StorageSlices = (&'a mut [HEAD], TAIL::StorageSlices);
+
+	#[inline]
+	fn get_slices(
+		storage_locked: &'a mut Self::StoragesLockedRef,
+		group: usize,
+	) -> Option<Self::StorageSlices> {
+		let found = storage_locked.0.group_mut(group)?;
+		let rest = TAIL::get_slices(&mut storage_locked.1, group)?;
+		Some((found, rest))
+	}
+
+	#[inline]
+	fn split_slices_at(
+		slices: Self::StorageSlices,
+		mid: usize,
+	) -> (Self::StorageSlices, Self::StorageSlices) {
+		let (found_first, found_second) = slices.0.split_at_mut(mid);
+		let (rest_first, rest_second) = TAIL::split_slices_at(slices.1, mid);
+		((found_first, rest_first), (found_second, rest_second))
+	}
+}
+
+/// Tunes how the groups of a `DenseEntityDynamicPagedMultiValueTable` are allocated.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PageConfig {
+	/// How many values each new group is pre-sized to hold, so workloads where every archetype
+	/// grows to about the same size don't reallocate while filling it.  0 allocates on first insert.
+	pub group_capacity_hint: usize,
+}
+
+/// How selective a query is, as returned by `DenseEntityDynamicPagedMultiValueTable::query_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryStats {
+	/// How many groups hold every component of the query, including empty ones.
+	pub matching_groups: usize,
+	/// How many entities the query would visit across all of its matching groups.
+	pub total_entities: usize,
+	/// How many of the matching groups are empty and so are skipped while iterating.
+	pub skipped_empty_groups: usize,
+}
+
+/// How deleting an entity of a `DenseEntityDynamicPagedMultiValueTable` fills the hole it leaves
+/// in its group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeletePolicy {
+	/// Moves the last entity of the group into the hole, O(1) but reorders the group.
+	SwapRemove,
+	/// Shifts every following entity of the group down by one, O(n) but keeps the group in order.
+	ShiftRemove,
+}
+
+impl Default for DeletePolicy {
+	fn default() -> Self {
+		DeletePolicy::SwapRemove
+	}
+}
+
+/// The bitsets of which storages hold a component of each entity, indexed by entity index.
+#[derive(Default)]
+struct ComponentMasks {
+	masks: Vec<Option<BitVec>>,
+}
+
+impl ComponentMasks {
+	fn set(&mut self, idx: usize, storage_idxs: &[usize]) {
+		if self.masks.len() <= idx {
+			self.masks.resize_with(idx + 1, || None);
+		}
+		let mask = self.masks[idx].get_or_insert_with(BitVec::new);
+		mask.clear();
+		if let Some(&last) = storage_idxs.iter().max() {
+			mask.resize(last + 1, false);
+		}
+		for &storage_idx in storage_idxs {
+			mask.set(storage_idx, true);
+		}
+	}
+
+	fn remove(&mut self, idx: usize) {
+		if let Some(mask) = self.masks.get_mut(idx) {
+			*mask = None;
+		}
+	}
+
+	fn get(&self, idx: usize) -> Option<&BitSlice> {
+		self.masks.get(idx)?.as_deref()
+	}
+
+	fn clear(&mut self) {
+		self.masks.clear();
+	}
 }
 
 pub struct DenseEntityPagedMultiValueTableBuilder<EntityType: Entity> {
 	entity_table: Rc<RefCell<EntityTable<EntityType>>>,
 	capacity: usize,
+	page_config: PageConfig,
+	delete_policy: DeletePolicy,
+	component_masks: bool,
+	component_types: Vec<TypeId>,
+	component_storages: Vec<(TypeId, ComponentStorageBuilder)>,
+}
+
+type ComponentStorageBuilder = Box<dyn FnOnce(usize) -> Rc<RefCell<dyn DynDensePagedData>>>;
+
+impl<EntityType: Entity> DenseEntityPagedMultiValueTableBuilder<EntityType> {
+	pub fn page_config(mut self, page_config: PageConfig) -> Self {
+		self.page_config = page_config;
+		self
+	}
+
+	pub fn delete_policy(mut self, delete_policy: DeletePolicy) -> Self {
+		self.delete_policy = delete_policy;
+		self
+	}
+
+	/// Keeps a bitset per entity of which components it has for `component_mask`, costing a
+	/// bitset of up to the storage count bits for every entity ever in the table.
+	pub fn component_masks(mut self, enabled: bool) -> Self {
+		self.component_masks = enabled;
+		self
+	}
+
+	/// Pre-registers a storage for each of `tids`, in order, so the storages are sized for them
+	/// from the start instead of growing as each type is first used.
+	///
+	/// Each stays a type-erased placeholder until its first typed use.
+	pub fn with_component_types(mut self, tids: &[TypeId]) -> Self {
+		self.component_types.extend_from_slice(tids);
+		self
+	}
+
+	/// Keeps the values of `T` in `columns` instead of on the heap, such as a
+	/// `MmapDensePagedData` behind the `mmap` feature, `columns` must not have any groups yet.
+	///
+	/// Registered after the `with_component_types` storages, or in place of the one for `T`.
+	pub fn with_component_storage<T: 'static>(
+		mut self,
+		columns: impl DensePagedColumns<T>,
+	) -> Self {
+		let columns: Box<dyn DensePagedColumns<T>> = Box::new(columns);
+		self.component_storages.push((
+			TypeId::of::<T>(),
+			Box::new(move |idx| {
+				DensePagedData::with_columns(idx, columns) as Rc<RefCell<dyn DynDensePagedData>>
+			}),
+		));
+		self
+	}
 }
 
 impl<EntityType: Entity> TableBuilder for DenseEntityPagedMultiValueTableBuilder<EntityType> {
@@ -1382,6 +4471,20 @@ impl<EntityType: Entity> TableBuilder for DenseEntityPagedMultiValueTableBuilder
 		table_id: TableId,
 	) -> Rc<RefCell<Self::Table>> {
 		let mut entities = self.entity_table.borrow_mut();
+		let mut storages =
+			IndexMap::with_capacity_and_hasher(self.component_types.len(), UniqueHasherBuilder);
+		for &tid in self.component_types.iter() {
+			let idx = storages.len();
+			storages.entry(tid).or_insert_with(|| {
+				PlaceholderPagedData::new(idx, tid) as Rc<RefCell<dyn DynDensePagedData>>
+			});
+		}
+		for (tid, build_storage) in self.component_storages {
+			let idx = storages
+				.get_index_of(&tid)
+				.unwrap_or_else(|| storages.len());
+			storages.insert(tid, build_storage(idx));
+		}
 		let this = Rc::new(RefCell::new(DenseEntityDynamicPagedMultiValueTable::<
 			EntityType,
 		> {
@@ -1389,15 +4492,29 @@ impl<EntityType: Entity> TableBuilder for DenseEntityPagedMultiValueTableBuilder
 			database_id,
 			table_name: table_name.into(),
 			table_id,
+			entity_table: Rc::downgrade(&self.entity_table),
+			world_id: entities.world_id(),
 			reverse: SecondaryEntityIndex::new(ComponentLocations::INVALID),
 			entities: Vec::with_capacity(self.capacity),
-			storages: IndexMap::default(),
+			storages,
 			group_inserts: IndexMap::default(),
 			group_queries: IndexMap::default(),
+			page_config: self.page_config,
+			delete_policy: self.delete_policy,
+			component_masks: if self.component_masks {
+				Some(ComponentMasks::default())
+			} else {
+				None
+			},
+			on_archetype_created: Vec::new(),
 		}));
 		this.borrow_mut().this = Rc::downgrade(&this);
-		let another_this = this.clone();
+		let another_this = Rc::downgrade(&this);
 		let _id = entities.on_delete_entity(Box::new(move |_entity_table_id, entity| {
+			let another_this = match another_this.upgrade() {
+				Some(another_this) => another_this,
+				None => return,
+			};
 			if let Ok(mut deleter) = another_this.try_borrow_mut() {
 				// Ignore the entity does not exist error
 				let _ = deleter.delete(entity);// .expect("Unknown deletion error while deleting valid entity");
@@ -1429,6 +4546,37 @@ impl<EntityType: Entity> Table for DenseEntityDynamicPagedMultiValueTable<Entity
 	fn table_id(&self) -> TableId {
 		self.table_id
 	}
+
+	fn rename(&mut self, new_name: &str) -> bool {
+		self.table_name = new_name.into();
+		true
+	}
+
+	fn rehome(&mut self, database_id: DatabaseId, table_id: TableId) -> bool {
+		self.database_id = database_id;
+		self.table_id = table_id;
+		true
+	}
+
+	fn entity_table(&self) -> Option<Rc<RefCell<dyn Table>>> {
+		self.entity_table
+			.upgrade()
+			.map(|entity_table| entity_table as Rc<RefCell<dyn Table>>)
+	}
+
+	fn clear_table(&mut self) {
+		// Groups are kept as existing `GroupInsert`'s and `GroupQuery`'s refer to them by index
+		self.reverse = SecondaryEntityIndex::new(ComponentLocations::INVALID);
+		for entities in self.entities.iter_mut() {
+			entities.clear();
+		}
+		for storage in self.storages.values() {
+			storage.borrow_mut().clear();
+		}
+		if let Some(masks) = &mut self.component_masks {
+			masks.clear();
+		}
+	}
 }
 
 impl<EntityType: Entity> TableCastable for DenseEntityDynamicPagedMultiValueTable<EntityType> {
@@ -1440,9 +4588,16 @@ impl<EntityType: Entity> TableCastable for DenseEntityDynamicPagedMultiValueTabl
 #[cfg(test)]
 mod tests {
 	use crate::database::*;
-	use crate::tables::dense_entity_dynamic_paged_multi_value_table::DenseEntityDynamicPagedMultiValueTable;
+	use crate::entity::Entity;
+	use crate::tables::dense_entity_dynamic_paged_multi_value_table::{
+		archetype_key_of, join, ComponentLocations, DeletePolicy,
+		DenseEntityDynamicPagedMultiValueTable, DenseEntityDynamicPagedMultiValueTableErrors,
+		DensePagedData, InsertValueTypes, PageConfig, QueryStats,
+	};
 	use crate::tables::entity_table::EntityTable;
+	use crate::utils::secondary_entity_index::SecondaryEntityIndexErrors;
 	use crate::{tl, TL};
+	use std::any::TypeId;
 	use std::cell::RefCell;
 	use std::rc::Rc;
 
@@ -1467,47 +4622,1267 @@ mod tests {
 	}
 
 	#[test]
-	fn transforms() {
+	fn find_archetype() {
+		let (_database, _entities_storage, multi_storage) = basic_setup();
+		let mut multi = multi_storage.borrow_mut();
+		assert_eq!(multi.find_archetype::<TL![&mut bool, &mut usize]>(), None);
+		let inserter = multi.group_insert::<TL![&mut bool, &mut usize]>().unwrap();
+		assert_eq!(
+			multi.find_archetype::<TL![&mut bool, &mut usize]>(),
+			Some(inserter.group)
+		);
+		assert_eq!(multi.find_archetype::<TL![&mut bool]>(), None);
+		assert_eq!(
+			&*archetype_key_of::<TL![&mut bool, &mut usize]>(),
+			&[TypeId::of::<bool>(), TypeId::of::<usize>()]
+		);
+	}
+
+	#[test]
+	fn reserve_groups() {
 		let (_database, entities_storage, multi_storage) = basic_setup();
 		let mut entities = entities_storage.borrow_mut();
 		let mut multi = multi_storage.borrow_mut();
-		let mut first_inserter = multi
-			.group_insert::<TL![&mut bool, &mut usize, &mut u8]>()
+		let _first = multi.group_insert::<TL![&mut bool]>().unwrap();
+		multi.reserve_groups(8);
+		let mut inserter = multi.group_insert::<TL![&mut bool, &mut usize]>().unwrap();
+		let _third = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let entity = entities.insert();
+		inserter
+			.lock(&mut multi)
+			.insert(entity, tl![true, 42])
 			.unwrap();
-		let next_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
-		let mut query_before = multi.group_query::<TL![&bool, &usize]>().unwrap();
-		let mut query_after = multi.group_query::<TL![&bool, &isize]>().unwrap();
-		let entity1 = entities.insert();
+		assert_eq!(multi.find_archetype::<TL![&mut u8]>(), Some(2));
+		let mut query = multi.group_query::<TL![&bool, &usize]>().unwrap();
+		assert_eq!(
+			query.lock(&multi).get::<TL![&bool, &usize]>(entity),
+			Some(tl![&true, &42])
+		);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn join_tables() {
+		struct Position(i32);
+		struct Velocity(i32);
+		let (mut database, entities_storage, positions_storage) = basic_setup();
+		let velocities_storage = database
+			.tables
+			.create(
+				"velocities",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut positions = positions_storage.borrow_mut();
+		let mut velocities = velocities_storage.borrow_mut();
+		let mut position_inserter = positions.group_insert::<TL![&mut Position]>().unwrap();
+		let mut velocity_inserter = velocities.group_insert::<TL![&mut Velocity]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(4).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			position_inserter
+				.lock(&mut positions)
+				.insert(e, tl![Position(i as i32)])
+				.unwrap();
+			if i % 2 == 0 {
+				velocity_inserter
+					.lock(&mut velocities)
+					.insert(e, tl![Velocity(10)])
+					.unwrap();
+			}
+		}
+		let mut position_query = positions.group_query::<TL![&mut Position]>().unwrap();
+		let mut velocity_query = velocities.group_query::<TL![&Velocity]>().unwrap();
+		{
+			let mut position_lock = position_query.lock(&positions);
+			let mut velocity_lock = velocity_query.lock(&velocities);
+			let mut joined = 0;
+			for ((position, ()), velocity) in join(&mut position_lock, &mut velocity_lock) {
+				if let Some((velocity, ())) = velocity {
+					position.0 += velocity.0;
+					joined += 1;
+				}
+			}
+			assert_eq!(joined, 2);
+		}
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let expected = if i % 2 == 0 { i as i32 + 10 } else { i as i32 };
+			assert_eq!(
+				position_query
+					.lock(&positions)
+					.get_all(entities.valid(e).unwrap())
+					.map(|(position, ())| position.0),
+				Some(expected)
+			);
+		}
+	}
+
+	#[test]
+	fn defragment() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		assert_eq!(multi.fragmentation(), 0.0);
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(100).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			inserter
+				.lock(&mut multi)
+				.insert(entities.valid(e).unwrap(), tl![i, 1])
+				.unwrap();
+		}
+		let before = multi.fragmentation();
+		for &e in entity_vec.iter().skip(10) {
+			multi.delete(entities.valid(e).unwrap()).unwrap();
+		}
+		assert!(multi.fragmentation() > before);
+		assert!(multi.fragmentation() > 0.5);
+		multi.defragment();
+		assert!(multi.fragmentation() < 0.01);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn group_query_by_index() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
 		first_inserter
 			.lock(&mut multi)
-			.insert(entity1, tl![true, 42, 16])
+			.insert(entities.valid(entity1).unwrap(), tl![1])
+			.unwrap();
+		second_inserter
+			.lock(&mut multi)
+			.insert(entities.valid(entity2).unwrap(), tl![2, 2])
 			.unwrap();
+		let second = multi.find_archetype::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut query = multi.group_query_by_index::<TL![&usize]>(second).unwrap();
 		assert_eq!(
-			query_before.lock(&multi).get::<TL![&usize]>(entity1),
-			Some(tl![&42])
+			query.lock(&multi).get_all(entities.valid(entity1).unwrap()),
+			None
 		);
 		assert_eq!(
-			query_before.lock(&multi).get::<TL![&bool, &usize]>(entity1),
-			Some(tl![&true, &42])
+			query.lock(&multi).get_all(entities.valid(entity2).unwrap()),
+			Some(tl![&2])
 		);
-		assert_eq!(query_after.lock(&multi).get::<TL![&isize]>(entity1), None);
-		{
-			let mut lock = multi.lock().unwrap();
-			lock.transform::<TL![usize], _>(entity1, &next_inserter, tl![21isize])
+		assert_eq!(
+			query
+				.lock(&multi)
+				.iter_with_entities()
+				.map(|(entity, (value, ()))| (entity, *value))
+				.collect::<Vec<_>>(),
+			vec![(entity2, 2)]
+		);
+		let first = multi.find_archetype::<TL![&mut usize]>().unwrap();
+		assert!(matches!(
+			multi.group_query_by_index::<TL![&u8]>(first),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(group, tid))
+				if group == first && tid == TypeId::of::<u8>()
+		));
+		assert!(matches!(
+			multi.group_query_by_index::<TL![&usize]>(2),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::GroupDoesNotExist(2))
+		));
+	}
+
+	#[test]
+	fn component_eq() {
+		let (mut database, entities_storage, multi_storage) = basic_setup();
+		let other_storage = database
+			.tables
+			.create(
+				"other",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut other = other_storage.borrow_mut();
+		let entity_vec: Vec<_> = entities.extend_iter().take(6).map(|e| e.raw()).collect();
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			inserter
+				.lock(&mut multi)
+				.insert(entities.valid(e).unwrap(), tl![i])
 				.unwrap();
 		}
-		assert_eq!(query_before.lock(&multi).get::<TL![&usize]>(entity1), None);
+		let mut first_inserter = other.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut second_inserter = other.group_insert::<TL![&mut usize]>().unwrap();
+		for (i, &e) in entity_vec.iter().enumerate().rev() {
+			let e = entities.valid(e).unwrap();
+			if i % 2 == 0 {
+				first_inserter
+					.lock(&mut other)
+					.insert(e, tl![i, 1])
+					.unwrap();
+			} else {
+				second_inserter.lock(&mut other).insert(e, tl![i]).unwrap();
+			}
+		}
+		assert!(multi.entities_eq(&other));
+		assert!(multi.component_eq::<usize>(&other));
+		assert!(!multi.component_eq::<u8>(&other));
+		let entity = entities.valid(entity_vec[3]).unwrap();
+		let mut query = other.group_query::<TL![&mut usize]>().unwrap();
+		*query.lock(&other).get_all(entity).unwrap().0 = 42;
+		assert!(!multi.component_eq::<usize>(&other));
+		other.delete(entity).unwrap();
+		assert!(!multi.entities_eq(&other));
+	}
+
+	#[test]
+	fn load_archetype() {
+		let (mut database, entities_storage, multi_storage) = basic_setup();
+		let restored_storage = database
+			.tables
+			.create(
+				"restored",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut restored = restored_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		for i in 0..8 {
+			let e = entities.insert();
+			inserter
+				.lock(&mut multi)
+				.insert(e, tl![i * 10, i as u8])
+				.unwrap();
+		}
+		let mut query = multi.group_query::<TL![&usize, &u8]>().unwrap();
+		let (mut entity_vec, mut usizes, mut u8s) = (Vec::new(), Vec::new(), Vec::new());
+		for (entity, (a, (b, ()))) in query.lock(&multi).iter_with_entities() {
+			entity_vec.push(entity);
+			usizes.push(*a);
+			u8s.push(*b);
+		}
+		restored
+			.load_archetype::<TL![&mut usize, &mut u8]>(&entity_vec, tl![usizes, u8s])
+			.unwrap();
 		assert_eq!(
-			query_after.lock(&multi).get::<TL![&bool, &isize]>(entity1),
-			Some(tl![&true, &21])
+			restored.find_archetype::<TL![&mut usize, &mut u8]>(),
+			Some(0)
 		);
+		assert!(multi.entities_eq(&restored));
+		assert!(multi.component_eq::<usize>(&restored));
+		assert!(multi.component_eq::<u8>(&restored));
+		restored.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn delete_many() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(10).map(|e| e.raw()).collect();
+		let outside = entities.insert().raw();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			if i % 2 == 0 {
+				first_inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+			} else {
+				second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, i as u8])
+					.unwrap();
+			}
+		}
+		let doomed = [0, 1, 3, 4, 8, 9];
+		let mut batch: Vec<_> = doomed
+			.iter()
+			.map(|&i| entities.valid(entity_vec[i]).unwrap())
+			.collect();
+		batch.push(entities.valid(outside).unwrap());
+		batch.push(entities.valid(entity_vec[3]).unwrap());
 		{
 			let mut lock = multi.lock().unwrap();
-			lock.transform::<TL![isize], _>(entity1, &first_inserter, tl![false, 42usize, 16])
-				.unwrap();
+			assert_eq!(lock.delete_many(&batch).unwrap(), doomed.len());
+			assert_eq!(lock.delete_many(&batch[..2]).unwrap(), 0);
 		}
-		assert_eq!(
-			query_before.lock(&multi).get::<TL![&bool, &usize]>(entity1),
+		multi.debug_check_invariants().unwrap();
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let expected = if doomed.contains(&i) { None } else { Some(i) };
+			assert_eq!(
+				query
+					.lock(&multi)
+					.get_all(entities.valid(e).unwrap())
+					.map(|(value, ())| *value),
+				expected
+			);
+		}
+	}
+
+	#[test]
+	fn columns() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut f32, &mut usize]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(4).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			inserter
+				.lock(&mut multi)
+				.insert(entities.valid(e).unwrap(), tl![i as f32, i])
+				.unwrap();
+		}
+		let group = multi.find_archetype::<TL![&mut f32, &mut usize]>().unwrap();
+		let mut query = multi.group_query::<TL![&mut f32, &usize]>().unwrap();
+		{
+			let mut lock = query.lock(&multi);
+			assert_eq!(lock.column::<usize>(group), Some(&[0, 1, 2, 3][..]));
+			assert_eq!(lock.column::<u8>(group), None);
+			assert!(lock.column_mut::<usize>(group).is_none());
+			for value in lock.column_mut::<f32>(group).unwrap() {
+				*value *= 2.0;
+			}
+		}
+		for (i, &e) in entity_vec.iter().enumerate() {
+			assert_eq!(
+				query.lock(&multi).get_all(entities.valid(e).unwrap()),
+				Some(tl![&mut (i as f32 * 2.0), &i])
+			);
+		}
+	}
+
+	#[test]
+	fn page_config() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let multi_storage = database
+			.tables
+			.create(
+				"multi",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone())
+					.page_config(PageConfig {
+						group_capacity_hint: 64,
+					}),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let transform_to = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let entity = entities.insert();
+		inserter.lock(&mut multi).insert(entity, tl![1]).unwrap();
+		assert!(multi.entities[inserter.group].capacity() >= 64);
+		assert!(multi.fragmentation() > 0.9);
+		multi
+			.lock()
+			.unwrap()
+			.transform::<(), _>(entity, &transform_to, tl![2])
+			.unwrap();
+		// The transform created a third group for `usize` and `u8` together
+		assert_eq!(multi.entities.len(), 3);
+		assert!(multi.entities[2].capacity() >= 64);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn with_component_types() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let tids = [
+			TypeId::of::<u8>(),
+			TypeId::of::<usize>(),
+			TypeId::of::<u16>(),
+		];
+		let multi_storage = database
+			.tables
+			.create(
+				"multi",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone())
+					.with_component_types(&tids),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let capacity = multi.storages.capacity();
+		assert_eq!(multi.registered_component_types(), tids);
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		for i in 0..10 {
+			let e = entities.insert();
+			inserter
+				.lock(&mut multi)
+				.insert(e, tl![i, i as u8])
+				.unwrap();
+		}
+		let mut query = multi.group_query::<TL![&u8]>().unwrap();
+		assert_eq!(query.lock(&multi).iter_with_entities().count(), 10);
+		assert_eq!(multi.storages.capacity(), capacity);
+		assert_eq!(multi.registered_component_types(), tids);
+		assert_eq!(multi.storage_len(TypeId::of::<u16>()), Some(0));
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn component_mask() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let multi_storage = database
+			.tables
+			.create(
+				"multi",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone())
+					.component_masks(true),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let transform_to = multi.group_insert::<TL![&mut isize]>().unwrap();
+		let entity = entities.insert();
+		inserter.lock(&mut multi).insert(entity, tl![1, 2]).unwrap();
+		let mask_of = |multi: &DenseEntityDynamicPagedMultiValueTable<u64>, tids: &[TypeId]| {
+			multi
+				.registered_component_types()
+				.iter()
+				.map(|tid| tids.contains(tid))
+				.collect::<Vec<_>>()
+		};
+		let mask_bits = |multi: &DenseEntityDynamicPagedMultiValueTable<u64>| {
+			let mask = multi.component_mask(entity).unwrap();
+			(0..multi.registered_component_types().len())
+				.map(|idx| mask.get(idx).map_or(false, |bit| *bit))
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(
+			mask_bits(&multi),
+			mask_of(&multi, &[TypeId::of::<usize>(), TypeId::of::<u8>()])
+		);
+		multi
+			.lock()
+			.unwrap()
+			.transform::<TL![u8], _>(entity, &transform_to, tl![3isize])
+			.unwrap();
+		let group = multi.raw_location(entity).unwrap().0;
+		let (group_key, _group_value) = multi.group_inserts.get_index(group).unwrap();
+		assert_eq!(mask_bits(&multi), mask_of(&multi, &group_key.include));
+		assert_eq!(
+			mask_bits(&multi),
+			mask_of(&multi, &[TypeId::of::<usize>(), TypeId::of::<isize>()])
+		);
+		multi.delete(entity).unwrap();
+		assert!(multi.component_mask(entity).is_none());
+	}
+
+	#[test]
+	fn delete_policy() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		for delete_policy in [DeletePolicy::SwapRemove, DeletePolicy::ShiftRemove].iter() {
+			let multi_storage = database
+				.tables
+				.create(
+					&format!("multi_{:?}", delete_policy),
+					DenseEntityDynamicPagedMultiValueTable::<u64>::builder(
+						entities_storage.clone(),
+					)
+					.delete_policy(*delete_policy),
+				)
+				.unwrap();
+			let mut entities = entities_storage.borrow_mut();
+			let mut multi = multi_storage.borrow_mut();
+			let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+			let entity_vec: Vec<_> = entities.extend_iter().take(5).map(|e| e.raw()).collect();
+			for (i, &e) in entity_vec.iter().enumerate() {
+				inserter
+					.lock(&mut multi)
+					.insert(entities.valid(e).unwrap(), tl![i])
+					.unwrap();
+			}
+			multi
+				.delete(entities.valid(entity_vec[1]).unwrap())
+				.unwrap();
+			multi
+				.lock()
+				.unwrap()
+				.delete(entities.valid(entity_vec[2]).unwrap())
+				.unwrap();
+			multi.debug_check_invariants().unwrap();
+			let mut query = multi.group_query::<TL![&usize]>().unwrap();
+			for (i, &e) in entity_vec.iter().enumerate() {
+				let expected = if i == 1 || i == 2 { None } else { Some(i) };
+				assert_eq!(
+					query
+						.lock(&multi)
+						.get_all(entities.valid(e).unwrap())
+						.map(|(value, ())| *value),
+					expected
+				);
+			}
+			let order = match delete_policy {
+				DeletePolicy::SwapRemove => vec![0, 4, 3],
+				DeletePolicy::ShiftRemove => vec![0, 3, 4],
+			};
+			assert_eq!(
+				query.lock(&multi).column::<usize>(inserter.group),
+				Some(order.as_slice())
+			);
+		}
+	}
+
+	#[test]
+	fn registered_component_types() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		assert!(multi.registered_component_types().is_empty());
+		let mut first_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		for i in 0..5 {
+			let e = entities.insert();
+			if i % 2 == 0 {
+				first_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, 1])
+					.unwrap();
+			} else {
+				second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, true])
+					.unwrap();
+			}
+		}
+		assert_eq!(
+			multi.registered_component_types(),
+			vec![
+				TypeId::of::<usize>(),
+				TypeId::of::<u8>(),
+				TypeId::of::<bool>()
+			]
+		);
+		assert_eq!(multi.storage_len(TypeId::of::<usize>()), Some(5));
+		assert_eq!(multi.storage_len(TypeId::of::<u8>()), Some(3));
+		assert_eq!(multi.storage_len(TypeId::of::<bool>()), Some(2));
+		assert_eq!(multi.storage_len(TypeId::of::<isize>()), None);
+	}
+
+	#[test]
+	fn sort_group_by() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let values = [5usize, 3, 9, 1, 7, 3];
+		let raws: Vec<_> = values
+			.iter()
+			.enumerate()
+			.map(|(i, &value)| {
+				let e = entities.insert();
+				inserter
+					.lock(&mut multi)
+					.insert(e, tl![value, i as u8])
+					.unwrap();
+				e.raw()
+			})
+			.collect();
+		multi.sort_group_by::<usize>(inserter.group).unwrap();
+		multi.debug_check_invariants().unwrap();
+		let mut query = multi.group_query::<TL![&usize, &u8]>().unwrap();
+		{
+			let lock = query.lock(&multi);
+			assert_eq!(
+				lock.column::<usize>(inserter.group),
+				Some(&[1, 3, 3, 5, 7, 9][..])
+			);
+			// The other storages moved in lockstep, and equal keys kept their order
+			assert_eq!(
+				lock.column::<u8>(inserter.group),
+				Some(&[3, 1, 5, 0, 4, 2][..])
+			);
+		}
+		for (i, &raw) in raws.iter().enumerate() {
+			let entity = entities.valid(raw).unwrap();
+			assert_eq!(
+				query.lock(&multi).get_all(entity),
+				Some(tl![&values[i], &(i as u8)])
+			);
+		}
+		assert!(matches!(
+			multi.sort_group_by::<bool>(inserter.group),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(_, _))
+		));
+		assert!(matches!(
+			multi.sort_group_by::<usize>(inserter.group + 1),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::GroupDoesNotExist(_))
+		));
+	}
+
+	#[test]
+	fn swap_components() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let raws: Vec<_> = (0..3)
+			.map(|i| {
+				let e = entities.insert();
+				inserter.lock(&mut multi).insert(e, tl![i, i == 0]).unwrap();
+				e.raw()
+			})
+			.collect();
+		let other = entities.insert();
+		other_inserter
+			.lock(&mut multi)
+			.insert(other, tl![9])
+			.unwrap();
+		let other = other.raw();
+		let first = entities.valid(raws[0]).unwrap();
+		let last = entities.valid(raws[2]).unwrap();
+		multi.swap_components(first, last).unwrap();
+		multi.debug_check_invariants().unwrap();
+		assert_eq!(multi.raw_location(first), Some((inserter.group, 2)));
+		assert_eq!(multi.raw_location(last), Some((inserter.group, 0)));
+		let mut query = multi.group_query::<TL![&usize, &bool]>().unwrap();
+		{
+			let lock = query.lock(&multi);
+			assert_eq!(lock.column::<usize>(inserter.group), Some(&[2, 1, 0][..]));
+			assert_eq!(
+				lock.column::<bool>(inserter.group),
+				Some(&[false, false, true][..])
+			);
+		}
+		assert_eq!(query.lock(&multi).get_all(first), Some(tl![&0, &true]));
+		assert!(matches!(
+			multi.swap_components(first, entities.valid(other).unwrap()),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::EntitiesNotInSameGroup(a, b))
+				if a == raws[0] && b == other
+		));
+	}
+
+	#[test]
+	fn get_homogeneous() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let raws: Vec<_> = (0..5)
+			.map(|i| {
+				let e = entities.insert();
+				if i % 2 == 0 {
+					first_inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+				} else {
+					second_inserter
+						.lock(&mut multi)
+						.insert(e, tl![i, true])
+						.unwrap();
+				}
+				e.raw()
+			})
+			.collect();
+		let other = entities.insert();
+		other_inserter
+			.lock(&mut multi)
+			.insert(other, tl![1])
+			.unwrap();
+		let other = other.raw();
+		let mut query = multi.group_query::<TL![&mut usize]>().unwrap();
+		let mut lock = query.lock(&multi);
+		let valid: Vec<_> = raws
+			.iter()
+			.rev()
+			.map(|&e| entities.valid(e).unwrap())
+			.collect();
+		{
+			let mut values = lock.get_homogeneous::<usize>(&valid).unwrap();
+			assert_eq!(values.len(), 5);
+			for value in values.iter_mut() {
+				**value *= 10;
+			}
+		}
+		let values = lock.get_homogeneous::<usize>(&valid).unwrap();
+		assert_eq!(
+			values.into_iter().map(|v| *v).collect::<Vec<_>>(),
+			vec![40, 30, 20, 10, 0]
+		);
+		let duplicated = [valid[0], valid[1], valid[0]];
+		assert!(lock.get_homogeneous::<usize>(&duplicated).is_none());
+		assert!(lock
+			.get_homogeneous::<usize>(&[entities.valid(other).unwrap()])
+			.is_none());
+		assert!(lock.get_homogeneous::<bool>(&valid[..1]).is_none());
+	}
+
+	#[test]
+	fn upsert() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let entity = entities.insert().raw();
+		// A new entity is inserted
+		multi
+			.upsert::<TL![&mut usize, &mut bool]>(entities.valid(entity).unwrap(), tl![1, true])
+			.unwrap();
+		let group = multi
+			.find_archetype::<TL![&mut usize, &mut bool]>()
+			.unwrap();
+		assert_eq!(
+			multi.raw_location(entities.valid(entity).unwrap()),
+			Some((group, 0))
+		);
+		// The same archetype is overwritten in place
+		multi
+			.upsert::<TL![&mut usize, &mut bool]>(entities.valid(entity).unwrap(), tl![2, false])
+			.unwrap();
+		assert_eq!(
+			multi.raw_location(entities.valid(entity).unwrap()),
+			Some((group, 0))
+		);
+		let mut query = multi.group_query::<TL![&usize, &bool]>().unwrap();
+		assert_eq!(
+			query.lock(&multi).get_all(entities.valid(entity).unwrap()),
+			Some(tl![&2, &false])
+		);
+		// Another archetype adds the missing types and overwrites the shared ones
+		multi
+			.upsert::<TL![&mut usize, &mut u8]>(entities.valid(entity).unwrap(), tl![3, 4])
+			.unwrap();
+		multi.debug_check_invariants().unwrap();
+		assert_eq!(
+			multi.component_count(entities.valid(entity).unwrap()),
+			Some(3)
+		);
+		let mut query = multi.group_query::<TL![&usize, &bool, &u8]>().unwrap();
+		assert_eq!(
+			query.lock(&multi).get_all(entities.valid(entity).unwrap()),
+			Some(tl![&3, &false, &4])
+		);
+		assert_eq!(multi.storage_len(TypeId::of::<usize>()), Some(1));
+	}
+
+	#[test]
+	fn entity_from_other_entity_table() {
+		let (mut database, entities_storage, multi_storage) = basic_setup();
+		let other_storage = database
+			.tables
+			.create("other", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut other = other_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		// Both tables hand out the same raw entity, only the handle of `entities` may be used
+		let entity = entities.insert().raw();
+		assert_eq!(other.insert().raw(), entity);
+		let foreign = other.valid(entity).unwrap();
+		let mismatch = SecondaryEntityIndexErrors::EntityTableMismatch(entity);
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		assert!(matches!(
+			inserter.lock(&mut multi).insert(foreign, tl![1]),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::SecondaryIndexError(error))
+				if error == mismatch
+		));
+		assert_eq!(multi.component_count(entities.valid(entity).unwrap()), None);
+		inserter
+			.lock(&mut multi)
+			.insert(entities.valid(entity).unwrap(), tl![2])
+			.unwrap();
+		assert_eq!(multi.component_count(foreign), None);
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		assert_eq!(query.lock(&multi).get_all(foreign), None);
+		assert!(matches!(
+			multi.delete(foreign),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::SecondaryIndexError(error))
+				if error == mismatch
+		));
+		assert_eq!(
+			multi.component_count(entities.valid(entity).unwrap()),
+			Some(1)
+		);
+	}
+
+	#[test]
+	fn component_count() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut null_inserter = multi.group_insert::<TL![]>().unwrap();
+		let mut triple_inserter = multi
+			.group_insert::<TL![&mut usize, &mut u8, &mut bool]>()
+			.unwrap();
+		let null = entities.insert();
+		null_inserter.lock(&mut multi).insert(null, tl![]).unwrap();
+		let null = null.raw();
+		let triple = entities.insert();
+		triple_inserter
+			.lock(&mut multi)
+			.insert(triple, tl![1, 2, true])
+			.unwrap();
+		let triple = triple.raw();
+		let outside = entities.insert().raw();
+		assert_eq!(
+			multi.component_count(entities.valid(null).unwrap()),
+			Some(0)
+		);
+		assert_eq!(
+			multi.component_count(entities.valid(triple).unwrap()),
+			Some(3)
+		);
+		assert_eq!(
+			multi.component_count(entities.valid(outside).unwrap()),
+			None
+		);
+	}
+
+	#[test]
+	fn group_sizes() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let empty_inserter = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		let _query = multi.group_query::<TL![&usize]>().unwrap();
+		assert_eq!(multi.group_sizes().count(), 0);
+		for i in 0..5 {
+			let e = entities.insert();
+			if i < 3 {
+				first_inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+			} else {
+				second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, true])
+					.unwrap();
+			}
+		}
+		assert_eq!(
+			multi.group_sizes().collect::<Vec<_>>(),
+			vec![(first_inserter.group, 3), (second_inserter.group, 2)]
+		);
+		assert!(multi
+			.group_sizes()
+			.all(|(group, _)| group != empty_inserter.group));
+	}
+
+	#[test]
+	fn largest_and_smallest_group() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut small_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut large_inserter = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let mut middle_inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		let _empty_inserter = multi.group_insert::<TL![&mut u16]>().unwrap();
+		assert_eq!(multi.largest_group(), None);
+		assert_eq!(multi.smallest_nonempty_group(), None);
+		for i in 0..9 {
+			let e = entities.insert();
+			match i {
+				0 => small_inserter.lock(&mut multi).insert(e, tl![i]).unwrap(),
+				1..=5 => large_inserter.lock(&mut multi).insert(e, tl![1]).unwrap(),
+				_ => middle_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, true])
+					.unwrap(),
+			}
+		}
+		assert_eq!(multi.largest_group(), Some((large_inserter.group, 5)));
+		assert_eq!(
+			multi.smallest_nonempty_group(),
+			Some((small_inserter.group, 1))
+		);
+	}
+
+	#[test]
+	fn audit() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let raws: Vec<_> = (0..3)
+			.map(|i| {
+				let e = entities.insert();
+				inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+				e.raw()
+			})
+			.collect();
+		assert!(multi.audit().is_empty());
+		// Point the first entity at the slot of the second
+		let (group, index) = multi
+			.raw_location(entities.valid(raws[1]).unwrap())
+			.unwrap();
+		let location = multi.reverse.get_mut(raws[0]).unwrap();
+		location.group = group;
+		location.index = index;
+		assert_eq!(multi.audit(), vec![raws[0]]);
+	}
+
+	#[test]
+	fn stale_location_errors() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let entity = entities.insert();
+		inserter.lock(&mut multi).insert(entity, tl![1]).unwrap();
+		multi.delete(entity).unwrap();
+		// Leave a stale location behind that points past the end of its now empty group
+		*multi.reverse.insert_mut(entity.raw()).unwrap() = ComponentLocations {
+			group: inserter.group,
+			index: 0,
+		};
+		assert_eq!(multi.raw_location(entity), None);
+		assert!(matches!(
+			multi.delete(entity),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::EntityLocationInvalid(e, _, 0)) if e == entity.raw()
+		));
+		multi.reverse.get_mut(entity.raw()).unwrap().group = usize::MAX;
+		assert!(!multi.reverse.get(entity.raw()).unwrap().is_valid());
+		assert!(matches!(
+			multi.delete(entity),
+			Err(
+				DenseEntityDynamicPagedMultiValueTableErrors::EntityLocationInvalid(
+					_,
+					usize::MAX,
+					0
+				)
+			)
+		));
+	}
+
+	#[test]
+	fn remap_component() {
+		#[derive(Debug, PartialEq)]
+		struct Wrapped(u32);
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut single_inserter = multi.group_insert::<TL![&mut u32]>().unwrap();
+		let mut pair_inserter = multi.group_insert::<TL![&mut usize, &mut u32]>().unwrap();
+		let mut wrapped_inserter = multi.group_insert::<TL![&mut Wrapped]>().unwrap();
+		let single = entities.insert().raw();
+		let pair = entities.insert().raw();
+		let existing = entities.insert().raw();
+		let single = entities.valid(single).unwrap();
+		let pair = entities.valid(pair).unwrap();
+		let existing = entities.valid(existing).unwrap();
+		single_inserter
+			.lock(&mut multi)
+			.insert(single, tl![1])
+			.unwrap();
+		pair_inserter
+			.lock(&mut multi)
+			.insert(pair, tl![20, 2])
+			.unwrap();
+		wrapped_inserter
+			.lock(&mut multi)
+			.insert(existing, tl![Wrapped(3)])
+			.unwrap();
+		let pair_group = multi.raw_location(pair).unwrap().0;
+
+		multi.remap_component::<u32, Wrapped>(Wrapped).unwrap();
+		multi.debug_check_invariants().unwrap();
+		let mut old_query = multi.group_query::<TL![&u32]>().unwrap();
+		assert_eq!(old_query.lock(&multi).iter_with_entities().count(), 0);
+		let mut query = multi.group_query::<TL![&Wrapped]>().unwrap();
+		assert_eq!(query.lock(&multi).iter_with_entities().count(), 3);
+		assert_eq!(query.lock(&multi).get_all(single), Some(tl![&Wrapped(1)]));
+		assert_eq!(query.lock(&multi).get_all(existing), Some(tl![&Wrapped(3)]));
+		// The single `u32` archetype merged into the existing `Wrapped` one
+		assert_eq!(
+			multi.raw_location(single).unwrap().0,
+			multi.raw_location(existing).unwrap().0
+		);
+		// The pair archetype was rekeyed in place
+		assert_eq!(multi.raw_location(pair).unwrap().0, pair_group);
+		assert_eq!(
+			multi.find_archetype::<TL![&mut usize, &mut Wrapped]>(),
+			Some(pair_group)
+		);
+		let mut pair_query = multi.group_query::<TL![&usize, &Wrapped]>().unwrap();
+		assert_eq!(
+			pair_query.lock(&multi).get_all(pair),
+			Some(tl![&20, &Wrapped(2)])
+		);
+		let other = entities.insert();
+		multi
+			.group_insert::<TL![&mut usize, &mut Wrapped]>()
+			.unwrap()
+			.lock(&mut multi)
+			.insert(other, tl![21, Wrapped(4)])
+			.unwrap();
+		assert_eq!(multi.raw_location(other).unwrap().0, pair_group);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn coalesce_archetypes() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut forward = multi.group_insert::<TL![&mut u16, &mut u32]>().unwrap();
+		let mut backward = multi.group_insert::<TL![&mut u32, &mut u16]>().unwrap();
+		let raws: Vec<_> = entities.extend_iter().take(3).map(|e| e.raw()).collect();
+		let first = entities.valid(raws[0]).unwrap();
+		let second = entities.valid(raws[1]).unwrap();
+		let third = entities.valid(raws[2]).unwrap();
+		forward.lock(&mut multi).insert(first, tl![1, 10]).unwrap();
+		backward
+			.lock(&mut multi)
+			.insert(second, tl![20, 2])
+			.unwrap();
+		backward.lock(&mut multi).insert(third, tl![30, 3]).unwrap();
+		let group = multi.raw_location(first).unwrap().0;
+		assert_ne!(multi.raw_location(second).unwrap().0, group);
+
+		multi.coalesce_archetypes();
+		multi.debug_check_invariants().unwrap();
+		for &entity in [first, second, third].iter() {
+			assert_eq!(multi.raw_location(entity).unwrap().0, group);
+		}
+		let mut query = multi.group_query::<TL![&u16, &u32]>().unwrap();
+		assert_eq!(query.lock(&multi).iter_with_entities().count(), 3);
+		assert_eq!(query.lock(&multi).get_all(second), Some(tl![&2, &20]));
+		assert_eq!(query.lock(&multi).get_all(third), Some(tl![&3, &30]));
+		multi.coalesce_archetypes();
+		assert_eq!(multi.raw_location(third).unwrap().0, group);
+	}
+
+	#[test]
+	fn transition_cost() {
+		let (_database, _entities_storage, multi_storage) = basic_setup();
+		let mut multi = multi_storage.borrow_mut();
+		multi
+			.group_insert::<TL![&mut u8, &mut u16, &mut u32, &mut u64, &mut i8, &mut i16, &mut i32, &mut i64]>()
+			.unwrap();
+		let group = multi
+			.find_archetype::<TL![&mut u8, &mut u16, &mut u32, &mut u64, &mut i8, &mut i16, &mut i32, &mut i64]>()
+			.unwrap();
+		assert_eq!(multi.transition_cost::<(), TL![&mut usize]>(group), 8);
+		assert_eq!(multi.transition_cost::<TL![u8], TL![&mut usize]>(group), 7);
+		// Overwritten values are replaced rather than moved
+		assert_eq!(multi.transition_cost::<(), TL![&mut u16]>(group), 7);
+	}
+
+	#[test]
+	fn group_query_order() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut bool, &mut usize]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut inserted = Vec::new();
+		for i in 0..4 {
+			let entity = entities.insert();
+			inserted.push(entity.raw());
+			inserter
+				.lock(&mut multi)
+				.insert(entity, tl![i % 2 == 0, i])
+				.unwrap();
+		}
+		let other = entities.insert();
+		other_inserter
+			.lock(&mut multi)
+			.insert(other, tl![9])
+			.unwrap();
+		let mut forward = multi.group_query::<TL![&bool, &usize]>().unwrap();
+		let mut backward = multi.group_query::<TL![&usize, &bool]>().unwrap();
+		assert_eq!(forward.group, backward.group);
+		assert_ne!(
+			multi.group_query::<TL![&usize]>().unwrap().group,
+			forward.group
+		);
+		let mut forward_seen: Vec<_> = forward
+			.lock(&multi)
+			.iter_with_entities()
+			.map(|(entity, (&flag, (&value, ())))| (entity, value, flag))
+			.collect();
+		let mut backward_seen: Vec<_> = backward
+			.lock(&multi)
+			.iter_with_entities()
+			.map(|(entity, (&value, (&flag, ())))| (entity, value, flag))
+			.collect();
+		forward_seen.sort_unstable();
+		backward_seen.sort_unstable();
+		assert_eq!(forward_seen, backward_seen);
+		assert_eq!(
+			forward_seen.iter().map(|s| s.0).collect::<Vec<_>>(),
+			inserted
+		);
+	}
+
+	#[test]
+	fn lock_inserters() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut single_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut pair_inserter = multi.group_insert::<TL![&mut u8, &mut usize]>().unwrap();
+		let single = entities.insert().raw();
+		let pair = entities.insert().raw();
+		let single = entities.valid(single).unwrap();
+		let pair = entities.valid(pair).unwrap();
+		{
+			let mut lock = multi.lock_inserters(&mut [&mut single_inserter, &mut pair_inserter]);
+			lock.insert(&single_inserter, single, tl![1]).unwrap();
+			lock.insert(&pair_inserter, pair, tl![2, 20]).unwrap();
+			assert!(matches!(
+				lock.insert(&single_inserter, pair, tl![3]),
+				Err(DenseEntityDynamicPagedMultiValueTableErrors::SecondaryIndexError(_))
+			));
+		}
+		assert_eq!(multi.raw_location(single).unwrap().0, single_inserter.group);
+		assert_eq!(multi.raw_location(pair).unwrap().0, pair_inserter.group);
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		assert_eq!(query.lock(&multi).get_all(single), Some(tl![&1]));
+		assert_eq!(query.lock(&multi).get_all(pair), Some(tl![&20]));
+		let mut query = multi.group_query::<TL![&u8]>().unwrap();
+		assert_eq!(query.lock(&multi).get_all(pair), Some(tl![&2]));
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn insert_with_defaults() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let entity = entities.insert();
+		multi
+			.insert_with_defaults::<TL![&mut usize, &mut String]>(entity)
+			.unwrap();
+		assert!(multi
+			.insert_with_defaults::<TL![&mut usize, &mut String]>(entity)
+			.is_err());
+		let mut query = multi.group_query::<TL![&usize, &String]>().unwrap();
+		assert_eq!(
+			query.lock(&multi).get_all(entity),
+			Some(tl![&0, &String::new()])
+		);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn zero_sized_marker() {
+		struct Enemy;
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut plain_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut enemy_inserter = multi.group_insert::<TL![&mut usize, &mut Enemy]>().unwrap();
+		assert_ne!(plain_inserter.group, enemy_inserter.group);
+		for i in 0..100 {
+			let e = entities.insert();
+			if i % 2 == 0 {
+				plain_inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+			} else {
+				enemy_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, Enemy])
+					.unwrap();
+			}
+		}
+		let mut query = multi.group_query::<TL![&Enemy, &usize]>().unwrap();
+		assert!(query
+			.lock(&multi)
+			.iter_with_entities()
+			.all(|(_entity, (_enemy, (value, ())))| value % 2 == 1));
+		assert_eq!(query.lock(&multi).iter_with_entities().count(), 50);
+		assert_eq!(multi.storage_len(TypeId::of::<Enemy>()), Some(50));
+		assert_eq!(multi.storage_heap_bytes(TypeId::of::<Enemy>()), Some(0));
+		assert!(multi.storage_heap_bytes(TypeId::of::<usize>()).unwrap() >= 100 * 8);
+		let fragmentation = multi.fragmentation();
+		assert!((0.0..1.0).contains(&fragmentation));
+	}
+
+	#[test]
+	fn raw_location() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let raws: Vec<_> = (0..3)
+			.map(|i| {
+				let e = entities.insert();
+				inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+				e.raw()
+			})
+			.collect();
+		for (i, &raw) in raws.iter().enumerate() {
+			let location = multi.raw_location(entities.valid(raw).unwrap());
+			assert_eq!(location, Some((inserter.group, i)));
+			assert_eq!(multi.entity_at(inserter.group, i), Some(raw));
+		}
+		assert_eq!(multi.entity_at(inserter.group, 3), None);
+		assert_eq!(multi.entity_at(inserter.group + 1, 0), None);
+		let outside = entities.insert();
+		assert_eq!(multi.raw_location(outside), None);
+		// Deleting the first swap-removes the last into its place
+		multi.delete(entities.valid(raws[0]).unwrap()).unwrap();
+		assert_eq!(multi.raw_location(entities.valid(raws[0]).unwrap()), None);
+		assert_eq!(
+			multi.raw_location(entities.valid(raws[2]).unwrap()),
+			Some((inserter.group, 0))
+		);
+		assert_eq!(multi.entity_at(inserter.group, 0), Some(raws[2]));
+		assert_eq!(multi.entity_at(inserter.group, 2), None);
+	}
+
+	#[test]
+	fn transforms() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi
+			.group_insert::<TL![&mut bool, &mut usize, &mut u8]>()
+			.unwrap();
+		let next_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
+		let mut query_before = multi.group_query::<TL![&bool, &usize]>().unwrap();
+		let mut query_after = multi.group_query::<TL![&bool, &isize]>().unwrap();
+		let entity1 = entities.insert();
+		first_inserter
+			.lock(&mut multi)
+			.insert(entity1, tl![true, 42, 16])
+			.unwrap();
+		assert_eq!(
+			query_before.lock(&multi).get::<TL![&usize]>(entity1),
+			Some(tl![&42])
+		);
+		assert_eq!(
+			query_before.lock(&multi).get::<TL![&bool, &usize]>(entity1),
+			Some(tl![&true, &42])
+		);
+		assert_eq!(query_after.lock(&multi).get::<TL![&isize]>(entity1), None);
+		{
+			let mut lock = multi.lock().unwrap();
+			lock.transform::<TL![usize], _>(entity1, &next_inserter, tl![21isize])
+				.unwrap();
+		}
+		assert_eq!(query_before.lock(&multi).get::<TL![&usize]>(entity1), None);
+		assert_eq!(
+			query_after.lock(&multi).get::<TL![&bool, &isize]>(entity1),
+			Some(tl![&true, &21])
+		);
+		{
+			let mut lock = multi.lock().unwrap();
+			lock.transform::<TL![isize], _>(entity1, &first_inserter, tl![false, 42usize, 16])
+				.unwrap();
+		}
+		assert_eq!(
+			query_before.lock(&multi).get::<TL![&bool, &usize]>(entity1),
 			Some(tl![&false, &42])
 		);
 		assert_eq!(query_after.lock(&multi).get::<TL![&isize]>(entity1), None);
@@ -1515,6 +5890,160 @@ mod tests {
 			query_before.lock(&multi).get_all(entity1),
 			Some(tl![&false, &42])
 		);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn transform_many() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut bool_inserter = multi.group_insert::<TL![&mut bool, &mut usize]>().unwrap();
+		let mut usize_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let add_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
+		let mut query = multi.group_query::<TL![&usize, &isize]>().unwrap();
+		let entity_vec: Vec<_> = (0..10).map(|_| entities.insert().raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			if i % 2 == 0 {
+				bool_inserter
+					.lock(&mut multi)
+					.insert(e, tl![true, i])
+					.unwrap();
+			} else {
+				usize_inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+			}
+		}
+		// Out of the middle of both groups, so swap removes have to fix up the others
+		let transforming: Vec<_> = entity_vec
+			.iter()
+			.copied()
+			.enumerate()
+			.filter(|(i, _e)| i % 3 != 0)
+			.map(|(_i, e)| e)
+			.collect();
+		multi
+			.lock()
+			.unwrap()
+			.transform_many::<(), _, _>(
+				&add_inserter,
+				transforming
+					.iter()
+					.map(|&e| (entities.valid(e).unwrap(), tl![-(e as isize)])),
+			)
+			.unwrap();
+		multi.debug_check_invariants().unwrap();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			if transforming.contains(&e.raw()) {
+				assert_eq!(
+					query.lock(&multi).get_all(e),
+					Some(tl![&i, &-(e.raw() as isize)])
+				);
+			} else {
+				assert_eq!(query.lock(&multi).get_all(e), None);
+			}
+		}
+	}
+
+	#[test]
+	fn transform_batch() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let add_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
+		let mut query = multi.group_query::<TL![&usize, &isize]>().unwrap();
+		let mut old_query = multi.group_query::<TL![&u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(120).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			inserter.lock(&mut multi).insert(e, tl![i, 1]).unwrap();
+		}
+		// Spread through the group, so swap removes have to fix up the entities that stay
+		let (staying, moving): (Vec<_>, Vec<_>) = entity_vec
+			.iter()
+			.copied()
+			.enumerate()
+			.partition(|(i, _e)| i % 6 == 0);
+		assert_eq!(moving.len(), 100);
+		let moving_entities: Vec<_> = moving
+			.iter()
+			.map(|&(_i, e)| entities.valid(e).unwrap())
+			.collect();
+		assert!(matches!(
+			multi.lock().unwrap().transform_batch::<TL![u8], _>(
+				&add_inserter,
+				&moving_entities,
+				tl![vec![0; 99]]
+			),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::IteratorsNotAllSameLength)
+		));
+		let adds = moving.iter().map(|&(i, _e)| -(i as isize)).collect();
+		multi
+			.lock()
+			.unwrap()
+			.transform_batch::<TL![u8], _>(&add_inserter, &moving_entities, tl![adds])
+			.unwrap();
+		multi.debug_check_invariants().unwrap();
+		for &(i, e) in moving.iter() {
+			let e = entities.valid(e).unwrap();
+			assert_eq!(query.lock(&multi).get_all(e), Some(tl![&i, &-(i as isize)]));
+			assert_eq!(old_query.lock(&multi).get_all(e), None);
+		}
+		for &(_i, e) in staying.iter() {
+			let e = entities.valid(e).unwrap();
+			assert_eq!(query.lock(&multi).get_all(e), None);
+			assert_eq!(old_query.lock(&multi).get_all(e), Some(tl![&1]));
+		}
+		let mixed = [moving_entities[0], entities.valid(staying[0].1).unwrap()];
+		assert!(matches!(
+			multi
+				.lock()
+				.unwrap()
+				.transform_batch::<(), _>(&add_inserter, &mixed, tl![vec![0; 2]]),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::EntitiesNotInSameGroup(_, _))
+		));
+	}
+
+	#[test]
+	fn transform_adding_types_already_present() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let next_inserter = multi.group_insert::<TL![&mut u8, &mut isize]>().unwrap();
+		let mut query = multi.group_query::<TL![&usize, &u8, &isize]>().unwrap();
+		let mut query_first = multi.group_query::<TL![&usize, &u8]>().unwrap();
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
+		{
+			let mut lock = first_inserter.lock(&mut multi);
+			lock.insert(entities.valid(entity1).unwrap(), tl![1, 1])
+				.unwrap();
+			lock.insert(entities.valid(entity2).unwrap(), tl![2, 2])
+				.unwrap();
+		}
+		{
+			let mut lock = multi.lock().unwrap();
+			lock.transform::<TL![], _>(
+				entities.valid(entity1).unwrap(),
+				&next_inserter,
+				tl![16, -1],
+			)
+			.unwrap();
+		}
+		assert_eq!(
+			query.lock(&multi).get_all(entities.valid(entity1).unwrap()),
+			Some(tl![&1, &16, &-1])
+		);
+		assert_eq!(
+			query_first
+				.lock(&multi)
+				.get_all(entities.valid(entity2).unwrap()),
+			Some(tl![&2, &2])
+		);
+		multi.debug_check_invariants().unwrap();
 	}
 
 	#[test]
@@ -1551,9 +6080,8 @@ mod tests {
 		let mut inserter = multi.group_insert::<Type8>().unwrap();
 		{
 			let mut lock = inserter.lock(&mut multi);
-			for &e in entity_vec.iter() {
-				lock.insert(entities.valid(e).unwrap(), type8_new(e))
-					.unwrap();
+			for e in entities.validate_slice(&entity_vec).unwrap() {
+				lock.insert(e, type8_new(e.raw())).unwrap();
 			}
 		}
 		let transform_to = multi.group_insert::<TL![&mut P]>().unwrap();
@@ -1620,5 +6148,754 @@ mod tests {
 		for (mut i, e) in entity_vec.iter().enumerate() {
 			assert_eq!(singles.lock(&mut multi).get_all(*e).unwrap(), tl![&mut i]);
 		}
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn extend_slices_mismatched_lengths() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(10).collect();
+		assert!(matches!(
+			inserter
+				.lock(&mut multi)
+				.extend_slices(&entity_vec, tl![(0..10).collect(), vec![0; 9]]),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::IteratorsNotAllSameLength)
+		));
+		assert!(entity_vec.iter().all(|&e| multi.raw_location(e).is_none()));
+		inserter
+			.lock(&mut multi)
+			.extend_slices(&entity_vec, tl![(0..10).collect(), vec![0; 10]])
+			.unwrap();
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn query_all() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
+		let entity1 = entities.valid(entity1).unwrap();
+		let entity2 = entities.valid(entity2).unwrap();
+		inserter
+			.lock(&mut multi)
+			.insert(entity1, tl![42, 16])
+			.unwrap();
+		let lock = multi.query_all();
+		assert_eq!(
+			lock.get_any(entity1, TypeId::of::<usize>())
+				.and_then(|v| v.downcast_ref::<usize>()),
+			Some(&42)
+		);
+		assert_eq!(
+			lock.get_any(entity1, TypeId::of::<u8>())
+				.and_then(|v| v.downcast_ref::<u8>()),
+			Some(&16)
+		);
+		assert!(lock.get_any(entity1, TypeId::of::<isize>()).is_none());
+		assert!(lock.get_any(entity2, TypeId::of::<usize>()).is_none());
+	}
+
+	#[test]
+	fn for_each_component() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
+		let entity1 = entities.valid(entity1).unwrap();
+		let entity2 = entities.valid(entity2).unwrap();
+		inserter
+			.lock(&mut multi)
+			.insert(entity1, tl![42, 16])
+			.unwrap();
+		let mut seen = Vec::new();
+		multi.for_each_component(entity1, |tid, value| {
+			if let Some(&value) = value.downcast_ref::<usize>() {
+				seen.push((tid, value));
+			} else if let Some(&value) = value.downcast_ref::<u8>() {
+				seen.push((tid, value as usize));
+			} else {
+				panic!("unexpected component type");
+			}
+		});
+		seen.sort_by_key(|&(_tid, value)| value);
+		assert_eq!(
+			seen,
+			vec![(TypeId::of::<u8>(), 16), (TypeId::of::<usize>(), 42)]
+		);
+		multi.for_each_component(entity2, |_tid, _value| panic!("entity2 has no components"));
+	}
+
+	#[test]
+	fn retain() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(20).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			match i % 3 {
+				0 => first_inserter.lock(&mut multi).insert(e, tl![i]).unwrap(),
+				1 => second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, 1])
+					.unwrap(),
+				_ => other_inserter.lock(&mut multi).insert(e, tl![1]).unwrap(),
+			}
+		}
+		multi.retain::<TL![&usize]>(|_entity, (value, ())| *value % 2 == 0);
+		multi.debug_check_invariants().unwrap();
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		let mut others = multi.group_query::<TL![&u8]>().unwrap();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			if i % 3 == 2 {
+				assert!(others.lock(&multi).get_all(e).is_some());
+			} else if i % 2 == 0 {
+				assert_eq!(query.lock(&multi).get_all(e), Some(tl![&i]));
+			} else {
+				assert_eq!(query.lock(&multi).get_all(e), None);
+			}
+		}
+	}
+
+	#[test]
+	fn iter_sorted_by() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let values = [7usize, 3, 9, 0, 5, 1, 8, 2, 6, 4];
+		let entity_vec: Vec<_> = entities.extend_iter().take(10).map(|e| e.raw()).collect();
+		for (&e, &value) in entity_vec.iter().zip(values.iter()) {
+			let e = entities.valid(e).unwrap();
+			if value % 2 == 0 {
+				first_inserter
+					.lock(&mut multi)
+					.insert(e, tl![value])
+					.unwrap();
+			} else {
+				second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![value, 1])
+					.unwrap();
+			}
+		}
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		let mut lock = query.lock(&multi);
+		let sorted: Vec<_> = lock
+			.iter_sorted_by(|(value, ())| **value)
+			.map(|(entity, (value, ()))| (entity.raw(), *value))
+			.collect();
+		assert_eq!(sorted.len(), values.len());
+		for (i, &(entity, value)) in sorted.iter().enumerate() {
+			assert_eq!(value, i);
+			let position = values.iter().position(|&v| v == i).unwrap();
+			assert_eq!(entity, entity_vec[position]);
+		}
+	}
+
+	#[test]
+	fn query_entity_range() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(30).map(|e| e.raw()).collect();
+		for &e in entity_vec.iter() {
+			let index = e.idx();
+			let e = entities.valid(e).unwrap();
+			if index % 2 == 0 {
+				first_inserter
+					.lock(&mut multi)
+					.insert(e, tl![index])
+					.unwrap();
+			} else {
+				second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![index, 1])
+					.unwrap();
+			}
+		}
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		let mut lock = query.lock(&multi);
+		let mut seen: Vec<usize> = lock
+			.query_entity_range(10..20)
+			.map(|(entity, (value, ()))| {
+				assert_eq!(*value, entity.idx());
+				*value
+			})
+			.collect();
+		seen.sort_unstable();
+		assert_eq!(seen, (10..20).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn iter_with_entities() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(6).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			match i % 3 {
+				0 => first_inserter.lock(&mut multi).insert(e, tl![i]).unwrap(),
+				1 => second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, 1])
+					.unwrap(),
+				_ => other_inserter.lock(&mut multi).insert(e, tl![1]).unwrap(),
+			}
+		}
+		let mut query = multi.group_query::<TL![&mut usize]>().unwrap();
+		{
+			let mut lock = query.lock(&multi);
+			let mut seen = 0;
+			for (entity, (value, ())) in lock.iter_with_entities() {
+				*value += entity as usize * 100;
+				seen += 1;
+			}
+			assert_eq!(seen, 4);
+		}
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let expected = if i % 3 == 2 {
+				None
+			} else {
+				Some(i + e as usize * 100)
+			};
+			assert_eq!(
+				query
+					.lock(&multi)
+					.get_all(entities.valid(e).unwrap())
+					.map(|(value, ())| *value),
+				expected
+			);
+		}
+	}
+
+	#[test]
+	fn group_soa() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut other = multi.group_insert::<TL![&mut usize]>().unwrap();
+		for i in 0..10 {
+			let e = entities.insert();
+			inserter
+				.lock(&mut multi)
+				.insert(e, tl![i, i as u8])
+				.unwrap();
+		}
+		let e = entities.insert();
+		other.lock(&mut multi).insert(e, tl![100]).unwrap();
+		let group = inserter.group;
+		let count = multi.entities[group].len();
+		let mut query = multi.group_query::<TL![&mut usize, &u8]>().unwrap();
+		{
+			let mut lock = query.lock(&multi);
+			let (values, (bytes, ())) = lock.group_soa(group).unwrap();
+			assert_eq!(count, 10);
+			assert_eq!(values.len(), count);
+			assert_eq!(bytes.len(), count);
+			for (value, byte) in values.iter_mut().zip(bytes.iter()) {
+				assert_eq!(*value as u8, *byte);
+				*value += 1;
+			}
+		}
+		let mut lock = query.lock(&multi);
+		assert!(lock.group_soa(other.group).is_none());
+	}
+
+	#[test]
+	fn iter_groups() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut second_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut u8]>().unwrap();
+		for i in 0..6 {
+			let e = entities.insert();
+			match i % 3 {
+				0 => first_inserter.lock(&mut multi).insert(e, tl![i]).unwrap(),
+				1 => second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, 1])
+					.unwrap(),
+				_ => other_inserter.lock(&mut multi).insert(e, tl![1]).unwrap(),
+			}
+		}
+		let first_group = multi.find_archetype::<TL![&mut usize]>().unwrap();
+		let second_group = multi.find_archetype::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut query = multi.group_query::<TL![&mut usize]>().unwrap();
+		let mut lock = query.lock(&multi);
+		let mut seen = 0;
+		for (group, include, (values, ())) in lock.iter_groups() {
+			if group == first_group {
+				assert_eq!(include, &*archetype_key_of::<TL![&mut usize]>());
+				assert_eq!(values, &[0, 3]);
+			} else {
+				assert_eq!(group, second_group);
+				assert_eq!(include, &*archetype_key_of::<TL![&mut usize, &mut u8]>());
+				assert_eq!(values, &[1, 4]);
+			}
+			values[0] += 10;
+			seen += 1;
+		}
+		assert_eq!(seen, 2);
+	}
+
+	#[test]
+	fn delete_all_with() {
+		struct Projectile;
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi
+			.group_insert::<TL![&mut Projectile, &mut usize]>()
+			.unwrap();
+		let mut second_inserter = multi
+			.group_insert::<TL![&mut Projectile, &mut u8]>()
+			.unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(9).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			match i % 3 {
+				0 => first_inserter
+					.lock(&mut multi)
+					.insert(e, tl![Projectile, i])
+					.unwrap(),
+				1 => second_inserter
+					.lock(&mut multi)
+					.insert(e, tl![Projectile, 1])
+					.unwrap(),
+				_ => other_inserter.lock(&mut multi).insert(e, tl![i]).unwrap(),
+			}
+		}
+		assert_eq!(multi.delete_all_with::<Projectile>(), 6);
+		assert_eq!(multi.delete_all_with::<Projectile>(), 0);
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let expected = if i % 3 == 2 { Some(i) } else { None };
+			assert_eq!(
+				query
+					.lock(&multi)
+					.get_all(entities.valid(e).unwrap())
+					.map(|(value, ())| *value),
+				expected
+			);
+		}
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	fn get_or_create_storage_reuses_identical_type() {
+		let (_database, _entities_storage, multi_storage) = basic_setup();
+		let mut multi = multi_storage.borrow_mut();
+		let first = DensePagedData::<usize>::get_or_create(&mut multi.storages);
+		let second = DensePagedData::<usize>::get_or_create(&mut multi.storages);
+		let other = DensePagedData::<u8>::get_or_create(&mut multi.storages);
+		assert!(Rc::ptr_eq(&first, &second));
+		assert_eq!(first.borrow().idx, second.borrow().idx);
+		assert_ne!(first.borrow().idx, other.borrow().idx);
+		assert_eq!(multi.storages.len(), 2);
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "does not hold a DensePagedData<usize>")]
+	fn get_or_create_storage_type_collision() {
+		let (_database, _entities_storage, multi_storage) = basic_setup();
+		let mut multi = multi_storage.borrow_mut();
+		// As if `u8` had the same `TypeId` as `usize`
+		let colliding = DensePagedData::<u8>::new(0);
+		multi.storages.insert(TypeId::of::<usize>(), colliding);
+		DensePagedData::<usize>::get_or_create(&mut multi.storages);
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "does not hold values of usize")]
+	fn push_prelocked_mismatched_storage_index() {
+		let (_database, _entities_storage, multi_storage) = basic_setup();
+		let mut multi = multi_storage.borrow_mut();
+		multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let group = multi.find_archetype::<TL![&mut usize, &mut u8]>().unwrap();
+		let usize_idx = multi.storages.get_full(&TypeId::of::<usize>()).unwrap().0;
+		let u8_idx = multi.storages.get_full(&TypeId::of::<u8>()).unwrap().0;
+		let mut lock = multi.lock().unwrap();
+		// Swapped storage indexes, as a corrupted group key would have
+		<TL![&mut usize, &mut u8]>::push_prelocked(
+			&mut lock.storages,
+			&[u8_idx, usize_idx],
+			group,
+			tl![1, 2],
+		);
+	}
+
+	#[test]
+	fn drain_all() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(3).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			inserter
+				.lock(&mut multi)
+				.insert(entities.valid(e).unwrap(), tl![i, i as u8 * 10])
+				.unwrap();
+		}
+		assert!(matches!(
+			multi.drain_all::<TL![&mut usize]>(),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::StorageNotCoveredByTypes(_, _))
+		));
+		assert!(matches!(
+			multi.drain_all::<TL![&mut usize, &mut u8, &mut bool]>(),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(_, _))
+		));
+		let drained: Vec<_> = multi
+			.drain_all::<TL![&mut usize, &mut u8]>()
+			.unwrap()
+			.collect();
+		assert_eq!(
+			drained,
+			vec![
+				(entity_vec[0], tl![0, 0]),
+				(entity_vec[1], tl![1, 10]),
+				(entity_vec[2], tl![2, 20]),
+			]
+		);
+		let mut query = multi.group_query::<TL![&usize]>().unwrap();
+		for &e in entity_vec.iter() {
+			assert_eq!(query.lock(&multi).get_all(entities.valid(e).unwrap()), None);
+		}
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn invariants_after_transforms_and_deletes() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut first_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let next_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(8).map(|e| e.raw()).collect();
+		{
+			let mut lock = first_inserter.lock(&mut multi);
+			for (i, &e) in entity_vec.iter().enumerate() {
+				lock.insert(entities.valid(e).unwrap(), tl![i, i as u8])
+					.unwrap();
+			}
+		}
+		multi.debug_check_invariants().unwrap();
+		{
+			let mut lock = multi.lock().unwrap();
+			for &e in entity_vec.iter().step_by(2) {
+				lock.transform::<TL![usize], _>(
+					entities.valid(e).unwrap(),
+					&next_inserter,
+					tl![-1],
+				)
+				.unwrap();
+			}
+			lock.delete(entities.valid(entity_vec[1]).unwrap()).unwrap();
+			lock.delete(entities.valid(entity_vec[2]).unwrap()).unwrap();
+		}
+		multi.debug_check_invariants().unwrap();
+		multi
+			.delete(entities.valid(entity_vec[7]).unwrap())
+			.unwrap();
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn on_archetype_created() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let created = Rc::new(RefCell::new(Vec::new()));
+		let recorder = created.clone();
+		multi.on_archetype_created(move |group, include| {
+			recorder.borrow_mut().push((group, include.to_vec()))
+		});
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let add_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
+		// Neither queries nor repeated inserters create archetypes
+		let _query = multi.group_query::<TL![&usize]>().unwrap();
+		let _inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		assert_eq!(created.borrow().len(), 2);
+		let entity = entities.insert().raw();
+		let entity = entities.valid(entity).unwrap();
+		inserter.lock(&mut multi).insert(entity, tl![1, 2]).unwrap();
+		multi
+			.lock()
+			.unwrap()
+			.transform::<TL![u8], _>(entity, &add_inserter, tl![3isize])
+			.unwrap();
+		let created = created.borrow();
+		assert_eq!(created.len(), 3);
+		let expected = [
+			archetype_key_of::<TL![&mut usize, &mut u8]>(),
+			archetype_key_of::<TL![&mut isize]>(),
+			archetype_key_of::<TL![&mut usize, &mut isize]>(),
+		];
+		for (i, ((group, include), expected)) in created.iter().zip(expected.iter()).enumerate() {
+			assert_eq!(*group, i);
+			let mut include = include.clone();
+			let mut expected = expected.to_vec();
+			include.sort();
+			expected.sort();
+			assert_eq!(include, expected);
+		}
+	}
+
+	#[test]
+	fn archetype_created_once() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let created = Rc::new(RefCell::new(Vec::new()));
+		let recorder = created.clone();
+		multi.on_archetype_created(move |group, _include| recorder.borrow_mut().push(group));
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let add_inserter = multi.group_insert::<TL![&mut isize]>().unwrap();
+		// The same archetype again
+		let _inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		assert_eq!(*created.borrow(), vec![0, 1]);
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
+		for (&entity, &value) in [entity1, entity2].iter().zip([1, 2].iter()) {
+			inserter
+				.lock(&mut multi)
+				.insert(entities.valid(entity).unwrap(), tl![value, 0])
+				.unwrap();
+		}
+		// The first transform creates the archetype, the second reuses it
+		for &entity in [entity1, entity2].iter() {
+			multi
+				.lock()
+				.unwrap()
+				.transform::<TL![u8], _>(
+					entities.valid(entity).unwrap(),
+					&add_inserter,
+					tl![5isize],
+				)
+				.unwrap();
+		}
+		assert_eq!(*created.borrow(), vec![0, 1, 2]);
+		multi.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn query_stats() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut usize_inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut u8_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut isize_inserter = multi.group_insert::<TL![&mut usize, &mut isize]>().unwrap();
+		let _empty_inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(30).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			match i % 3 {
+				0 => usize_inserter.lock(&mut multi).insert(e, tl![i]).unwrap(),
+				1 => u8_inserter.lock(&mut multi).insert(e, tl![i, 1]).unwrap(),
+				_ => isize_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, 2])
+					.unwrap(),
+			}
+		}
+		assert_eq!(
+			multi.query_stats::<TL![&usize]>(),
+			QueryStats {
+				matching_groups: 4,
+				total_entities: 30,
+				skipped_empty_groups: 1,
+			}
+		);
+		assert_eq!(
+			multi.query_stats::<TL![&usize, &u8]>(),
+			QueryStats {
+				matching_groups: 1,
+				total_entities: 10,
+				skipped_empty_groups: 0,
+			}
+		);
+		assert_eq!(
+			multi.query_stats::<TL![&u8, &isize]>(),
+			QueryStats::default()
+		);
+	}
+
+	#[test]
+	fn overlapping_mutable_locks() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut usize, &mut isize]>().unwrap();
+		let mut query = multi.group_query::<TL![&mut usize, &u8]>().unwrap();
+		let mut overlapping = multi.group_query::<TL![&mut usize, &isize]>().unwrap();
+		let mut reading = multi.group_query::<TL![&usize]>().unwrap();
+		let mut other_reading = multi.group_query::<TL![&usize]>().unwrap();
+		let entity = entities.insert().raw();
+		let other_entity = entities.insert().raw();
+		let entity = entities.valid(entity).unwrap();
+		let other_entity = entities.valid(other_entity).unwrap();
+		inserter.lock(&mut multi).insert(entity, tl![1, 2]).unwrap();
+		other_inserter
+			.lock(&mut multi)
+			.insert(other_entity, tl![3, 4])
+			.unwrap();
+		{
+			let _locked = query.lock(&multi);
+			assert!(overlapping.try_lock(&multi).is_none());
+			assert!(reading.try_lock(&multi).is_none());
+		}
+		{
+			let _locked = reading.lock(&multi);
+			assert!(other_reading.try_lock(&multi).is_some());
+			assert!(overlapping.try_lock(&multi).is_none());
+		}
+		assert!(overlapping.try_lock(&multi).is_some());
+	}
+
+	#[test]
+	fn get_two_mut() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(3).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			inserter
+				.lock(&mut multi)
+				.insert(e, tl![i, i as u8])
+				.unwrap();
+		}
+		let entity = entities.valid(entity_vec[1]).unwrap();
+		let mut lock = multi.lock().unwrap();
+		{
+			let (a, b) = lock.get_two_mut::<u8, usize>(entity).unwrap();
+			*a += 10;
+			*b += 20;
+		}
+		let (a, b) = lock.get_two_mut::<usize, u8>(entity).unwrap();
+		assert_eq!((*a, *b), (21, 11));
+		assert!(matches!(
+			lock.get_two_mut::<usize, usize>(entity),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::ComponentTypeRequestedTwice(_))
+		));
+		assert!(matches!(
+			lock.get_two_mut::<usize, isize>(entity),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::StorageDoesNotExistInGroup(_, _))
+		));
+	}
+
+	#[test]
+	fn write_archetype() {
+		let (mut database, entities_storage, multi_storage) = basic_setup();
+		let restored_storage = database
+			.tables
+			.create(
+				"restored",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut restored = restored_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut u64, &mut u32]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(10).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			inserter
+				.lock(&mut multi)
+				.insert(e, tl![i as u64 * 1000, i as u32])
+				.unwrap();
+		}
+		let group = multi.find_archetype::<TL![&mut u64, &mut u32]>().unwrap();
+		let mut buffer = Vec::new();
+		assert!(matches!(
+			multi.write_archetype::<TL![&mut u64], _>(group, &mut buffer),
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::StorageNotCoveredByTypes(_, _))
+		));
+		assert!(buffer.is_empty());
+		multi
+			.write_archetype::<TL![&mut u32, &mut u64], _>(group, &mut buffer)
+			.unwrap();
+		assert!(matches!(
+			unsafe { restored.read_archetype::<TL![&mut u64, &mut u32], _>(&mut &buffer[..]) },
+			Err(DenseEntityDynamicPagedMultiValueTableErrors::IoError(_))
+		));
+		unsafe {
+			restored
+				.read_archetype::<TL![&mut u32, &mut u64], _>(&mut &buffer[..])
+				.unwrap();
+		}
+		assert!(multi.entities_eq(&restored));
+		assert!(multi.component_eq::<u64>(&restored));
+		assert!(multi.component_eq::<u32>(&restored));
+		restored.debug_check_invariants().unwrap();
+	}
+
+	#[test]
+	fn iter_chunks() {
+		let (_database, entities_storage, multi_storage) = basic_setup();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi.group_insert::<TL![&mut usize]>().unwrap();
+		let mut other_inserter = multi.group_insert::<TL![&mut usize, &mut u8]>().unwrap();
+		let _empty_inserter = multi.group_insert::<TL![&mut usize, &mut bool]>().unwrap();
+		let mut query = multi.group_query::<TL![&mut usize]>().unwrap();
+		let entity_vec: Vec<_> = entities.extend_iter().take(13).map(|e| e.raw()).collect();
+		for (i, &e) in entity_vec.iter().enumerate() {
+			let e = entities.valid(e).unwrap();
+			if i < 10 {
+				inserter.lock(&mut multi).insert(e, tl![i]).unwrap();
+			} else {
+				other_inserter
+					.lock(&mut multi)
+					.insert(e, tl![i, 0])
+					.unwrap();
+			}
+		}
+		let mut lengths = Vec::new();
+		for (values, ()) in query.lock(&multi).iter_chunks(4) {
+			lengths.push(values.len());
+			for value in values.iter_mut() {
+				*value *= 2;
+			}
+		}
+		assert_eq!(lengths, [4, 4, 2, 3]);
+		let mut values: Vec<usize> = query
+			.lock(&multi)
+			.iter_chunks(4)
+			.flat_map(|(values, ())| values.to_vec())
+			.collect();
+		values.sort_unstable();
+		assert_eq!(values, (0..13).map(|i| i * 2).collect::<Vec<_>>());
 	}
 }
@@ -9,12 +9,98 @@ use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::rc::{Rc, Weak};
 
+/// Why a `merge_from` refused to merge, nothing is moved in either case.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DenseEntityValueTableMergeErrors {
+	/// The `entity_table` passed in is not the `EntityTable` this table is built on.
+	EntityTableMismatch,
+	/// The other table is built on a different `EntityTable` than this table.
+	OtherTableMismatch,
+}
+
+impl std::error::Error for DenseEntityValueTableMergeErrors {}
+
+impl std::fmt::Display for DenseEntityValueTableMergeErrors {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+		use DenseEntityValueTableMergeErrors::*;
+		match self {
+			EntityTableMismatch => write!(f, "EntityTable is not the one of this table"),
+			OtherTableMismatch => write!(f, "Other table is built on a different EntityTable"),
+		}
+	}
+}
+
+/// Counts of what happened to each entry of the other table during a `merge_from`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct DenseEntityValueTableMergeCounts {
+	/// Entries that did not exist in this table and were inserted.
+	pub merged: usize,
+	/// Entries that already existed in this table and were resolved with `on_conflict`.
+	pub overwritten: usize,
+	/// Entries whose entity is no longer valid in the entity table and were dropped.
+	pub skipped: usize,
+}
+
+/// A view into the value of a single entity of a `DenseEntityValueTable`, which may be vacant.
+pub struct DenseEntityValueTableEntry<'a, EntityType: Entity, ValueType: 'static> {
+	table: &'a mut DenseEntityValueTable<EntityType, ValueType>,
+	entity: EntityType,
+	slot: EntrySlot,
+}
+
+enum EntrySlot {
+	Occupied(usize),
+	/// Holds a value of an older generation of the entity, which is replaced on insert.
+	Stale(usize),
+	Vacant,
+}
+
+impl<'a, EntityType: Entity, ValueType: 'static>
+	DenseEntityValueTableEntry<'a, EntityType, ValueType>
+{
+	pub fn or_insert(self, default: ValueType) -> &'a mut ValueType {
+		self.or_insert_with(|| default)
+	}
+
+	pub fn or_insert_with(self, f: impl FnOnce() -> ValueType) -> &'a mut ValueType {
+		let table = self.table;
+		let location = match self.slot {
+			EntrySlot::Occupied(location) => location,
+			EntrySlot::Stale(location) => {
+				table.entities[location] = self.entity;
+				table.values[location] = f();
+				location
+			}
+			EntrySlot::Vacant => {
+				let location = table
+					.reverse
+					.insert_mut(self.entity)
+					.expect("reverse mapping is in invalid state with DenseEntityValueTable");
+				*location = table.entities.len();
+				table.entities.push(self.entity);
+				table.values.push(f());
+				table.values.len() - 1
+			}
+		};
+		&mut table.values[location]
+	}
+
+	/// Calls `f` with the value if the entry is occupied.
+	pub fn and_modify(self, f: impl FnOnce(&mut ValueType)) -> Self {
+		if let EntrySlot::Occupied(location) = self.slot {
+			f(&mut self.table.values[location]);
+		}
+		self
+	}
+}
+
 pub struct DenseEntityValueTable<EntityType: Entity, ValueType: 'static> {
 	this: Weak<RefCell<Self>>,
 	database_id: DatabaseId,
 	table_name: SmolStr,
 	table_id: TableId,
-	//entity_table: EntityTable<EntityType>,
+	entity_table: Weak<RefCell<EntityTable<EntityType>>>,
+	world_id: u32,
 	reverse: SecondaryEntityIndex<EntityType, usize>,
 	entities: Vec<EntityType>,
 	values: Vec<ValueType>,
@@ -59,13 +145,38 @@ impl<EntityType: Entity, ValueType: 'static> DenseEntityValueTable<EntityType, V
 		entity: ValidEntity<EntityType>,
 		value: ValueType,
 	) -> Result<(), SecondaryEntityIndexErrors<EntityType>> {
-		let location = self.reverse.insert_mut(entity.raw())?;
+		let entity = entity.raw_in(self.world_id)?;
+		let location = self.reverse.insert_mut(entity)?;
 		*location = self.entities.len();
-		self.entities.push(entity.raw());
+		self.entities.push(entity);
 		self.values.push(value);
 		Ok(())
 	}
 
+	/// Returns the entry of `entity` for in-place access, a value left by an older generation of
+	/// the entity counts as vacant.
+	///
+	/// Errors with `EntityTableMismatch` if `entity` is from a different `EntityTable`.
+	pub fn entry(
+		&mut self,
+		entity: ValidEntity<EntityType>,
+	) -> Result<
+		DenseEntityValueTableEntry<EntityType, ValueType>,
+		SecondaryEntityIndexErrors<EntityType>,
+	> {
+		let entity = entity.raw_in(self.world_id)?;
+		let slot = match self.reverse.get(entity) {
+			Ok(&location) if self.entities[location] == entity => EntrySlot::Occupied(location),
+			Ok(&location) => EntrySlot::Stale(location),
+			Err(_) => EntrySlot::Vacant,
+		};
+		Ok(DenseEntityValueTableEntry {
+			table: self,
+			entity,
+			slot,
+		})
+	}
+
 	pub fn delete(
 		&mut self,
 		entity: EntityType,
@@ -87,6 +198,56 @@ impl<EntityType: Entity, ValueType: 'static> DenseEntityValueTable<EntityType, V
 		}
 		Ok(())
 	}
+
+	/// Moves all values out of `other` into this table, leaving `other` empty.
+	///
+	/// Entities that are no longer valid in `entity_table` (deleted or of an older generation) are
+	/// skipped, entities that already have a value in this table are resolved by calling
+	/// `on_conflict` with the existing value and the value from `other`.
+	///
+	/// Errors without moving anything if `entity_table` or `other` belong to a different
+	/// `EntityTable` than this table.
+	pub fn merge_from(
+		&mut self,
+		entity_table: &EntityTable<EntityType>,
+		other: &mut Self,
+		on_conflict: impl Fn(&mut ValueType, ValueType),
+	) -> Result<DenseEntityValueTableMergeCounts, DenseEntityValueTableMergeErrors> {
+		if entity_table.world_id() != self.world_id {
+			return Err(DenseEntityValueTableMergeErrors::EntityTableMismatch);
+		}
+		if other.world_id != self.world_id {
+			return Err(DenseEntityValueTableMergeErrors::OtherTableMismatch);
+		}
+		let mut counts = DenseEntityValueTableMergeCounts::default();
+		other.reverse = SecondaryEntityIndex::new(usize::MAX);
+		let entities = std::mem::take(&mut other.entities);
+		let values = std::mem::take(&mut other.values);
+		for (entity, value) in entities.into_iter().zip(values) {
+			let entity = if let Some(entity) = entity_table.valid(entity) {
+				entity
+			} else {
+				counts.skipped += 1;
+				continue;
+			};
+			if let Ok(&location) = self.reverse.get(entity.raw()) {
+				if self.entities[location] == entity.raw() {
+					on_conflict(&mut self.values[location], value);
+					counts.overwritten += 1;
+				} else {
+					// Stale entry of an older generation still in this table, replace it
+					self.entities[location] = entity.raw();
+					self.values[location] = value;
+					counts.merged += 1;
+				}
+			} else {
+				self.insert(entity, value)
+					.expect("reverse mapping is in invalid state with DenseEntityValueTable");
+				counts.merged += 1;
+			}
+		}
+		Ok(counts)
+	}
 }
 
 pub struct DenseEntityValueTableBuilder<EntityType: Entity, ValueType: 'static> {
@@ -113,14 +274,22 @@ impl<EntityType: Entity, ValueType: 'static> TableBuilder
 				database_id,
 				table_name: table_name.into(),
 				table_id,
+				entity_table: Rc::downgrade(&self.entity_table),
+				world_id: entities.world_id(),
 				reverse: SecondaryEntityIndex::new(usize::MAX),
 				entities: Vec::with_capacity(self.capacity),
 				values: Vec::with_capacity(self.capacity),
 			},
 		));
 		this.borrow_mut().this = Rc::downgrade(&this);
-		let another_this = this.clone();
+		// Weak so this table is dropped once removed from its `Database` and no longer used, the
+		// hook is then left behind as a no-op
+		let another_this = Rc::downgrade(&this);
 		let _id = entities.on_delete_entity(Box::new(move |_entity_table_id, entity| {
+			let another_this = match another_this.upgrade() {
+				Some(another_this) => another_this,
+				None => return,
+			};
 			if let Ok(mut deleter) = another_this.try_borrow_mut() {
 				// Don't care if it didn't exist
 				let _ = deleter.delete(entity.raw()); // .expect("Unknown deletion error while deleting valid entity")
@@ -154,6 +323,29 @@ impl<EntityType: Entity, ValueType: 'static> Table
 	fn table_id(&self) -> TableId {
 		self.table_id
 	}
+
+	fn rename(&mut self, new_name: &str) -> bool {
+		self.table_name = new_name.into();
+		true
+	}
+
+	fn rehome(&mut self, database_id: DatabaseId, table_id: TableId) -> bool {
+		self.database_id = database_id;
+		self.table_id = table_id;
+		true
+	}
+
+	fn entity_table(&self) -> Option<Rc<RefCell<dyn Table>>> {
+		self.entity_table
+			.upgrade()
+			.map(|entity_table| entity_table as Rc<RefCell<dyn Table>>)
+	}
+
+	fn clear_table(&mut self) {
+		self.reverse = SecondaryEntityIndex::new(usize::MAX);
+		self.entities.clear();
+		self.values.clear();
+	}
 }
 
 impl<EntityType: Entity, ValueType: 'static> TableCastable
@@ -163,3 +355,171 @@ impl<EntityType: Entity, ValueType: 'static> TableCastable
 		self.this.upgrade().unwrap() // It's obviously valid since it's obviously self
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::database::*;
+	use crate::entity::Entity;
+	use crate::tables::dense_entity_value_table::*;
+	use crate::tables::entity_table::EntityTable;
+
+	#[test]
+	fn merge_from() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let other_entities_storage = database
+			.tables
+			.create("other_entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let first_storage = database
+			.tables
+			.create(
+				"first",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let second_storage = database
+			.tables
+			.create(
+				"second",
+				DenseEntityValueTable::<u64, isize>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let other_storage = database
+			.tables
+			.create(
+				"other",
+				DenseEntityValueTable::<u64, isize>::builder(other_entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
+		let entity3 = entities.insert().raw();
+		let entity4 = entities.insert().raw();
+		entities.delete(entity3).unwrap();
+		{
+			let mut first = first_storage.borrow_mut();
+			let mut second = second_storage.borrow_mut();
+			first.insert(entities.valid(entity1).unwrap(), 1).unwrap();
+			first.insert(entities.valid(entity2).unwrap(), 2).unwrap();
+			second.insert(entities.valid(entity2).unwrap(), 20).unwrap();
+			// Left behind as if the deletion of `entity3` was missed
+			second
+				.insert(ValidEntity::new_unchecked(entity3, entities.world_id()), 30)
+				.unwrap();
+			second.insert(entities.valid(entity4).unwrap(), 40).unwrap();
+		}
+		let mut first = first_storage.borrow_mut();
+		let mut second = second_storage.borrow_mut();
+		// Tables and handles of another `EntityTable` are refused without moving anything
+		let other_entities = other_entities_storage.borrow();
+		assert_eq!(
+			first
+				.merge_from(&other_entities, &mut second, |a, b| *a += b)
+				.err(),
+			Some(DenseEntityValueTableMergeErrors::EntityTableMismatch)
+		);
+		assert_eq!(
+			first
+				.merge_from(&entities, &mut other_storage.borrow_mut(), |a, b| *a += b)
+				.err(),
+			Some(DenseEntityValueTableMergeErrors::OtherTableMismatch)
+		);
+		assert_eq!(second.len(), 3);
+		let counts = first
+			.merge_from(&entities, &mut second, |a, b| *a += b)
+			.unwrap();
+		assert_eq!(
+			counts,
+			DenseEntityValueTableMergeCounts {
+				merged: 1,
+				overwritten: 1,
+				skipped: 1,
+			}
+		);
+		assert!(second.is_empty());
+		assert!(!second.contains(entity2));
+		assert_eq!(first.len(), 3);
+		assert!(!first.contains(entity3));
+		assert_eq!(first.values[*first.reverse.get(entity2).unwrap()], 22);
+		assert_eq!(first.values[*first.reverse.get(entity4).unwrap()], 40);
+	}
+
+	#[test]
+	fn entry() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		// A separate entity table so deletions in `entities` leave stale values behind
+		let other_entities_storage = database
+			.tables
+			.create("other_entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ints_storage = database
+			.tables
+			.create(
+				"ints",
+				DenseEntityValueTable::<u64, isize>::builder(other_entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut other_entities = other_entities_storage.borrow_mut();
+		let mut ints = ints_storage.borrow_mut();
+		let entity1 = entities.insert().raw();
+		let entity2 = entities.insert().raw();
+		assert_eq!(other_entities.extend_iter().take(2).count(), 2);
+		// Handles of `other_entities` for the raw entities of `entities`
+		let valid = |entity| ValidEntity::new_unchecked(entity, other_entities.world_id());
+		let mut calls = 0;
+		for _ in 0..2 {
+			*ints.entry(valid(entity1)).unwrap().or_insert_with(|| {
+				calls += 1;
+				1
+			}) += 10;
+		}
+		assert_eq!(calls, 1);
+		assert_eq!(ints.len(), 1);
+		assert_eq!(ints.values[*ints.reverse.get(entity1).unwrap()], 21);
+		assert_eq!(
+			*ints
+				.entry(valid(entity1))
+				.unwrap()
+				.and_modify(|value| *value *= 2)
+				.or_insert(0),
+			42
+		);
+		ints.entry(valid(entity2))
+			.unwrap()
+			.and_modify(|value| *value = 100)
+			.or_insert(2);
+		assert_eq!(ints.values[*ints.reverse.get(entity2).unwrap()], 2);
+		// A newer generation of the same index sees the old value as vacant
+		entities.delete(entity1).unwrap();
+		let entity1_new = entities.insert().raw();
+		assert_eq!(entity1_new.idx(), entity1.idx());
+		assert_eq!(
+			*ints
+				.entry(valid(entity1_new))
+				.unwrap()
+				.and_modify(|value| *value = 100)
+				.or_insert(3),
+			3
+		);
+		assert_eq!(ints.len(), 2);
+		assert!(ints.contains(entity1_new));
+		// A handle of `entities` itself is from the wrong `EntityTable`
+		let entity3 = entities.insert();
+		assert_eq!(
+			ints.entry(entity3).err(),
+			Some(SecondaryEntityIndexErrors::EntityTableMismatch(
+				entity3.raw()
+			))
+		);
+	}
+}
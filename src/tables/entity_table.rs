@@ -7,9 +7,15 @@ use smol_str::SmolStr;
 use crate::database::{DatabaseId, TableId};
 use crate::entity::Entity;
 use crate::table::{Table, TableBuilder, TableCastable};
+use crate::utils::secondary_entity_index::SecondaryEntityIndexErrors;
 // use bitvec::prelude::*;
 use std::any::Any;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
+use std::sync::atomic;
+use std::sync::atomic::AtomicU32;
 // use tinyvec::TinyVec;
 
 // #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -50,27 +56,66 @@ use std::ops::Deref;
 // 	}
 // }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum EntityAllocationError {
+	/// Every index up to `Entity::MAX_IDX` is alive, no more entities can be allocated.
+	IndexSpaceExhausted(usize),
+}
+
+impl std::error::Error for EntityAllocationError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		use EntityAllocationError::*;
+		match self {
+			IndexSpaceExhausted(_max_idx) => None,
+		}
+	}
+}
+
+impl std::fmt::Display for EntityAllocationError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+		use EntityAllocationError::*;
+		match self {
+			IndexSpaceExhausted(max_idx) => write!(
+				f,
+				"Entity index space exhausted, all indexes up to {} are alive",
+				max_idx
+			),
+		}
+	}
+}
+
+static WORLD_IDS: AtomicU32 = AtomicU32::new(0);
+
 pub struct EntityTable<EntityType: Entity> {
 	this: Weak<RefCell<Self>>,
+	/// Unique per `EntityTable`, stamped into every `ValidEntity` it hands out so handles of
+	/// another `EntityTable` with the same `EntityType` can be told apart.
+	world_id: u32,
 	database_id: DatabaseId,
 	table_name: SmolStr,
 	table_id: TableId,
 	on_delete: Vec<Box<dyn FnMut(TableId, ValidEntity<EntityType>)>>,
+	/// Entities queued by `defer_delete` for the next `flush_deletions`.
+	deferred_deletes: Vec<EntityType>,
 	// pub on_destroy: EventIndexedHandler<Box<dyn Fn(TableId, &[EntityType])>>,
 	//registrations_destroy: Vec<BitVec>,
 	/// `entities` is interesting in that alive ones have their internal index
 	/// match their actual index, if it's dead they don't.  If it's dead the
 	/// internal index actually points to the actual index of the next 'dead'
 	/// one, thus making a handle-based link-list.  If it points to
-	/// `0` then there are no more dead entities after this one.
+	/// the index of `EntityType::invalid()` then there are no more dead entities after this one.
 	/// The generation gets incremented on destruction.
 	entities: Vec<EntityType>,
 	/// This is the 'head' of the singly-linked list of destroyed entities.
 	destroyed: EntityType,
+	/// In deterministic mode the destroyed indexes are kept here instead of in the linked list so
+	/// the lowest one is always reused first.
+	destroyed_heap: Option<BinaryHeap<Reverse<usize>>>,
 }
 
 pub struct EntityTableBuilder<EntityType: Entity> {
 	capacity: usize,
+	deterministic: bool,
 	_phantom: PhantomData<EntityType>,
 }
 
@@ -78,6 +123,7 @@ impl<EntityType: Entity> EntityTable<EntityType> {
 	pub fn builder() -> EntityTableBuilder<EntityType> {
 		EntityTableBuilder {
 			capacity: 0,
+			deterministic: false,
 			_phantom: PhantomData,
 		}
 	}
@@ -85,6 +131,7 @@ impl<EntityType: Entity> EntityTable<EntityType> {
 	pub fn builder_with_capacity(capacity: usize) -> EntityTableBuilder<EntityType> {
 		EntityTableBuilder {
 			capacity,
+			deterministic: false,
 			_phantom: PhantomData,
 		}
 	}
@@ -97,63 +144,229 @@ impl<EntityType: Entity> EntityTable<EntityType> {
 		self.on_delete.len() - 1
 	}
 
+	pub fn world_id(&self) -> u32 {
+		self.world_id
+	}
+
 	pub fn contains(&self, entity: EntityType) -> bool {
 		let idx = entity.idx();
 		(idx < self.entities.len()) && self.entities[idx] == entity
 	}
 
+	/// Returns the count of live entities, of destroyed indexes waiting to be reused, and of
+	/// indexes ever allocated, which is always the sum of the first two.
+	///
+	/// Counting the destroyed indexes walks them so this is **O(free)**.
+	pub fn entity_stats(&self) -> (usize, usize, usize) {
+		let high_water = if EntityType::invalid().idx() == 0 {
+			// Index 0 is the reserved null entity
+			self.entities.len().saturating_sub(1)
+		} else {
+			self.entities.len()
+		};
+		let free = if let Some(destroyed_heap) = &self.destroyed_heap {
+			destroyed_heap.len()
+		} else {
+			let mut free = 0;
+			let mut next = self.destroyed;
+			while !next.is_null() {
+				free += 1;
+				next = EntityType::new(self.entities[next.idx()].idx());
+			}
+			free
+		};
+		(high_water - free, free, high_water)
+	}
+
 	pub fn valid(&self, entity: EntityType) -> Option<ValidEntity<EntityType>> {
 		if self.contains(entity) {
-			Some(ValidEntity(entity, PhantomData))
+			Some(ValidEntity(entity, self.world_id, PhantomData))
+		} else {
+			None
+		}
+	}
+
+	/// Re-validates a `ValidEntity` against this table, returning `None` if it was handed out by a
+	/// different `EntityTable` or is no longer alive in this one.
+	pub fn valid_handle(&self, entity: ValidEntity<EntityType>) -> Option<ValidEntity<EntityType>> {
+		if entity.1 == self.world_id {
+			self.valid(entity.0)
 		} else {
 			None
 		}
 	}
 
+	/// Iterates every live entity in index order with its current generation.
+	pub fn iter_live(&self) -> impl Iterator<Item = ValidEntity<EntityType>> + '_ {
+		// The null entity, when it has a slot, always points to itself
+		self.entities
+			.iter()
+			.enumerate()
+			.filter(|&(idx, entity)| entity.idx() == idx && entity.is_valid())
+			.map(move |(_idx, &entity)| ValidEntity(entity, self.world_id, PhantomData))
+	}
+
+	/// Validates every entity of `raws` at once, returning the first one that is not alive.
+	pub fn validate_slice(
+		&self,
+		raws: &[EntityType],
+	) -> Result<Vec<ValidEntity<EntityType>>, EntityType> {
+		let mut out = Vec::with_capacity(raws.len());
+		self.validate_slice_into(raws, &mut out)?;
+		Ok(out)
+	}
+
+	/// Same as `validate_slice` but appends to `out` so its allocation can be reused, on failure
+	/// `out` is left as it was.
+	pub fn validate_slice_into<'a>(
+		&'a self,
+		raws: &[EntityType],
+		out: &mut Vec<ValidEntity<'a, EntityType>>,
+	) -> Result<(), EntityType> {
+		let len = out.len();
+		out.reserve(raws.len());
+		for &raw in raws {
+			if let Some(entity) = self.valid(raw) {
+				out.push(entity);
+			} else {
+				out.truncate(len);
+				return Err(raw);
+			}
+		}
+		Ok(())
+	}
+
+	/// Allocates a new entity, panics if the index space of the `EntityType` is exhausted, see
+	/// `try_insert` for a non-panicking version.
 	pub fn insert(&mut self) -> ValidEntity<EntityType> {
-		if self.destroyed.is_null() {
-			// `destroyed` linked list is empty
+		self.try_insert()
+			.expect("EntityTable index space exhausted while inserting an entity")
+	}
+
+	pub fn try_insert(&mut self) -> Result<ValidEntity<EntityType>, EntityAllocationError> {
+		if let Some(entity) = self.pop_destroyed() {
+			Ok(ValidEntity(entity, self.world_id, PhantomData))
+		} else {
+			// No destroyed entities to reuse
+			if self.entities.len() > EntityType::MAX_IDX {
+				return Err(EntityAllocationError::IndexSpaceExhausted(
+					EntityType::MAX_IDX,
+				));
+			}
 			let entity = EntityType::new(self.entities.len());
 			self.entities.push(entity);
-			ValidEntity(entity, PhantomData)
+			Ok(ValidEntity(entity, self.world_id, PhantomData))
+		}
+	}
+
+	/// Revives the next destroyed entity to be reused, if any, with its bumped generation.
+	fn pop_destroyed(&mut self) -> Option<EntityType> {
+		let head = if let Some(destroyed_heap) = &mut self.destroyed_heap {
+			destroyed_heap.pop()?.0
+		} else if self.destroyed.is_null() {
+			// `destroyed` linked list is empty
+			return None;
 		} else {
 			let head = self.destroyed.idx();
-			// This unsafe is safe because the head is always in a valid index for a valid `self.destroyed`
-			// let head_entity = &mut self.entities[head];
-			let head_entity = unsafe { self.entities.get_unchecked_mut(head) };
-			self.destroyed = EntityType::new(head_entity.idx()); // New head of destroyed list
-			ValidEntity(*head_entity.set_idx(head), PhantomData)
-		}
+			self.destroyed = EntityType::new(self.entities[head].idx()); // New head of destroyed list
+			head
+		};
+		Some(*self.entities[head].set_idx(head))
 	}
 
 	pub fn extend_iter(&mut self) -> InsertEntityIterator<EntityType> {
 		InsertEntityIterator(self)
 	}
 
+	/// Allocates `n` entities at once, reusing deleted slots first and then growing the table a
+	/// single time for the rest, panics if the index space of the `EntityType` would be exhausted.
+	pub fn reserve_entities(&mut self, n: usize) -> Vec<ValidEntity<EntityType>> {
+		let mut reserved = Vec::with_capacity(n);
+		while reserved.len() < n {
+			if let Some(entity) = self.pop_destroyed() {
+				reserved.push(ValidEntity(entity, self.world_id, PhantomData));
+			} else {
+				break;
+			}
+		}
+		let fresh = n - reserved.len();
+		let start = self.entities.len();
+		if fresh > 0 && start + fresh - 1 > EntityType::MAX_IDX {
+			panic!("EntityTable index space exhausted while reserving entities");
+		}
+		self.entities.reserve(fresh);
+		for idx in start..start + fresh {
+			let entity = EntityType::new(idx);
+			self.entities.push(entity);
+			reserved.push(ValidEntity(entity, self.world_id, PhantomData));
+		}
+		reserved
+	}
+
 	pub fn delete(&mut self, entity: EntityType) -> Result<(), ()> {
 		let idx = entity.idx();
 		if idx >= self.entities.len() || self.entities[idx] != entity {
 			return Err(());
 		}
 
-		(&mut self.entities[idx]).bump_version_with_idx(self.destroyed.idx());
-		self.destroyed = EntityType::new(idx);
+		if let Some(destroyed_heap) = &mut self.destroyed_heap {
+			// Any index other than its own marks it as dead
+			(&mut self.entities[idx]).bump_version_with_idx(EntityType::invalid().idx());
+			destroyed_heap.push(Reverse(idx));
+		} else {
+			(&mut self.entities[idx]).bump_version_with_idx(self.destroyed.idx());
+			self.destroyed = EntityType::new(idx);
+		}
 
 		//let listeners = &self.registrations_destroy[idx];
 		//for listener_id in listeners.ite {}
 		//self.registrations.destroy.iter();
 		for cb in self.on_delete.iter_mut() {
-			cb(self.table_id, ValidEntity(entity, PhantomData));
+			cb(
+				self.table_id,
+				ValidEntity(entity, self.world_id, PhantomData),
+			);
 		}
 
 		Ok(())
 	}
 
+	/// Queues `entity` to be deleted by the next `flush_deletions`, until then it stays alive and
+	/// keeps its rows in every table, so systems can mark entities for deletion mid-iteration.
+	///
+	/// Errors with `IndexDoesNotExist` if `entity` is not alive, queueing the same entity twice only
+	/// deletes it once.
+	pub fn defer_delete(
+		&mut self,
+		entity: EntityType,
+	) -> Result<(), SecondaryEntityIndexErrors<EntityType>> {
+		if !self.contains(entity) {
+			return Err(SecondaryEntityIndexErrors::IndexDoesNotExist(entity));
+		}
+		self.deferred_deletes.push(entity);
+		Ok(())
+	}
+
+	/// Deletes every entity queued by `defer_delete` in one pass, running the delete hooks for each,
+	/// and returns how many distinct entities were deleted.
+	///
+	/// An entity queued more than once is only deleted and counted once, one that was already
+	/// deleted some other way since it was queued is skipped and not counted.
+	pub fn flush_deletions(&mut self) -> usize {
+		let mut deferred = std::mem::take(&mut self.deferred_deletes);
+		deferred.sort_unstable();
+		deferred.dedup();
+		deferred
+			.into_iter()
+			.filter(|&entity| self.delete(entity).is_ok())
+			.count()
+	}
+
 	pub fn clear(&mut self) -> Result<(), ()> {
-		// Entity 0 is the null entity, always points to itself
-		for idx in 1..self.entities.len() {
+		// The null entity, when it has a slot, always points to itself
+		for idx in 0..self.entities.len() {
 			let entity = self.entities[idx];
-			if entity.idx() == idx {
+			if entity.idx() == idx && entity.is_valid() {
 				self.delete(entity)?;
 			}
 		}
@@ -161,8 +374,44 @@ impl<EntityType: Entity> EntityTable<EntityType> {
 	}
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct ValidEntity<'a, EntityType: Entity>(EntityType, PhantomData<&'a ()>);
+#[derive(Clone, Copy)]
+pub struct ValidEntity<'a, EntityType: Entity>(EntityType, u32, PhantomData<&'a ()>);
+
+/// Decodes the entity into its index and generation, use `{:x}` for the packed raw form.
+impl<'a, EntityType: Entity> std::fmt::Debug for ValidEntity<'a, EntityType> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Entity")
+			.field("index", &self.0.idx())
+			.field("generation", &self.0.version_as_usize())
+			.finish()
+	}
+}
+
+impl<'a, EntityType: Entity + std::fmt::LowerHex> std::fmt::LowerHex
+	for ValidEntity<'a, EntityType>
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::LowerHex::fmt(&self.0, f)
+	}
+}
+
+/// Equal only when the index, the generation, and the `EntityTable` all match, so the handle of a
+/// reused index never equals the handle it had before.
+impl<'a, EntityType: Entity> PartialEq for ValidEntity<'a, EntityType> {
+	fn eq(&self, other: &Self) -> bool {
+		self.0 == other.0 && self.1 == other.1
+	}
+}
+
+impl<'a, EntityType: Entity> Eq for ValidEntity<'a, EntityType> {}
+
+impl<'a, EntityType: Entity> Hash for ValidEntity<'a, EntityType> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.0.idx().hash(state);
+		self.0.version_as_usize().hash(state);
+		self.1.hash(state);
+	}
+}
 
 impl<'a, EntityType: Entity> Deref for ValidEntity<'a, EntityType> {
 	type Target = EntityType;
@@ -173,9 +422,33 @@ impl<'a, EntityType: Entity> Deref for ValidEntity<'a, EntityType> {
 }
 
 impl<'a, EntityType: Entity> ValidEntity<'a, EntityType> {
+	/// Only for entities that are known to be valid, such as ones held by a table that is kept in
+	/// sync with the entity table through its delete hook.
+	pub(crate) fn new_unchecked(entity: EntityType, world_id: u32) -> Self {
+		ValidEntity(entity, world_id, PhantomData)
+	}
+
 	pub fn raw(&self) -> EntityType {
 		self.0
 	}
+
+	/// The `world_id` of the `EntityTable` this handle was validated by.
+	pub fn world_id(&self) -> u32 {
+		self.1
+	}
+
+	/// Returns the raw entity only if this handle was validated by the `EntityTable` of `world_id`,
+	/// tables use this to reject handles from an `EntityTable` other than their own.
+	pub fn raw_in(
+		&self,
+		world_id: u32,
+	) -> Result<EntityType, SecondaryEntityIndexErrors<EntityType>> {
+		if self.1 == world_id {
+			Ok(self.0)
+		} else {
+			Err(SecondaryEntityIndexErrors::EntityTableMismatch(self.0))
+		}
+	}
 }
 
 pub struct InsertEntityIterator<'s, EntityType: Entity>(&'s mut EntityTable<EntityType>);
@@ -184,23 +457,31 @@ impl<'s, EntityType: Entity> Iterator for InsertEntityIterator<'s, EntityType> {
 	type Item = ValidEntity<'s, EntityType>;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		// Basically the same code as `insert`
-		if self.0.destroyed.is_null() {
-			// `destroyed` linked list is empty
+		// Basically the same code as `try_insert`
+		if let Some(entity) = self.0.pop_destroyed() {
+			Some(ValidEntity(entity, self.0.world_id, PhantomData))
+		} else {
+			// No destroyed entities to reuse
+			if self.0.entities.len() > EntityType::MAX_IDX {
+				return None;
+			}
 			let entity = EntityType::new(self.0.entities.len());
 			self.0.entities.push(entity);
-			Some(ValidEntity(entity, PhantomData))
-		} else {
-			let head = self.0.destroyed.idx();
-			// This unsafe is safe because the head is always in a valid index for a valid `self.destroyed`
-			// let head_entity = &mut self.entities[head];
-			let head_entity = unsafe { self.0.entities.get_unchecked_mut(head) };
-			self.0.destroyed = EntityType::new(head_entity.idx()); // New head of destroyed list
-			Some(ValidEntity(*head_entity.set_idx(head), PhantomData))
+			Some(ValidEntity(entity, self.0.world_id, PhantomData))
 		}
 	}
 }
 
+impl<EntityType: Entity> EntityTableBuilder<EntityType> {
+	/// Always reuses the lowest destroyed index first so the same sequence of inserts and deletes
+	/// hands out the same entities, such as for reproducible tests, at the cost of a heap instead
+	/// of the default most recently destroyed first reuse.
+	pub fn deterministic(mut self, deterministic: bool) -> Self {
+		self.deterministic = deterministic;
+		self
+	}
+}
+
 impl<EntityType: Entity> TableBuilder for EntityTableBuilder<EntityType> {
 	type Table = EntityTable<EntityType>;
 
@@ -212,15 +493,25 @@ impl<EntityType: Entity> TableBuilder for EntityTableBuilder<EntityType> {
 	) -> Rc<RefCell<Self::Table>> {
 		let this = Rc::new(RefCell::new(EntityTable {
 			this: Weak::new(),
+			world_id: WORLD_IDS.fetch_add(1, atomic::Ordering::Relaxed),
 			database_id,
 			table_name: table_name.into(),
 			table_id,
 			on_delete: Vec::with_capacity(self.capacity),
+			deferred_deletes: Vec::new(),
 			//on_destroy: EventIndexedHandler::with_capacity(self.capacity),
 			entities: Vec::with_capacity(self.capacity),
-			destroyed: EntityType::new(0),
+			destroyed: EntityType::invalid(),
+			destroyed_heap: if self.deterministic {
+				Some(BinaryHeap::new())
+			} else {
+				None
+			},
 		}));
-		this.borrow_mut().entities.push(EntityType::new(0));
+		if EntityType::invalid().idx() == 0 {
+			// Reserve index 0 as it's the null entity, otherwise the null index is past `MAX_IDX`
+			this.borrow_mut().entities.push(EntityType::invalid());
+		}
 		this.borrow_mut().this = Rc::downgrade(&this);
 		this
 	}
@@ -247,6 +538,21 @@ impl<EntityType: Entity> Table for EntityTable<EntityType> {
 		self.table_id
 	}
 
+	fn rename(&mut self, new_name: &str) -> bool {
+		self.table_name = new_name.into();
+		true
+	}
+
+	fn rehome(&mut self, database_id: DatabaseId, table_id: TableId) -> bool {
+		self.database_id = database_id;
+		self.table_id = table_id;
+		true
+	}
+
+	fn flush_deletions(&mut self) {
+		EntityTable::flush_deletions(self);
+	}
+
 	// fn indexes_len(&self) -> usize {
 	// 	1
 	// }
@@ -269,3 +575,256 @@ impl<EntityType: Entity> TableCastable for EntityTable<EntityType> {
 		self.this.upgrade().unwrap() // It's obviously valid since it's obviously self
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::database::*;
+	use crate::entity::{Entity, MaxInvalidU32};
+	use crate::tables::entity_table::*;
+
+	#[test]
+	fn index_space_exhausted() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u16>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		// Index 0 is the null entity so only `MAX_IDX` entities can be alive at once
+		for _ in 0..u16::MAX_IDX {
+			entities.try_insert().unwrap();
+		}
+		assert_eq!(
+			entities.try_insert().map(|e| e.raw()),
+			Err(EntityAllocationError::IndexSpaceExhausted(4095))
+		);
+		assert_eq!(entities.extend_iter().next().map(|e| e.raw()), None);
+		entities.delete(42).unwrap();
+		assert_eq!(entities.try_insert().map(|e| e.idx()), Ok(42));
+	}
+
+	#[test]
+	fn validate_slice() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let raws: Vec<_> = entities.extend_iter().take(4).map(|e| e.raw()).collect();
+		assert_eq!(
+			entities
+				.validate_slice(&raws)
+				.unwrap()
+				.iter()
+				.map(|e| e.raw())
+				.collect::<Vec<_>>(),
+			raws
+		);
+		entities.delete(raws[2]).unwrap();
+		assert_eq!(
+			entities.validate_slice(&raws).map(|v| v.len()),
+			Err(raws[2])
+		);
+		let mut out = entities.validate_slice(&raws[..2]).unwrap();
+		assert_eq!(entities.validate_slice_into(&raws, &mut out), Err(raws[2]));
+		assert_eq!(out.len(), 2);
+		entities.validate_slice_into(&raws[3..], &mut out).unwrap();
+		assert_eq!(out.len(), 3);
+	}
+
+	#[test]
+	fn valid_handle() {
+		let mut database = Database::new();
+		let first_storage = database
+			.tables
+			.create("first", EntityTable::<u64>::builder())
+			.unwrap();
+		let second_storage = database
+			.tables
+			.create("second", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut first = first_storage.borrow_mut();
+		let mut second = second_storage.borrow_mut();
+		assert_ne!(first.world_id(), second.world_id());
+		let raw = first.insert().raw();
+		assert_eq!(second.insert().raw(), raw);
+		let from_first = first.valid(raw).unwrap();
+		let from_second = second.valid(raw).unwrap();
+		assert_eq!(from_first.world_id(), first.world_id());
+		assert_eq!(first.valid_handle(from_first).map(|e| e.raw()), Some(raw));
+		assert!(first.valid_handle(from_second).is_none());
+		assert!(second.valid_handle(from_first).is_none());
+		assert_eq!(second.valid_handle(from_second).map(|e| e.raw()), Some(raw));
+	}
+
+	#[test]
+	fn reserve_entities() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let raws: Vec<_> = entities.extend_iter().take(1000).map(|e| e.raw()).collect();
+		for &raw in raws.iter().step_by(2) {
+			entities.delete(raw).unwrap();
+		}
+		let len = entities.entities.len();
+		let reserved: Vec<_> = entities
+			.reserve_entities(1000)
+			.iter()
+			.map(|e| e.raw())
+			.collect();
+		assert_eq!(reserved.len(), 1000);
+		let recycled = reserved.iter().filter(|raw| raw.idx() < len).count();
+		assert_eq!(recycled, 500);
+		assert!(reserved[..500]
+			.iter()
+			.all(|raw| raw.version_as_usize() == 1));
+		assert_eq!(entities.entities.len(), len + 500);
+		assert!(reserved.iter().all(|&raw| entities.contains(raw)));
+		assert!(entities.reserve_entities(0).is_empty());
+	}
+
+	#[test]
+	fn valid_entity_equality() {
+		use std::collections::hash_map::DefaultHasher;
+		fn hash_of(entity: ValidEntity<u64>) -> u64 {
+			let mut hasher = DefaultHasher::new();
+			entity.hash(&mut hasher);
+			hasher.finish()
+		}
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let old = entities.insert().raw();
+		entities.delete(old).unwrap();
+		let new = entities.insert().raw();
+		assert_eq!(new.idx(), old.idx());
+		// The old handle can't be validated anymore so it is made as it was before the delete
+		let old = ValidEntity(old, entities.world_id(), PhantomData);
+		let new = entities.valid(new).unwrap();
+		assert!(new == entities.valid(new.raw()).unwrap());
+		assert_eq!(hash_of(new), hash_of(entities.valid(new.raw()).unwrap()));
+		assert!(old != new);
+		assert_ne!(hash_of(old), hash_of(new));
+	}
+
+	#[test]
+	fn valid_entity_formatting() {
+		let entity = ValidEntity(u32::try_from_idx_version(5, 3).unwrap(), 0, PhantomData);
+		assert_eq!(
+			format!("{:?}", entity),
+			"Entity { index: 5, generation: 3 }"
+		);
+		assert_eq!(format!("{:x}", entity), "300005");
+	}
+
+	#[test]
+	fn max_invalid_entity() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<MaxInvalidU32>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		// Index 0 is a live index for this convention, the null index is at the top instead
+		let first = entities.insert().raw();
+		assert_eq!(first, MaxInvalidU32(0));
+		let second = entities.insert().raw();
+		assert_eq!(second.idx(), 1);
+		assert!(!entities.contains(MaxInvalidU32::invalid()));
+		entities.delete(first).unwrap();
+		assert!(!entities.contains(first));
+		let reused = entities.insert().raw();
+		assert_eq!(reused.idx(), 0);
+		assert_eq!(reused.version(), 1);
+		// The free list is empty again so a fresh index is allocated
+		assert_eq!(entities.insert().idx(), 2);
+		entities.clear().unwrap();
+		assert!(!entities.contains(reused));
+		assert!(!entities.contains(second));
+	}
+
+	#[test]
+	fn entity_stats() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		assert_eq!(entities.entity_stats(), (0, 0, 0));
+		let raws: Vec<_> = entities.extend_iter().take(10).map(|e| e.raw()).collect();
+		assert_eq!(entities.entity_stats(), (10, 0, 10));
+		entities.delete(raws[3]).unwrap();
+		entities.delete(raws[7]).unwrap();
+		entities.delete(raws[0]).unwrap();
+		assert_eq!(entities.entity_stats(), (7, 3, 10));
+		entities.insert();
+		assert_eq!(entities.entity_stats(), (8, 2, 10));
+		entities.reserve_entities(4);
+		assert_eq!(entities.entity_stats(), (12, 0, 12));
+		entities.clear().unwrap();
+		assert_eq!(entities.entity_stats(), (0, 12, 12));
+	}
+
+	#[test]
+	fn iter_live() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let raws: Vec<_> = entities.extend_iter().take(5).map(|e| e.raw()).collect();
+		entities.delete(raws[1]).unwrap();
+		entities.delete(raws[3]).unwrap();
+		let live: Vec<_> = entities.iter_live().map(|e| e.raw()).collect();
+		assert_eq!(live, vec![raws[0], raws[2], raws[4]]);
+		let reused = entities.insert().raw();
+		assert_eq!(reused.version_as_usize(), 1);
+		let live: Vec<_> = entities.iter_live().map(|e| e.raw()).collect();
+		assert_eq!(live, vec![raws[0], raws[2], reused, raws[4]]);
+		assert!(entities
+			.iter_live()
+			.all(|e| e.world_id() == entities.world_id()));
+	}
+
+	#[test]
+	fn deterministic() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create(
+				"entities",
+				EntityTable::<u64>::builder().deterministic(true),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let raws: Vec<_> = entities.extend_iter().take(8).map(|e| e.raw()).collect();
+		entities.delete(raws[2]).unwrap();
+		entities.delete(raws[6]).unwrap();
+		entities.delete(raws[4]).unwrap();
+		assert!(!entities.contains(raws[4]));
+		let reused = entities.insert().raw();
+		assert_eq!(reused.idx(), raws[2].idx());
+		assert_eq!(reused.version_as_usize(), 1);
+		let reserved: Vec<_> = entities
+			.reserve_entities(3)
+			.iter()
+			.map(|e| e.idx())
+			.collect();
+		assert_eq!(
+			reserved,
+			vec![raws[4].idx(), raws[6].idx(), raws[7].idx() + 1]
+		);
+		entities.clear().unwrap();
+		assert_eq!(entities.insert().idx(), 1);
+		assert_eq!(entities.extend_iter().next().map(|e| e.idx()), Some(2));
+	}
+}
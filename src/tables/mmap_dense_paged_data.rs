@@ -0,0 +1,404 @@
+use crate::tables::dense_entity_dynamic_paged_multi_value_table::DensePagedColumns;
+use memmap2::MmapMut;
+use std::fs::{File, OpenOptions};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// Bytes reserved at the start of every group file, the first 8 hold the len of the group and the
+/// next 8 the size of the value type, it also keeps the values aligned for up to 64 byte alignment.
+const HEADER_BYTES: usize = 64;
+
+struct MmapGroup<ValueType: Copy + 'static> {
+	file: File,
+	map: MmapMut,
+	len: usize,
+	capacity: usize,
+	_phantom: PhantomData<ValueType>,
+}
+
+impl<ValueType: Copy + 'static> MmapGroup<ValueType> {
+	fn create(path: &Path) -> std::io::Result<Self> {
+		let file = OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(true)
+			.open(path)?;
+		file.set_len(HEADER_BYTES as u64)?;
+		let map = unsafe { MmapMut::map_mut(&file)? };
+		let mut group = MmapGroup {
+			file,
+			map,
+			len: 0,
+			capacity: 0,
+			_phantom: PhantomData,
+		};
+		group.map[8..16].copy_from_slice(&(std::mem::size_of::<ValueType>() as u64).to_ne_bytes());
+		group.set_len(0);
+		Ok(group)
+	}
+
+	fn open(path: &Path) -> std::io::Result<Self> {
+		let file = OpenOptions::new().read(true).write(true).open(path)?;
+		let map = unsafe { MmapMut::map_mut(&file)? };
+		let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+		if map.len() < HEADER_BYTES {
+			return Err(invalid("group file is shorter than its header"));
+		}
+		let read_u64 = |at: usize| {
+			let mut bytes = [0; 8];
+			bytes.copy_from_slice(&map[at..at + 8]);
+			u64::from_ne_bytes(bytes) as usize
+		};
+		let len = read_u64(0);
+		if read_u64(8) != std::mem::size_of::<ValueType>() {
+			return Err(invalid("group file was written for a different value size"));
+		}
+		let capacity = match std::mem::size_of::<ValueType>() {
+			0 => len,
+			size => (map.len() - HEADER_BYTES) / size,
+		};
+		if len > capacity {
+			return Err(invalid("group file is shorter than its len"));
+		}
+		Ok(MmapGroup {
+			file,
+			map,
+			len,
+			capacity,
+			_phantom: PhantomData,
+		})
+	}
+
+	fn set_len(&mut self, len: usize) {
+		self.len = len;
+		self.map[0..8].copy_from_slice(&(len as u64).to_ne_bytes());
+	}
+
+	/// Resizes the file and maps it again, zero sized values only ever map the header.
+	fn remap(&mut self, capacity: usize) {
+		let bytes = HEADER_BYTES + capacity * std::mem::size_of::<ValueType>();
+		self.map
+			.flush()
+			.expect("failed to flush the mapped file of MmapDensePagedData");
+		self.file
+			.set_len(bytes as u64)
+			.expect("failed to resize the mapped file of MmapDensePagedData");
+		self.map = unsafe {
+			MmapMut::map_mut(&self.file).expect("failed to map the file of MmapDensePagedData")
+		};
+		self.capacity = capacity;
+	}
+
+	fn reserve(&mut self, additional: usize) {
+		let needed = self.len + additional;
+		if needed <= self.capacity {
+			return;
+		}
+		if std::mem::size_of::<ValueType>() == 0 {
+			self.capacity = needed;
+			return;
+		}
+		self.remap(needed.max(self.capacity * 2).max(64));
+	}
+
+	fn values(&self) -> &[ValueType] {
+		unsafe {
+			std::slice::from_raw_parts(
+				self.map.as_ptr().add(HEADER_BYTES) as *const ValueType,
+				self.len,
+			)
+		}
+	}
+
+	fn values_mut(&mut self) -> &mut [ValueType] {
+		unsafe {
+			std::slice::from_raw_parts_mut(
+				self.map.as_mut_ptr().add(HEADER_BYTES) as *mut ValueType,
+				self.len,
+			)
+		}
+	}
+
+	fn push(&mut self, data: ValueType) {
+		self.reserve(1);
+		unsafe {
+			(self.map.as_mut_ptr().add(HEADER_BYTES) as *mut ValueType)
+				.add(self.len)
+				.write(data);
+		}
+		self.set_len(self.len + 1);
+	}
+
+	fn swap_remove(&mut self, index: usize) -> ValueType {
+		let len = self.len;
+		assert!(index < len, "swap_remove index out of bounds");
+		let values = self.values_mut();
+		let value = values[index];
+		values[index] = values[len - 1];
+		self.set_len(len - 1);
+		value
+	}
+
+	fn remove(&mut self, index: usize) -> ValueType {
+		let len = self.len;
+		assert!(index < len, "remove index out of bounds");
+		let values = self.values_mut();
+		let value = values[index];
+		values.copy_within(index + 1.., index);
+		self.set_len(len - 1);
+		value
+	}
+}
+
+/// A `DensePagedColumns` that keeps each group in its own memory mapped file instead of on the
+/// heap, for offline batch processing of component sets that are larger than memory, register it
+/// on a table with `with_component_storage`.
+///
+/// Only `Copy` values are supported since the values are written to and read back from the files
+/// as raw bytes, they are never dropped.
+pub struct MmapDensePagedData<ValueType: Copy + 'static> {
+	directory: PathBuf,
+	groups: Vec<MmapGroup<ValueType>>,
+}
+
+impl<ValueType: Copy + 'static> MmapDensePagedData<ValueType> {
+	fn wrap(directory: PathBuf, groups: Vec<MmapGroup<ValueType>>) -> Self {
+		assert!(
+			std::mem::align_of::<ValueType>() <= HEADER_BYTES,
+			"MmapDensePagedData values can be aligned to at most 64 bytes"
+		);
+		MmapDensePagedData { directory, groups }
+	}
+
+	fn group_path(directory: &Path, group: usize) -> PathBuf {
+		directory.join(format!("{}.group", group))
+	}
+
+	/// Creates an empty storage that keeps its group files in `directory`, creating the directory
+	/// if needed and deleting any group files already in it.
+	pub fn new(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+		let directory = directory.into();
+		std::fs::create_dir_all(&directory)?;
+		for entry in std::fs::read_dir(&directory)? {
+			let path = entry?.path();
+			if path.extension() == Some("group".as_ref()) {
+				std::fs::remove_file(&path)?;
+			}
+		}
+		Ok(Self::wrap(directory, vec![]))
+	}
+
+	/// Opens the group files a storage of the same `ValueType` left in `directory`, the groups are
+	/// read in order until the first missing group file.
+	///
+	/// # Safety
+	///
+	/// The files must have been written by a `MmapDensePagedData<ValueType>`, and `ValueType` must
+	/// be valid for whatever bytes it was written as, so it must not hold any references or
+	/// pointers as those would dangle after a reopen.
+	pub unsafe fn open(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+		let directory = directory.into();
+		let mut groups = vec![];
+		loop {
+			let path = Self::group_path(&directory, groups.len());
+			if !path.exists() {
+				break;
+			}
+			groups.push(MmapGroup::open(&path)?);
+		}
+		Ok(Self::wrap(directory, groups))
+	}
+
+	/// Returns the directory holding the group files.
+	pub fn directory(&self) -> &Path {
+		&self.directory
+	}
+
+	/// Writes every modified value back to the group files.
+	pub fn flush(&self) -> std::io::Result<()> {
+		for group in self.groups.iter() {
+			group.map.flush()?;
+		}
+		Ok(())
+	}
+}
+
+impl<ValueType: Copy + 'static> DensePagedColumns<ValueType> for MmapDensePagedData<ValueType> {
+	fn group_count(&self) -> usize {
+		self.groups.len()
+	}
+
+	fn ensure_group_count(&mut self, group_count: usize) {
+		while self.groups.len() < group_count {
+			let path = Self::group_path(&self.directory, self.groups.len());
+			let group = MmapGroup::create(&path)
+				.expect("failed to create the group file of MmapDensePagedData");
+			self.groups.push(group);
+		}
+	}
+
+	fn reserve_groups(&mut self, additional: usize) {
+		self.groups.reserve(additional);
+	}
+
+	fn reserve_in_group(&mut self, group: usize, additional: usize) {
+		self.groups[group].reserve(additional);
+	}
+
+	fn group(&self, group: usize) -> &[ValueType] {
+		self.groups[group].values()
+	}
+
+	fn group_mut(&mut self, group: usize) -> &mut [ValueType] {
+		self.groups[group].values_mut()
+	}
+
+	fn push(&mut self, group: usize, value: ValueType) {
+		self.groups[group].push(value);
+	}
+
+	fn swap_remove(&mut self, group: usize, index: usize) -> ValueType {
+		self.groups[group].swap_remove(index)
+	}
+
+	fn remove(&mut self, group: usize, index: usize) -> ValueType {
+		self.groups[group].remove(index)
+	}
+
+	fn truncate(&mut self, group: usize, len: usize) {
+		let group = &mut self.groups[group];
+		if len < group.len {
+			group.set_len(len);
+		}
+	}
+
+	fn take_group(&mut self, group: usize) -> Vec<ValueType> {
+		let group = &mut self.groups[group];
+		let values = group.values().to_vec();
+		group.set_len(0);
+		values
+	}
+
+	fn capacity_len(&self) -> (usize, usize) {
+		self.groups.iter().fold((0, 0), |(capacity, len), group| {
+			(capacity + group.capacity, len + group.len)
+		})
+	}
+
+	fn heap_bytes(&self) -> usize {
+		// The values live in the mapped files, not on the heap
+		0
+	}
+
+	fn shrink_to_fit(&mut self) {
+		for group in self.groups.iter_mut() {
+			if group.capacity > group.len && std::mem::size_of::<ValueType>() != 0 {
+				group.remap(group.len);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::database::Database;
+	use crate::tables::dense_entity_dynamic_paged_multi_value_table::DenseEntityDynamicPagedMultiValueTable;
+	use crate::tables::entity_table::EntityTable;
+	use crate::tables::mmap_dense_paged_data::*;
+	use crate::{tl, TL};
+
+	#[test]
+	fn reopen() {
+		let directory =
+			std::env::temp_dir().join(format!("enrs-mmap-dense-paged-data-{}", std::process::id()));
+		{
+			let mut storage = MmapDensePagedData::<u64>::new(&directory).unwrap();
+			storage.ensure_group_count(3);
+			for value in 0..1_000_000 {
+				storage.push(1, value);
+			}
+			storage.push(0, 42);
+			storage.swap_remove(1, 0);
+			assert_eq!(storage.group(1).len(), 999_999);
+			storage.flush().unwrap();
+		}
+		{
+			let storage = unsafe { MmapDensePagedData::<u64>::open(&directory) }.unwrap();
+			assert_eq!(storage.group_count(), 3);
+			assert_eq!(storage.group(0), &[42]);
+			assert_eq!(storage.group(1).len(), 999_999);
+			assert_eq!(storage.group(1)[0], 999_999);
+			assert_eq!(storage.group(1)[1..].iter().sum::<u64>(), 499_998_500_001);
+		}
+		assert!(unsafe { MmapDensePagedData::<u32>::open(&directory) }.is_err());
+		// A new storage does not pick up the groups left by the old one
+		let mut storage = MmapDensePagedData::<u64>::new(&directory).unwrap();
+		storage.ensure_group_count(1);
+		drop(storage);
+		let storage = unsafe { MmapDensePagedData::<u64>::open(&directory) }.unwrap();
+		assert_eq!(storage.group_count(), 1);
+		assert!(storage.group(0).is_empty());
+		std::fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn table_storage() {
+		let directory =
+			std::env::temp_dir().join(format!("enrs-mmap-table-storage-{}", std::process::id()));
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let multi_storage = database
+			.tables
+			.create(
+				"multi",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone())
+					.with_component_storage::<u64>(MmapDensePagedData::new(&directory).unwrap()),
+			)
+			.unwrap();
+		let mut inserter = multi_storage
+			.borrow_mut()
+			.group_insert::<TL![&mut u64, &mut u8]>()
+			.unwrap();
+		let raws: Vec<_> = {
+			let mut entities = entities_storage.borrow_mut();
+			let mut multi = multi_storage.borrow_mut();
+			(0..1_000_000)
+				.map(|value| {
+					let e = entities.insert();
+					inserter
+						.lock(&mut multi)
+						.insert(e, tl![value, value as u8])
+						.unwrap();
+					e.raw()
+				})
+				.collect()
+		};
+		// The last value is swapped into the place of the deleted one, the sort moves it back
+		entities_storage.borrow_mut().delete(raws[0]).unwrap();
+		{
+			let entities = entities_storage.borrow();
+			let mut multi = multi_storage.borrow_mut();
+			let group = multi.find_archetype::<TL![&mut u64, &mut u8]>().unwrap();
+			multi.sort_group_by::<u64>(group).unwrap();
+			multi.debug_check_invariants().unwrap();
+			let expected: Vec<u64> = (1..1_000_000).collect();
+			let mut query = multi.group_query::<TL![&mut u64, &u8]>().unwrap();
+			let mut lock = query.lock(&multi);
+			assert_eq!(lock.column::<u64>(group), Some(&expected[..]));
+			let entity = entities.valid(raws[7]).unwrap();
+			*lock.get_all(entity).unwrap().0 = 70;
+		}
+		// Dropping the table unmaps the files, what was written stays in them
+		drop((multi_storage, database));
+		let storage = unsafe { MmapDensePagedData::<u64>::open(&directory) }.unwrap();
+		assert_eq!(storage.group_count(), 1);
+		assert_eq!(storage.group(0)[6], 70);
+		assert_eq!(storage.group(0)[999_998], 999_999);
+		assert_eq!(storage.group(0).len(), 999_999);
+		std::fs::remove_dir_all(&directory).unwrap();
+	}
+}
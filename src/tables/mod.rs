@@ -1,9 +1,17 @@
 pub mod dense_entity_dynamic_paged_multi_value_table;
 pub mod dense_entity_value_table;
 pub mod entity_table;
+#[cfg(feature = "mmap")]
+pub mod mmap_dense_paged_data;
+pub mod relation_table;
+#[cfg(feature = "threadsafe")]
+pub mod sync_dense_paged_data;
+pub mod ttl_table;
 pub mod vec_entity_value_table;
 
 pub use dense_entity_dynamic_paged_multi_value_table::*;
 pub use dense_entity_value_table::DenseEntityValueTable;
 pub use entity_table::{EntityTable, ValidEntity};
+pub use relation_table::RelationTable;
+pub use ttl_table::TtlTable;
 pub use vec_entity_value_table::VecEntityValueTable;
@@ -0,0 +1,391 @@
+use crate::database::{DatabaseId, TableId};
+use crate::entity::Entity;
+use crate::table::{Table, TableBuilder, TableCastable};
+use crate::tables::entity_table::{EntityTable, ValidEntity};
+use crate::utils::secondary_entity_index::{SecondaryEntityIndex, SecondaryEntityIndexErrors};
+use smallvec::SmallVec;
+use smol_str::SmolStr;
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RelationTableErrors<EntityType: Entity> {
+	/// Making the second entity the parent of the first would make the first its own ancestor.
+	WouldCreateCycle(EntityType, EntityType),
+	/// The entity was handed out by a different `EntityTable` than the one this table is built on.
+	EntityTableMismatch(EntityType),
+}
+
+impl<EntityType: Entity> std::error::Error for RelationTableErrors<EntityType> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		use RelationTableErrors::*;
+		match self {
+			WouldCreateCycle(_child, _parent) => None,
+			EntityTableMismatch(_entity) => None,
+		}
+	}
+}
+
+impl<EntityType: Entity> std::fmt::Display for RelationTableErrors<EntityType> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+		use RelationTableErrors::*;
+		match self {
+			WouldCreateCycle(child, parent) => write!(
+				f,
+				"Setting the parent of {:?} to {:?} would create a cycle",
+				child, parent
+			),
+			EntityTableMismatch(entity) => {
+				write!(f, "Entity is from a different EntityTable: {:?}", entity)
+			}
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Relation<EntityType: Entity> {
+	parent: Option<EntityType>,
+	children: SmallVec<[EntityType; 4]>,
+}
+
+impl<EntityType: Entity> Default for Relation<EntityType> {
+	fn default() -> Self {
+		Relation {
+			parent: None,
+			children: SmallVec::new(),
+		}
+	}
+}
+
+/// Parent and child relations between the entities of an `EntityTable`, forming a forest.
+///
+/// When an entity is deleted its children are reparented to its own parent, or orphaned if it had
+/// none.
+pub struct RelationTable<EntityType: Entity> {
+	this: Weak<RefCell<Self>>,
+	database_id: DatabaseId,
+	table_name: SmolStr,
+	table_id: TableId,
+	entity_table: Weak<RefCell<EntityTable<EntityType>>>,
+	world_id: u32,
+	reverse: SecondaryEntityIndex<EntityType, usize>,
+	entities: Vec<EntityType>,
+	relations: Vec<Relation<EntityType>>,
+}
+
+impl<EntityType: Entity> RelationTable<EntityType> {
+	pub fn builder(
+		entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+	) -> RelationTableBuilder<EntityType> {
+		RelationTableBuilder {
+			entity_table,
+			capacity: 0,
+		}
+	}
+
+	pub fn builder_with_capacity(
+		entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+		capacity: usize,
+	) -> RelationTableBuilder<EntityType> {
+		RelationTableBuilder {
+			entity_table,
+			capacity,
+		}
+	}
+
+	fn location(&self, entity: EntityType) -> Option<usize> {
+		match self.reverse.get(entity) {
+			Ok(&location) if self.entities[location] == entity => Some(location),
+			_ => None,
+		}
+	}
+
+	fn location_or_insert(&mut self, entity: EntityType) -> usize {
+		let entities = &mut self.entities;
+		let relations = &mut self.relations;
+		let location = *self.reverse.get_or_insert_with(entity, || {
+			entities.push(entity);
+			relations.push(Relation::default());
+			entities.len() - 1
+		});
+		if self.entities[location] != entity {
+			// Left by an older generation of the entity
+			self.entities[location] = entity;
+			self.relations[location] = Relation::default();
+		}
+		location
+	}
+
+	pub fn contains(&self, entity: EntityType) -> bool {
+		self.location(entity).is_some()
+	}
+
+	pub fn len(&self) -> usize {
+		self.entities.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entities.is_empty()
+	}
+
+	pub fn parent_of(&self, entity: EntityType) -> Option<EntityType> {
+		self.location(entity)
+			.and_then(|location| self.relations[location].parent)
+	}
+
+	pub fn children_of(&self, entity: EntityType) -> &[EntityType] {
+		self.location(entity)
+			.map_or(&[][..], |location| &self.relations[location].children[..])
+	}
+
+	/// Makes `parent` the parent of `child`, detaching it from its previous parent, or detaches it
+	/// from its parent when `parent` is `None`.
+	///
+	/// Errors with `WouldCreateCycle` if `child` is `parent` or one of its ancestors, or with
+	/// `EntityTableMismatch` if either is from a different `EntityTable`.
+	pub fn set_parent(
+		&mut self,
+		child: ValidEntity<EntityType>,
+		parent: Option<ValidEntity<EntityType>>,
+	) -> Result<(), RelationTableErrors<EntityType>> {
+		for entity in std::iter::once(child).chain(parent) {
+			if entity.world_id() != self.world_id {
+				return Err(RelationTableErrors::EntityTableMismatch(entity.raw()));
+			}
+		}
+		let child = child.raw();
+		let parent = parent.map(|parent| parent.raw());
+		let mut ancestor = parent;
+		while let Some(entity) = ancestor {
+			if entity == child {
+				return Err(RelationTableErrors::WouldCreateCycle(
+					child,
+					parent.unwrap(),
+				));
+			}
+			ancestor = self.parent_of(entity);
+		}
+		let child_location = self.location_or_insert(child);
+		if let Some(old_parent) = self.relations[child_location].parent.take() {
+			let old_location = self
+				.location(old_parent)
+				.expect("reverse mapping is in invalid state with RelationTable");
+			self.relations[old_location]
+				.children
+				.retain(|sibling| *sibling != child);
+		}
+		if let Some(parent) = parent {
+			let parent_location = self.location_or_insert(parent);
+			self.relations[parent_location].children.push(child);
+			self.relations[child_location].parent = Some(parent);
+		}
+		Ok(())
+	}
+
+	/// Removes `entity` from the relations, its children are reparented to its parent, or orphaned if
+	/// it has none.
+	pub fn delete(
+		&mut self,
+		entity: EntityType,
+	) -> Result<(), SecondaryEntityIndexErrors<EntityType>> {
+		let location_mut = self.reverse.get_mut(entity)?;
+		if self.entities[*location_mut] != entity {
+			return Err(SecondaryEntityIndexErrors::IndexDoesNotExist(entity));
+		}
+		let location = *location_mut;
+		*location_mut = usize::MAX;
+		self.entities.swap_remove(location);
+		let relation = self.relations.swap_remove(location);
+		if self.entities.len() > location {
+			let moved = self
+				.reverse
+				.get_mut(self.entities[location])
+				.expect("reverse mapping is in invalid state with RelationTable");
+			*moved = location
+		}
+		let parent_location = relation.parent.map(|parent| {
+			self.location(parent)
+				.expect("reverse mapping is in invalid state with RelationTable")
+		});
+		if let Some(parent_location) = parent_location {
+			self.relations[parent_location]
+				.children
+				.retain(|sibling| *sibling != entity);
+		}
+		for child in relation.children {
+			let child_location = self
+				.location(child)
+				.expect("reverse mapping is in invalid state with RelationTable");
+			self.relations[child_location].parent = relation.parent;
+			if let Some(parent_location) = parent_location {
+				self.relations[parent_location].children.push(child);
+			}
+		}
+		Ok(())
+	}
+}
+
+pub struct RelationTableBuilder<EntityType: Entity> {
+	entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+	capacity: usize,
+}
+
+impl<EntityType: Entity> TableBuilder for RelationTableBuilder<EntityType> {
+	type Table = RelationTable<EntityType>;
+
+	fn build(
+		self,
+		database_id: DatabaseId,
+		table_name: &str,
+		table_id: TableId,
+	) -> Rc<RefCell<Self::Table>> {
+		let mut entities = self.entity_table.borrow_mut();
+		let this = Rc::new(RefCell::new(RelationTable::<EntityType> {
+			this: Weak::new(),
+			database_id,
+			table_name: table_name.into(),
+			table_id,
+			entity_table: Rc::downgrade(&self.entity_table),
+			world_id: entities.world_id(),
+			reverse: SecondaryEntityIndex::new(usize::MAX),
+			entities: Vec::with_capacity(self.capacity),
+			relations: Vec::with_capacity(self.capacity),
+		}));
+		this.borrow_mut().this = Rc::downgrade(&this);
+		let another_this = Rc::downgrade(&this);
+		let _id = entities.on_delete_entity(Box::new(move |_entity_table_id, entity| {
+			let another_this = match another_this.upgrade() {
+				Some(another_this) => another_this,
+				None => return,
+			};
+			if let Ok(mut deleter) = another_this.try_borrow_mut() {
+				// Don't care if it didn't exist
+				let _ = deleter.delete(entity.raw());
+			} else {
+				panic!("RelationTable<{}> already locked while deleting an entity, all tables must be free when deleting an Entity", std::any::type_name::<EntityType>());
+			};
+		}));
+		this
+	}
+}
+
+impl<EntityType: Entity> Table for RelationTable<EntityType> {
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn get_strong(&self) -> Rc<RefCell<dyn Table>> {
+		self.get_strong_self()
+	}
+
+	fn get_database_id(&self) -> DatabaseId {
+		self.database_id
+	}
+
+	fn table_name(&self) -> &str {
+		&self.table_name
+	}
+
+	fn table_id(&self) -> TableId {
+		self.table_id
+	}
+
+	fn rename(&mut self, new_name: &str) -> bool {
+		self.table_name = new_name.into();
+		true
+	}
+
+	fn rehome(&mut self, database_id: DatabaseId, table_id: TableId) -> bool {
+		self.database_id = database_id;
+		self.table_id = table_id;
+		true
+	}
+
+	fn entity_table(&self) -> Option<Rc<RefCell<dyn Table>>> {
+		self.entity_table
+			.upgrade()
+			.map(|entity_table| entity_table as Rc<RefCell<dyn Table>>)
+	}
+
+	fn clear_table(&mut self) {
+		self.reverse = SecondaryEntityIndex::new(usize::MAX);
+		self.entities.clear();
+		self.relations.clear();
+	}
+}
+
+impl<EntityType: Entity> TableCastable for RelationTable<EntityType> {
+	fn get_strong_self(&self) -> Rc<RefCell<Self>> {
+		self.this.upgrade().unwrap() // It's obviously valid since it's obviously self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::database::*;
+	use crate::tables::entity_table::EntityTable;
+	use crate::tables::relation_table::*;
+
+	#[test]
+	fn delete_reparents_children() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let relations_storage = database
+			.tables
+			.create(
+				"relations",
+				RelationTable::<u64>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let root = entities.insert().raw();
+		let middle = entities.insert().raw();
+		let first = entities.insert().raw();
+		let second = entities.insert().raw();
+		let orphan = entities.insert().raw();
+		{
+			let mut relations = relations_storage.borrow_mut();
+			let valid = |entity| entities.valid(entity).unwrap();
+			relations
+				.set_parent(valid(middle), Some(valid(root)))
+				.unwrap();
+			relations
+				.set_parent(valid(first), Some(valid(middle)))
+				.unwrap();
+			relations
+				.set_parent(valid(second), Some(valid(middle)))
+				.unwrap();
+			relations
+				.set_parent(valid(orphan), Some(valid(first)))
+				.unwrap();
+			assert_eq!(
+				relations.set_parent(valid(root), Some(valid(orphan))),
+				Err(RelationTableErrors::WouldCreateCycle(root, orphan))
+			);
+			assert_eq!(
+				relations.set_parent(valid(root), Some(valid(root))),
+				Err(RelationTableErrors::WouldCreateCycle(root, root))
+			);
+			assert_eq!(relations.children_of(middle), &[first, second]);
+			assert_eq!(relations.parent_of(orphan), Some(first));
+		}
+		entities.delete(middle).unwrap();
+		{
+			let relations = relations_storage.borrow();
+			assert!(!relations.contains(middle));
+			assert_eq!(relations.children_of(root), &[first, second]);
+			assert_eq!(relations.parent_of(first), Some(root));
+			assert_eq!(relations.parent_of(orphan), Some(first));
+		}
+		entities.delete(root).unwrap();
+		let relations = relations_storage.borrow();
+		assert_eq!(relations.parent_of(first), None);
+		assert_eq!(relations.parent_of(second), None);
+		assert_eq!(relations.children_of(first), &[orphan]);
+		assert_eq!(relations.len(), 3);
+	}
+}
@@ -0,0 +1,112 @@
+use crate::tables::dense_entity_dynamic_paged_multi_value_table::{
+	ColumnStorage, DensePagedColumns, HeapPagedColumns,
+};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// The `Send + Sync` counterpart of `DensePagedData`, shared through an `Arc<RwLock<_>>` instead
+/// of an `Rc<RefCell<_>>` so many threads can read the same storage at once for read-mostly
+/// workloads.
+///
+/// Its untyped side is the same `DynDensePagedData`, as an
+/// `Arc<RwLock<dyn DynDensePagedData + Send + Sync>>`.
+pub struct SyncDensePagedData<ValueType: Send + Sync + 'static> {
+	idx: usize,
+	data: HeapPagedColumns<ValueType>,
+}
+
+impl<ValueType: Send + Sync + 'static> SyncDensePagedData<ValueType> {
+	pub fn new(idx: usize) -> Arc<RwLock<Self>> {
+		Arc::new(RwLock::new(SyncDensePagedData {
+			idx,
+			data: HeapPagedColumns(vec![]),
+		}))
+	}
+
+	pub fn push(&mut self, group: usize, data: ValueType) {
+		self.data.push(group, data);
+	}
+
+	pub fn extend(&mut self, group: usize, data: impl IntoIterator<Item = ValueType>) {
+		self.data.0[group].extend(data);
+	}
+
+	pub fn get(&self, group: usize, index: usize) -> Option<&ValueType> {
+		self.data.0.get(group)?.get(index)
+	}
+
+	pub fn get_mut(&mut self, group: usize, index: usize) -> Option<&mut ValueType> {
+		self.data.0.get_mut(group)?.get_mut(index)
+	}
+
+	/// Returns the contiguous values of `group`.
+	pub fn group(&self, group: usize) -> &[ValueType] {
+		self.data.group(group)
+	}
+
+	/// Same as `group` but mutable.
+	pub fn group_mut(&mut self, group: usize) -> &mut [ValueType] {
+		self.data.group_mut(group)
+	}
+}
+
+impl<ValueType: Send + Sync + 'static> ColumnStorage for SyncDensePagedData<ValueType> {
+	type Value = ValueType;
+	type Columns = HeapPagedColumns<ValueType>;
+
+	fn idx(&self) -> usize {
+		self.idx
+	}
+
+	fn columns(&self) -> &Self::Columns {
+		&self.data
+	}
+
+	fn columns_mut(&mut self) -> &mut Self::Columns {
+		&mut self.data
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tables::dense_entity_dynamic_paged_multi_value_table::{
+		DeletePolicy, DynDensePagedData,
+	};
+	use crate::tables::sync_dense_paged_data::*;
+
+	#[test]
+	fn concurrent_reads() {
+		let storage = SyncDensePagedData::<usize>::new(0);
+		{
+			let mut storage = storage.write();
+			storage.ensure_group_count(2);
+			storage.extend(1, 0..1000);
+		}
+		let dyn_storage: Arc<RwLock<dyn DynDensePagedData + Send + Sync>> = storage.clone();
+		let readers: Vec<_> = (0..4)
+			.map(|i| {
+				let storage = storage.clone();
+				let dyn_storage = dyn_storage.clone();
+				std::thread::spawn(move || {
+					let dyn_storage = dyn_storage.read();
+					assert_eq!(dyn_storage.group_len(1), 1000);
+					assert_eq!(
+						dyn_storage
+							.get_any(1, i)
+							.and_then(|value| value.downcast_ref::<usize>()),
+						Some(&i)
+					);
+					storage.read().group(1).iter().sum::<usize>()
+				})
+			})
+			.collect();
+		for reader in readers {
+			assert_eq!(reader.join().unwrap(), 499500);
+		}
+		storage.write().swap_remove(1, 0);
+		assert_eq!(storage.read().get(1, 0), Some(&999));
+		dyn_storage.write().remove(1, 0, DeletePolicy::ShiftRemove);
+		assert_eq!(storage.read().get(1, 0), Some(&1));
+		assert!(dyn_storage.read().heap_bytes() >= 998 * std::mem::size_of::<usize>());
+	}
+}
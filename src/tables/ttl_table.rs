@@ -0,0 +1,281 @@
+use crate::database::{DatabaseId, TableId};
+use crate::entity::Entity;
+use crate::table::{Table, TableBuilder, TableCastable};
+use crate::tables::entity_table::{EntityTable, ValidEntity};
+use crate::utils::secondary_entity_index::{SecondaryEntityIndex, SecondaryEntityIndexErrors};
+use smol_str::SmolStr;
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// How many ticks each entity of an `EntityTable` has left to live, every `tick` counts them down
+/// and deletes the entities whose time ran out.
+pub struct TtlTable<EntityType: Entity> {
+	this: Weak<RefCell<Self>>,
+	database_id: DatabaseId,
+	table_name: SmolStr,
+	table_id: TableId,
+	entity_table: Weak<RefCell<EntityTable<EntityType>>>,
+	world_id: u32,
+	reverse: SecondaryEntityIndex<EntityType, usize>,
+	entities: Vec<EntityType>,
+	ttls: Vec<u32>,
+}
+
+impl<EntityType: Entity> TtlTable<EntityType> {
+	pub fn builder(
+		entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+	) -> TtlTableBuilder<EntityType> {
+		TtlTableBuilder {
+			entity_table,
+			capacity: 0,
+		}
+	}
+
+	pub fn builder_with_capacity(
+		entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+		capacity: usize,
+	) -> TtlTableBuilder<EntityType> {
+		TtlTableBuilder {
+			entity_table,
+			capacity,
+		}
+	}
+
+	fn location(&self, entity: EntityType) -> Option<usize> {
+		match self.reverse.get(entity) {
+			Ok(&location) if self.entities[location] == entity => Some(location),
+			_ => None,
+		}
+	}
+
+	pub fn contains(&self, entity: EntityType) -> bool {
+		self.location(entity).is_some()
+	}
+
+	pub fn len(&self) -> usize {
+		self.entities.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entities.is_empty()
+	}
+
+	/// Returns how many more ticks `entity` lives for, or `None` if it has no ttl.
+	pub fn get_ttl(&self, entity: EntityType) -> Option<u32> {
+		self.location(entity).map(|location| self.ttls[location])
+	}
+
+	/// Deletes `entity` on the `ticks`th call of `tick` from now, replacing any ttl it already has.
+	/// A ttl of 0 is deleted on the next tick, same as 1.
+	///
+	/// Errors with `EntityTableMismatch` if `entity` is from a different `EntityTable`.
+	pub fn set_ttl(
+		&mut self,
+		entity: ValidEntity<EntityType>,
+		ticks: u32,
+	) -> Result<(), SecondaryEntityIndexErrors<EntityType>> {
+		let entity = entity.raw_in(self.world_id)?;
+		let entities = &mut self.entities;
+		let ttls = &mut self.ttls;
+		let location = *self.reverse.get_or_insert_with(entity, || {
+			entities.push(entity);
+			ttls.push(ticks);
+			entities.len() - 1
+		});
+		// Also replaces an entry left by an older generation of the entity
+		self.entities[location] = entity;
+		self.ttls[location] = ticks;
+		Ok(())
+	}
+
+	/// Removes the ttl of `entity` so it lives until deleted otherwise.
+	pub fn clear_ttl(
+		&mut self,
+		entity: EntityType,
+	) -> Result<(), SecondaryEntityIndexErrors<EntityType>> {
+		let location_mut = self.reverse.get_mut(entity)?;
+		if self.entities[*location_mut] != entity {
+			return Err(SecondaryEntityIndexErrors::IndexDoesNotExist(entity));
+		}
+		let location = *location_mut;
+		*location_mut = usize::MAX;
+		self.remove_location(location);
+		Ok(())
+	}
+
+	fn remove_location(&mut self, location: usize) {
+		self.entities.swap_remove(location);
+		self.ttls.swap_remove(location);
+		if self.entities.len() > location {
+			let moved = self
+				.reverse
+				.get_mut(self.entities[location])
+				.expect("reverse mapping is in invalid state with TtlTable");
+			*moved = location
+		}
+	}
+
+	/// Counts down the ttl of every entity and deletes those reaching 0 from `entity_table`, which
+	/// runs its delete callbacks so every other table drops them as well.
+	pub fn tick(&mut self, entity_table: &mut EntityTable<EntityType>) {
+		let mut expired = Vec::new();
+		// In reverse so the swap-removes only move already visited entities
+		for location in (0..self.entities.len()).rev() {
+			self.ttls[location] = self.ttls[location].saturating_sub(1);
+			if self.ttls[location] == 0 {
+				let entity = self.entities[location];
+				*self
+					.reverse
+					.get_mut(entity)
+					.expect("reverse mapping is in invalid state with TtlTable") = usize::MAX;
+				self.remove_location(location);
+				expired.push(entity);
+			}
+		}
+		for entity in expired {
+			// An entity deleted while this table was locked is already dead, that's fine
+			let _ = entity_table.delete(entity);
+		}
+	}
+}
+
+pub struct TtlTableBuilder<EntityType: Entity> {
+	entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+	capacity: usize,
+}
+
+impl<EntityType: Entity> TableBuilder for TtlTableBuilder<EntityType> {
+	type Table = TtlTable<EntityType>;
+
+	fn build(
+		self,
+		database_id: DatabaseId,
+		table_name: &str,
+		table_id: TableId,
+	) -> Rc<RefCell<Self::Table>> {
+		let mut entities = self.entity_table.borrow_mut();
+		let this = Rc::new(RefCell::new(TtlTable::<EntityType> {
+			this: Weak::new(),
+			database_id,
+			table_name: table_name.into(),
+			table_id,
+			entity_table: Rc::downgrade(&self.entity_table),
+			world_id: entities.world_id(),
+			reverse: SecondaryEntityIndex::new(usize::MAX),
+			entities: Vec::with_capacity(self.capacity),
+			ttls: Vec::with_capacity(self.capacity),
+		}));
+		this.borrow_mut().this = Rc::downgrade(&this);
+		let another_this = Rc::downgrade(&this);
+		let _id = entities.on_delete_entity(Box::new(move |_entity_table_id, entity| {
+			let another_this = match another_this.upgrade() {
+				Some(another_this) => another_this,
+				None => return,
+			};
+			// Locked while `tick` deletes the entities it already removed, an entry left any other
+			// way is only of a dead entity so `set_ttl` replaces it and `tick` ignores it
+			if let Ok(mut deleter) = another_this.try_borrow_mut() {
+				// Don't care if it didn't exist
+				let _ = deleter.clear_ttl(entity.raw());
+			};
+		}));
+		this
+	}
+}
+
+impl<EntityType: Entity> Table for TtlTable<EntityType> {
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn get_strong(&self) -> Rc<RefCell<dyn Table>> {
+		self.get_strong_self()
+	}
+
+	fn get_database_id(&self) -> DatabaseId {
+		self.database_id
+	}
+
+	fn table_name(&self) -> &str {
+		&self.table_name
+	}
+
+	fn table_id(&self) -> TableId {
+		self.table_id
+	}
+
+	fn rename(&mut self, new_name: &str) -> bool {
+		self.table_name = new_name.into();
+		true
+	}
+
+	fn rehome(&mut self, database_id: DatabaseId, table_id: TableId) -> bool {
+		self.database_id = database_id;
+		self.table_id = table_id;
+		true
+	}
+
+	fn entity_table(&self) -> Option<Rc<RefCell<dyn Table>>> {
+		self.entity_table
+			.upgrade()
+			.map(|entity_table| entity_table as Rc<RefCell<dyn Table>>)
+	}
+
+	fn clear_table(&mut self) {
+		self.reverse = SecondaryEntityIndex::new(usize::MAX);
+		self.entities.clear();
+		self.ttls.clear();
+	}
+}
+
+impl<EntityType: Entity> TableCastable for TtlTable<EntityType> {
+	fn get_strong_self(&self) -> Rc<RefCell<Self>> {
+		self.this.upgrade().unwrap() // It's obviously valid since it's obviously self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::database::*;
+	use crate::tables::entity_table::EntityTable;
+	use crate::tables::ttl_table::*;
+
+	#[test]
+	fn tick_deletes_expired() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let ttls_storage = database
+			.tables
+			.create("ttls", TtlTable::<u64>::builder(entities_storage.clone()))
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut ttls = ttls_storage.borrow_mut();
+		let short = entities.insert().raw();
+		let long = entities.insert().raw();
+		let cleared = entities.insert().raw();
+		ttls.set_ttl(entities.valid(short).unwrap(), 3).unwrap();
+		ttls.set_ttl(entities.valid(long).unwrap(), 5).unwrap();
+		ttls.set_ttl(entities.valid(cleared).unwrap(), 1).unwrap();
+		ttls.clear_ttl(cleared).unwrap();
+		for ticks_left in (1..3).rev() {
+			ttls.tick(&mut entities);
+			assert!(entities.contains(short));
+			assert_eq!(ttls.get_ttl(short), Some(ticks_left));
+		}
+		ttls.tick(&mut entities);
+		assert!(!entities.contains(short));
+		assert!(!ttls.contains(short));
+		assert!(entities.contains(long));
+		assert!(entities.contains(cleared));
+		assert_eq!(ttls.get_ttl(long), Some(2));
+		assert_eq!(ttls.len(), 1);
+		// Deleting elsewhere drops the ttl
+		drop(ttls);
+		entities.delete(long).unwrap();
+		assert!(ttls_storage.borrow().is_empty());
+	}
+}
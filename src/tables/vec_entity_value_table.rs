@@ -14,9 +14,13 @@ pub struct VecEntityValueTable<EntityType: Entity, ValueType: 'static> {
 	database_id: DatabaseId,
 	table_name: SmolStr,
 	table_id: TableId,
+	entity_table: Weak<RefCell<EntityTable<EntityType>>>,
+	world_id: u32,
 	entities: Vec<EntityType>,
 	values: Vec<MaybeUninit<ValueType>>,
 	count: usize,
+	/// Creates the value of every slot without an entity, see `builder_filled`.
+	fill: Option<Box<dyn Fn() -> ValueType>>,
 }
 
 impl<EntityType: Entity, ValueType: 'static> VecEntityValueTable<EntityType, ValueType> {
@@ -26,6 +30,7 @@ impl<EntityType: Entity, ValueType: 'static> VecEntityValueTable<EntityType, Val
 		VecEntityValueTableBuilder {
 			entity_table,
 			capacity: 0,
+			fill: None,
 			_phantom: PhantomData,
 		}
 	}
@@ -37,6 +42,25 @@ impl<EntityType: Entity, ValueType: 'static> VecEntityValueTable<EntityType, Val
 		VecEntityValueTableBuilder {
 			entity_table,
 			capacity,
+			fill: None,
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Every slot without an entity of its own holds a clone of `default` instead of being a gap,
+	/// so `get` returns `default` for any entity whose index has been allocated, such as for ambient
+	/// values that most entities share.
+	pub fn builder_filled(
+		entity_table: Rc<RefCell<EntityTable<EntityType>>>,
+		default: ValueType,
+	) -> VecEntityValueTableBuilder<EntityType, ValueType>
+	where
+		ValueType: Clone,
+	{
+		VecEntityValueTableBuilder {
+			entity_table,
+			capacity: 0,
+			fill: Some(Box::new(move || default.clone())),
 			_phantom: PhantomData,
 		}
 	}
@@ -53,13 +77,34 @@ impl<EntityType: Entity, ValueType: 'static> VecEntityValueTable<EntityType, Val
 		self.count == 0
 	}
 
+	/// Returns the value of `entity`, or the fill value of its slot if the table was built with
+	/// `builder_filled` and no entity holds that slot.
+	pub fn get(&self, entity: EntityType) -> Option<&ValueType> {
+		if self.entities.len() <= entity.idx() {
+			return None;
+		}
+		let slot_entity = self.entities[entity.idx()];
+		if slot_entity == entity || (self.fill.is_some() && !slot_entity.is_valid()) {
+			Some(unsafe { &*self.values[entity.idx()].as_ptr() })
+		} else {
+			None
+		}
+	}
+
+	/// Errors if `entity` already has a value or is from a different `EntityTable`.
 	pub fn insert(&mut self, entity: ValidEntity<EntityType>, value: ValueType) -> Result<(), ()> {
-		let entity = entity.raw();
+		let entity = entity.raw_in(self.world_id).map_err(|_| ())?;
 		if self.entities.len() <= entity.idx() {
-			self.entities.resize(entity.idx() + 1, EntityType::new(0));
-			self.values.reserve(entity.idx() - self.values.len() + 1);
-			unsafe {
-				self.values.set_len(entity.idx() + 1);
+			self.entities
+				.resize(entity.idx() + 1, EntityType::invalid());
+			if let Some(fill) = &self.fill {
+				let values = &mut self.values;
+				values.resize_with(entity.idx() + 1, || MaybeUninit::new(fill()));
+			} else {
+				self.values.reserve(entity.idx() - self.values.len() + 1);
+				unsafe {
+					self.values.set_len(entity.idx() + 1);
+				}
 			}
 		}
 		if self.entities[entity.idx()] == entity {
@@ -77,12 +122,15 @@ impl<EntityType: Entity, ValueType: 'static> VecEntityValueTable<EntityType, Val
 		if self.entities.len() <= entity.idx() || self.entities[entity.idx()] != entity {
 			return Err(());
 		}
-		self.entities[entity.idx()] = EntityType::new(0);
+		self.entities[entity.idx()] = EntityType::invalid();
 		unsafe {
 			// Can remove this and just forget about `self.values` if we can ensure it doesn't have a `Drop` implementation
 			let mut forgetting = MaybeUninit::uninit();
 			std::mem::swap(self.values.get_unchecked_mut(entity.idx()), &mut forgetting);
 		}
+		if let Some(fill) = &self.fill {
+			self.values[entity.idx()] = MaybeUninit::new(fill());
+		}
 		self.count -= 1;
 		Ok(())
 	}
@@ -91,6 +139,7 @@ impl<EntityType: Entity, ValueType: 'static> VecEntityValueTable<EntityType, Val
 pub struct VecEntityValueTableBuilder<EntityType: Entity, ValueType: 'static> {
 	entity_table: Rc<RefCell<EntityTable<EntityType>>>,
 	capacity: usize,
+	fill: Option<Box<dyn Fn() -> ValueType>>,
 	_phantom: PhantomData<ValueType>,
 }
 
@@ -111,13 +160,20 @@ impl<EntityType: Entity, ValueType: 'static> TableBuilder
 			database_id,
 			table_name: table_name.into(),
 			table_id,
+			entity_table: Rc::downgrade(&self.entity_table),
+			world_id: entities.world_id(),
 			entities: Vec::with_capacity(self.capacity),
 			values: Vec::with_capacity(self.capacity),
 			count: 0,
+			fill: self.fill,
 		}));
 		this.borrow_mut().this = Rc::downgrade(&this);
-		let another_this = this.clone();
+		let another_this = Rc::downgrade(&this);
 		let _id = entities.on_delete_entity(Box::new(move |_entity_table_id, entity| {
+			let another_this = match another_this.upgrade() {
+				Some(another_this) => another_this,
+				None => return,
+			};
 			if let Ok(mut deleter) = another_this.try_borrow_mut() {
 				// Don't care if it didn't exist
 				let _ = deleter.delete(entity.raw()); // .expect("Unknown deletion error while deleting valid entity")
@@ -149,6 +205,30 @@ impl<EntityType: Entity, ValueType: 'static> Table for VecEntityValueTable<Entit
 	fn table_id(&self) -> TableId {
 		self.table_id
 	}
+
+	fn rename(&mut self, new_name: &str) -> bool {
+		self.table_name = new_name.into();
+		true
+	}
+
+	fn rehome(&mut self, database_id: DatabaseId, table_id: TableId) -> bool {
+		self.database_id = database_id;
+		self.table_id = table_id;
+		true
+	}
+
+	fn entity_table(&self) -> Option<Rc<RefCell<dyn Table>>> {
+		self.entity_table
+			.upgrade()
+			.map(|entity_table| entity_table as Rc<RefCell<dyn Table>>)
+	}
+
+	fn clear_table(&mut self) {
+		// Same as `delete`, the `MaybeUninit` values are forgotten rather than dropped
+		self.entities.clear();
+		self.values.clear();
+		self.count = 0;
+	}
 }
 
 impl<EntityType: Entity, ValueType: 'static> TableCastable
@@ -158,3 +238,45 @@ impl<EntityType: Entity, ValueType: 'static> TableCastable
 		self.this.upgrade().unwrap() // It's obviously valid since it's obviously self
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::database::*;
+	use crate::tables::entity_table::EntityTable;
+	use crate::tables::vec_entity_value_table::*;
+
+	#[test]
+	fn builder_filled() {
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let values_storage = database
+			.tables
+			.create(
+				"values",
+				VecEntityValueTable::<u64, u32>::builder_filled(entities_storage.clone(), 42),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let entity_vec: Vec<_> = entities.extend_iter().take(6).map(|e| e.raw()).collect();
+		let mut values = values_storage.borrow_mut();
+		values
+			.insert(entities.valid(entity_vec[5]).unwrap(), 5)
+			.unwrap();
+		assert_eq!(values.len(), 1);
+		assert_eq!(values.get(entity_vec[5]), Some(&5));
+		for &entity in entity_vec[..5].iter() {
+			assert!(!values.contains(entity));
+			assert_eq!(values.get(entity), Some(&42));
+		}
+		drop(values);
+		entities.delete(entity_vec[5]).unwrap();
+		let values = values_storage.borrow();
+		assert_eq!(values.len(), 0);
+		assert_eq!(values.get(entity_vec[5]), Some(&42));
+		let reused = entities.insert().raw();
+		assert_eq!(values.get(reused), Some(&42));
+	}
+}
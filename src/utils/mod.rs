@@ -1,2 +1,4 @@
 pub mod secondary_entity_index;
 pub mod unique_hasher;
+#[cfg(feature = "unsafe-borrowed-components")]
+pub mod unsafe_borrowed_component;
@@ -7,6 +7,9 @@ const PER_PAGE: usize = u8::MAX as usize + 1; // 256; // Should only have a sing
 pub enum SecondaryEntityIndexErrors<EntityType: Entity> {
 	IndexAlreadyExists(EntityType),
 	IndexDoesNotExist(EntityType),
+	/// The `ValidEntity` was handed out by a different `EntityTable` than the one the table is built
+	/// on.
+	EntityTableMismatch(EntityType),
 }
 
 impl<EntityType: Entity> std::error::Error for SecondaryEntityIndexErrors<EntityType> {
@@ -15,6 +18,7 @@ impl<EntityType: Entity> std::error::Error for SecondaryEntityIndexErrors<Entity
 		match self {
 			IndexAlreadyExists(_entity) => None,
 			IndexDoesNotExist(_entity) => None,
+			EntityTableMismatch(_entity) => None,
 		}
 	}
 }
@@ -25,6 +29,9 @@ impl<EntityType: Entity> std::fmt::Display for SecondaryEntityIndexErrors<Entity
 		match self {
 			IndexAlreadyExists(entity) => write!(f, "Index already exists: {:?}", entity),
 			IndexDoesNotExist(entity) => write!(f, "Index does not exist: {:?}", entity),
+			EntityTableMismatch(entity) => {
+				write!(f, "Entity is from a different EntityTable: {:?}", entity)
+			}
 		}
 	}
 }
@@ -80,6 +87,29 @@ impl<EntityType: Entity, IndexType: Copy + PartialEq> SecondaryEntityIndex<Entit
 		Ok(location)
 	}
 
+	/// Returns the index for `entity`, first setting it to the result of `f` if it is still the
+	/// invalid index, allocating the page if needed.
+	pub fn get_or_insert_with(
+		&mut self,
+		entity: EntityType,
+		f: impl FnOnce() -> IndexType,
+	) -> &mut IndexType {
+		let (page_idx, offset) = Self::page_offset(entity);
+
+		if page_idx >= self.pages.len() {
+			self.pages.resize(page_idx + 1, None);
+		}
+		let invalid_index = &self.invalid_index;
+		let page = self.pages[page_idx].get_or_insert_with(|| Box::new([*invalid_index; PER_PAGE]));
+
+		let location = &mut page[offset as usize];
+		if *location == self.invalid_index {
+			*location = f();
+		}
+
+		location
+	}
+
 	pub fn get(
 		&self,
 		entity: EntityType,
@@ -126,6 +156,27 @@ impl<EntityType: Entity, IndexType: Copy + PartialEq> SecondaryEntityIndex<Entit
 		Ok(location)
 	}
 
+	/// Iterates every entity that has an index that is not the invalid index, in ascending order of
+	/// the entity index.
+	///
+	/// Only the entity index is stored so the yielded entities always have a version of 0, look them
+	/// up in their `EntityTable` to get the live entity.
+	pub fn iter(&self) -> impl Iterator<Item = (EntityType, &IndexType)> {
+		let invalid_index = self.invalid_index;
+		self.pages
+			.iter()
+			.enumerate()
+			.filter_map(|(page_idx, page)| page.as_ref().map(|page| (page_idx, page)))
+			.flat_map(move |(page_idx, page)| {
+				page.iter()
+					.enumerate()
+					.filter(move |(_offset, location)| **location != invalid_index)
+					.map(move |(offset, location)| {
+						(EntityType::new(page_idx * PER_PAGE + offset), location)
+					})
+			})
+	}
+
 	// pub fn remove(
 	// 	&mut self,
 	// 	entity: EntityType,
@@ -158,3 +209,50 @@ impl<EntityType: Entity, IndexType: Copy + PartialEq> SecondaryEntityIndex<Entit
 	// 	}
 	// }
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn get_or_insert_with() {
+		let mut index = SecondaryEntityIndex::<u32, usize>::new(usize::MAX);
+		let mut calls = 0;
+		assert_eq!(
+			*index.get_or_insert_with(1000, || {
+				calls += 1;
+				42
+			}),
+			42
+		);
+		assert_eq!(
+			*index.get_or_insert_with(1000, || {
+				calls += 1;
+				21
+			}),
+			42
+		);
+		assert_eq!(calls, 1);
+		*index.get_or_insert_with(1, || 0) += 1;
+		assert_eq!(index.get(1), Ok(&1));
+		assert_eq!(index.get(1000), Ok(&42));
+		assert_eq!(
+			index.get(2),
+			Err(SecondaryEntityIndexErrors::IndexDoesNotExist(2))
+		);
+	}
+
+	#[test]
+	fn iter() {
+		let mut index = SecondaryEntityIndex::<u32, usize>::new(usize::MAX);
+		*index.insert_mut(1000).unwrap() = 3;
+		*index.insert_mut(1).unwrap() = 1;
+		*index.insert_mut(300).unwrap() = 2;
+		*index.insert_mut(2).unwrap() = 4;
+		*index.get_mut(300).unwrap() = usize::MAX;
+		assert_eq!(
+			index.iter().collect::<Vec<_>>(),
+			vec![(1, &1), (2, &4), (1000, &3)]
+		);
+	}
+}
@@ -0,0 +1,107 @@
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+/// A component that borrows from some external storage, such as an arena, with the borrow lifetime
+/// erased so it is `'static` and thus can be stored in any table like any other component, including
+/// through `group_insert`/`group_query` on a `DenseEntityDynamicPagedMultiValueTable`.
+///
+/// # Safety
+///
+/// The storage the component borrows from must outlive every table that the component is stored in,
+/// and must not be mutated while the component is stored, this is not checked in any way.
+pub struct UnsafeBorrowedComponent<T: ?Sized + 'static>(NonNull<T>);
+
+impl<T: ?Sized + 'static> UnsafeBorrowedComponent<T> {
+	/// Erases the lifetime of the borrow.
+	///
+	/// # Safety
+	///
+	/// See the safety contract on the type, `value` must outlive every table this is stored in.
+	pub unsafe fn new(value: &T) -> Self {
+		Self(NonNull::from(value))
+	}
+
+	pub fn get(&self) -> &T {
+		// The construction safety contract guarantees the value is still alive
+		unsafe { self.0.as_ref() }
+	}
+}
+
+impl<T: ?Sized + 'static> Clone for UnsafeBorrowedComponent<T> {
+	fn clone(&self) -> Self {
+		Self(self.0)
+	}
+}
+
+impl<T: ?Sized + 'static> Copy for UnsafeBorrowedComponent<T> {}
+
+impl<T: ?Sized + 'static> Deref for UnsafeBorrowedComponent<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+impl<T: ?Sized + Debug + 'static> Debug for UnsafeBorrowedComponent<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("UnsafeBorrowedComponent")
+			.field(&self.get())
+			.finish()
+	}
+}
+
+impl<T: ?Sized + PartialEq + 'static> PartialEq for UnsafeBorrowedComponent<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.get() == other.get()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::database::*;
+	use crate::tables::*;
+	use crate::utils::unsafe_borrowed_component::UnsafeBorrowedComponent;
+	use crate::{tl, TL};
+
+	#[test]
+	fn borrowed_from_arena() {
+		// Declared first so it is dropped last, outliving the database and all of its tables
+		let arena: Vec<String> = (0..4).map(|i| format!("arena: {}", i)).collect();
+		let mut database = Database::new();
+		let entities_storage = database
+			.tables
+			.create("entities", EntityTable::<u64>::builder())
+			.unwrap();
+		let multi_storage = database
+			.tables
+			.create(
+				"multi",
+				DenseEntityDynamicPagedMultiValueTable::<u64>::builder(entities_storage.clone()),
+			)
+			.unwrap();
+		let mut entities = entities_storage.borrow_mut();
+		let mut multi = multi_storage.borrow_mut();
+		let mut inserter = multi
+			.group_insert::<TL![&mut UnsafeBorrowedComponent<str>, &mut usize]>()
+			.unwrap();
+		let mut query = multi
+			.group_query::<TL![&UnsafeBorrowedComponent<str>]>()
+			.unwrap();
+		let entity = entities.insert();
+		inserter
+			.lock(&mut multi)
+			.insert(
+				entity,
+				tl![
+					unsafe { UnsafeBorrowedComponent::new(arena[2].as_str()) },
+					2
+				],
+			)
+			.unwrap();
+		let mut lock = query.lock(&multi);
+		let (name, ()) = lock.get_all(entity).unwrap();
+		assert_eq!(&**name, "arena: 2");
+	}
+}